@@ -0,0 +1,40 @@
+//! Times drawing a large directory, to catch rendering slowing down as features are added.
+//! Run with `cargo bench --bench render`.
+
+use std::{
+    fs::File,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use ratatui::layout::Rect;
+use tempdir::TempDir;
+use tui_file_explorer::App;
+
+const ENTRIES: usize = 10_000;
+const ITERATIONS: u32 = 50;
+
+fn main() {
+    let tmp_dir = TempDir::new("render_bench").unwrap();
+    for index in 0..ENTRIES {
+        File::create(tmp_dir.path().join(format!("file_{index:05}.txt"))).unwrap();
+    }
+    let app = App::new(tmp_dir.path().to_path_buf());
+
+    for (width, height) in [(80, 24), (240, 70)] {
+        let area = Rect::new(0, 0, width, height);
+        // The first draw warms up anything cached
+        black_box(app.render_to_buffer(area));
+
+        let mut total = Duration::ZERO;
+        for _ in 0..ITERATIONS {
+            let start = Instant::now();
+            black_box(app.render_to_buffer(area));
+            total += start.elapsed();
+        }
+        println!(
+            "{ENTRIES} entries at {width}x{height}: {:?} per draw",
+            total / ITERATIONS
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum PatternToken {
+    Literal(String),
+    /// The file name without its extension
+    Name,
+    /// The extension, without the leading `.`
+    Extension,
+    /// The entry's position in the batch, starting at 1 and zero padded to `width`
+    Sequence {
+        width: usize,
+    },
+}
+
+/// A template for new file names, such as `photo_{n:03}.{ext}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenamePattern {
+    tokens: Vec<PatternToken>,
+}
+
+impl RenamePattern {
+    /// Parses `{name}`, `{ext}`, `{n}` and `{n:WIDTH}` tokens, with everything else kept as is
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        if pattern.contains('/') {
+            return Err("Names can't contain /".to_string());
+        }
+
+        let mut tokens = Vec::new();
+        let mut rest = pattern;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                tokens.push(PatternToken::Literal(rest[..start].to_string()));
+            }
+            let Some(length) = rest[start..].find('}') else {
+                return Err(format!("Unclosed {{ in {pattern:?}"));
+            };
+            tokens.push(Self::parse_token(&rest[start + 1..start + length])?);
+            rest = &rest[start + length + 1..];
+        }
+        if !rest.is_empty() {
+            tokens.push(PatternToken::Literal(rest.to_string()));
+        }
+
+        if tokens.is_empty() {
+            return Err("The pattern is empty".to_string());
+        }
+        Ok(RenamePattern { tokens })
+    }
+
+    fn parse_token(token: &str) -> Result<PatternToken, String> {
+        match token.split_once(':') {
+            None if token == "name" => Ok(PatternToken::Name),
+            None if token == "ext" => Ok(PatternToken::Extension),
+            None if token == "n" => Ok(PatternToken::Sequence { width: 0 }),
+            Some(("n", width)) => width
+                .parse()
+                .map(|width| PatternToken::Sequence { width })
+                .map_err(|_| format!("Invalid width in {{{token}}}")),
+            _ => Err(format!("Unknown token {{{token}}}")),
+        }
+    }
+
+    /// The new name for `path` when it is the `sequence_number`th entry renamed
+    pub fn expand(&self, path: &Path, sequence_number: usize) -> String {
+        self.tokens
+            .iter()
+            .map(|token| match token {
+                PatternToken::Literal(text) => text.clone(),
+                PatternToken::Name => path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+                PatternToken::Extension => path
+                    .extension()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+                PatternToken::Sequence { width } => format!("{sequence_number:0width$}"),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Renames each of `paths` within its own directory, numbering them in the order given
+pub fn plan_renames(paths: &[PathBuf], pattern: &RenamePattern) -> Vec<Rename> {
+    paths
+        .iter()
+        .enumerate()
+        .map(|(index, path)| Rename {
+            from: path.clone(),
+            to: path.with_file_name(pattern.expand(path, index + 1)),
+        })
+        .collect()
+}
+
+/// The new names that would clash, either with each other or with an entry that is staying put
+pub fn collisions(renames: &[Rename]) -> HashSet<PathBuf> {
+    let sources: HashSet<&PathBuf> = renames.iter().map(|rename| &rename.from).collect();
+    let mut targets: HashMap<&PathBuf, usize> = HashMap::new();
+    for rename in renames {
+        *targets.entry(&rename.to).or_default() += 1;
+    }
+
+    targets
+        .into_iter()
+        .filter(|(target, count)| {
+            *count > 1 || (!sources.contains(target) && fs::symlink_metadata(target).is_ok())
+        })
+        .map(|(target, _)| target.clone())
+        .collect()
+}
+
+/// Carries out `renames`, going via temporary names so entries can swap or shift names
+pub fn apply_renames(renames: &[Rename]) -> io::Result<()> {
+    let temporary_paths: Vec<PathBuf> = renames
+        .iter()
+        .enumerate()
+        .map(|(index, rename)| {
+            let file_name = rename
+                .from
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            rename
+                .from
+                .with_file_name(format!(".{file_name}.batch-rename-{index}"))
+        })
+        .collect();
+    if let Some(in_the_way) = temporary_paths
+        .iter()
+        .find(|temporary_path| fs::symlink_metadata(temporary_path).is_ok())
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} is in the way", in_the_way.display()),
+        ));
+    }
+
+    for (index, (rename, temporary_path)) in renames.iter().zip(&temporary_paths).enumerate() {
+        if let Err(err) = fs::rename(&rename.from, temporary_path) {
+            return Err(roll_back(
+                &renames[..index],
+                &temporary_paths[..index],
+                0,
+                err,
+            ));
+        }
+    }
+    for (index, (rename, temporary_path)) in renames.iter().zip(&temporary_paths).enumerate() {
+        if let Err(err) = fs::rename(temporary_path, &rename.to) {
+            return Err(roll_back(renames, &temporary_paths, index, err));
+        }
+    }
+    Ok(())
+}
+
+/// Puts the entries back under their old names after `err` stopped a batch, when the first
+/// `finished` had their new names and the rest their temporary ones. The error lists wherever an
+/// entry couldn't be moved back from
+fn roll_back(
+    renames: &[Rename],
+    temporary_paths: &[PathBuf],
+    finished: usize,
+    err: io::Error,
+) -> io::Error {
+    let mut stranded = Vec::new();
+
+    // Every new name is cleared before any old one is reused, as they can overlap
+    let mut movable = vec![true; renames.len()];
+    for (index, rename) in renames.iter().enumerate().take(finished) {
+        if fs::rename(&rename.to, &temporary_paths[index]).is_err() {
+            stranded.push(rename.to.clone());
+            movable[index] = false;
+        }
+    }
+    for (index, rename) in renames.iter().enumerate() {
+        if movable[index] && fs::rename(&temporary_paths[index], &rename.from).is_err() {
+            stranded.push(temporary_paths[index].clone());
+        }
+    }
+
+    if stranded.is_empty() {
+        return err;
+    }
+    let stranded: Vec<String> = stranded
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    io::Error::new(
+        err.kind(),
+        format!("{err}, and couldn't move back {}", stranded.join(", ")),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names
+            .iter()
+            .map(|name| Path::new("/photos").join(name))
+            .collect()
+    }
+
+    #[test]
+    fn pattern_is_expanded_over_each_entry() {
+        let pattern = RenamePattern::parse("photo_{n:03}.{ext}").unwrap();
+
+        assert_eq!(
+            plan_renames(&paths(&["IMG_9.jpg", "IMG_10.png"]), &pattern),
+            vec![
+                Rename {
+                    from: PathBuf::from("/photos/IMG_9.jpg"),
+                    to: PathBuf::from("/photos/photo_001.jpg"),
+                },
+                Rename {
+                    from: PathBuf::from("/photos/IMG_10.png"),
+                    to: PathBuf::from("/photos/photo_002.png"),
+                },
+            ]
+        );
+
+        let pattern = RenamePattern::parse("{n}-{name}.bak").unwrap();
+        assert_eq!(
+            pattern.expand(Path::new("/photos/notes.txt"), 12),
+            "12-notes.bak"
+        );
+    }
+
+    #[test]
+    fn invalid_patterns_are_refused() {
+        assert_eq!(
+            RenamePattern::parse("{size}"),
+            Err("Unknown token {size}".to_string())
+        );
+        assert_eq!(
+            RenamePattern::parse("photo_{n"),
+            Err("Unclosed { in \"photo_{n\"".to_string())
+        );
+        assert!(RenamePattern::parse("{n:wide}").is_err());
+        assert!(RenamePattern::parse("sub/{name}").is_err());
+    }
+
+    #[test]
+    fn duplicate_and_existing_names_collide() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_paths: Vec<PathBuf> = ["a.txt", "b.txt", "c.md", "taken.md"]
+            .iter()
+            .map(|name| tmp_dir.path().join(name))
+            .collect();
+        for file_path in &file_paths {
+            File::create(file_path).unwrap();
+        }
+
+        // Both text files become `same.txt`
+        let pattern = RenamePattern::parse("same.{ext}").unwrap();
+        assert_eq!(
+            collisions(&plan_renames(&file_paths[..2], &pattern)),
+            HashSet::from([tmp_dir.path().join("same.txt")])
+        );
+
+        // `taken.md` isn't being renamed, so keeps its name
+        let pattern = RenamePattern::parse("taken.{ext}").unwrap();
+        assert_eq!(
+            collisions(&plan_renames(&file_paths[2..3], &pattern)),
+            HashSet::from([tmp_dir.path().join("taken.md")])
+        );
+
+        // Names freed up by the batch itself can be reused
+        let pattern = RenamePattern::parse("{name}.{ext}").unwrap();
+        assert!(collisions(&plan_renames(&file_paths, &pattern)).is_empty());
+    }
+
+    #[test]
+    fn entries_can_swap_names() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let a_path = tmp_dir.path().join("a.txt");
+        let b_path = tmp_dir.path().join("b.txt");
+        fs::write(&a_path, "a").unwrap();
+        fs::write(&b_path, "b").unwrap();
+
+        apply_renames(&[
+            Rename {
+                from: a_path.clone(),
+                to: b_path.clone(),
+            },
+            Rename {
+                from: b_path.clone(),
+                to: a_path.clone(),
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(&a_path).unwrap(), "b");
+        assert_eq!(fs::read_to_string(&b_path).unwrap(), "a");
+    }
+
+    #[test]
+    fn failed_batch_is_rolled_back() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let a_path = tmp_dir.path().join("a.txt");
+        let b_path = tmp_dir.path().join("b.txt");
+        let full_dir_path = tmp_dir.path().join("full");
+        fs::write(&a_path, "a").unwrap();
+        fs::write(&b_path, "b").unwrap();
+        fs::create_dir(&full_dir_path).unwrap();
+        File::create(full_dir_path.join("inside.txt")).unwrap();
+
+        // The first rename goes through before the second can't replace a full directory
+        let renames = [
+            Rename {
+                from: b_path.clone(),
+                to: tmp_dir.path().join("c.txt"),
+            },
+            Rename {
+                from: a_path.clone(),
+                to: full_dir_path.clone(),
+            },
+        ];
+        assert!(apply_renames(&renames).is_err());
+
+        let mut names: Vec<_> = fs::read_dir(tmp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["a.txt", "b.txt", "full"]);
+        assert_eq!(fs::read_to_string(&b_path).unwrap(), "b");
+    }
+
+    #[test]
+    fn existing_temporary_name_stops_the_batch() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let a_path = tmp_dir.path().join("a.txt");
+        fs::write(&a_path, "a").unwrap();
+        fs::write(tmp_dir.path().join(".a.txt.batch-rename-0"), "kept").unwrap();
+
+        let err = apply_renames(&[Rename {
+            from: a_path.clone(),
+            to: tmp_dir.path().join("b.txt"),
+        }])
+        .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(a_path.exists());
+        assert_eq!(
+            fs::read_to_string(tmp_dir.path().join(".a.txt.batch-rename-0")).unwrap(),
+            "kept"
+        );
+    }
+}
@@ -0,0 +1,9 @@
+use std::io;
+
+use crossterm::{clipboard::CopyToClipboard, execute};
+
+/// Copies `text` to the system clipboard using the terminal's OSC 52 escape sequence,
+/// which also works over SSH.
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    execute!(io::stdout(), CopyToClipboard::to_clipboard_from(text))
+}
@@ -1,62 +1,509 @@
 use itertools::sorted;
-use std::{io, path::PathBuf};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant, SystemTime},
+};
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::{
+    cursor::MoveTo,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+};
 use ratatui::{
-    DefaultTerminal, Frame,
+    DefaultTerminal, Frame, Terminal,
+    backend::TestBackend,
     buffer::Buffer,
-    layout::{Constraint, Direction, Layout, Rect, Size},
-    style::Stylize,
+    layout::{Constraint, Direction, Flex, Layout, Position, Rect, Size},
+    style::{Style, Stylize},
     symbols::border,
-    text::Line,
+    text::{Line, Span},
     widgets::{Block, Clear, Paragraph, Widget},
 };
 
-use crate::components::{directory_view::DirectoryView, file_view::FileView};
+use crate::{
+    batch_rename::{Rename, RenamePattern, apply_renames, collisions, plan_renames},
+    clipboard::copy_to_clipboard,
+    components::{
+        batch_rename_view::BatchRenameView,
+        cd_prompt::CdPrompt,
+        confirmation::{Confirmation, PendingAction},
+        detail_view::{DetailView, EntryDetails},
+        dir_summary::DirSummary,
+        directory_view::DirectoryView,
+        extension_groups::{ExtensionGroup, collapse_groups, group_consecutive_extensions},
+        favorites_view::{FavoritesView, favorite_label},
+        file_view::{
+            FileView, LoadedText, PreviewContent, PreviewLimits, preview_lines_with_media,
+        },
+        git_diff::unstaged_diff,
+        group_headers::{GroupHeader, group_headers},
+        help_view::HelpView,
+        inline_image::{ImagePlacement, ImageProtocol, MAX_INLINE_IMAGE_BYTES},
+        media::{MediaProbe, ProbeState, get_media_metadata, media_kind, needs_probe},
+        name_prompt::{NamePrompt, NamePurpose, invalid_name_reason},
+        preview_cache::{PreviewCache, PreviewState},
+        recent_dirs_view::RecentDirsView,
+        right_pane::RightPaneMode,
+        search::Search,
+        selection_list_view::SelectionListView,
+        theme::Theme,
+    },
+    config::{Config, DirEnterBehavior, FileOpenAction, IgnoredDirDisplay, UnreadablePreview},
+    display_width::character_count,
+    external_program::{
+        Platform, editor_command, editor_command_at_line, pager_command, reveal_command,
+        system_opener_command,
+    },
+    favorites::Favorites,
+    file_ops::{
+        available_space, copy_destination, copy_recursively, device_id, hard_link_count,
+        insufficient_space_warning, is_mount_point, move_path, read_text_capped,
+        remove_recursively, resolve_parents, swap_paths, total_size,
+    },
+    gitignore_filter::GitignoreFilter,
+    help::{CUSTOM_ACTION_DESCRIPTION, help_lines},
+    human_size::human_size,
+    key_handlers::KeyHandlers,
+    navigation_history::NavigationHistory,
+    paths::{dir_path_text, is_hidden, normalize_dir_path, relative_to},
+    pinned::is_pinned,
+    recent_dirs::RecentDirs,
+    scan::Scan,
+    selection_hook::SelectionHook,
+    selection_list::SelectionList,
+    selection_listeners::{SelectionChange, SelectionListeners},
+    sorting::{Comparator, SortEntry, SortMode},
+    starred::Starred,
+    tree_text::tree_text,
+    view_settings::{DirViewSettings, ViewSettings},
+};
+
+const FAVORITES_WIDTH: u16 = 24;
+const SELECTION_LIST_WIDTH: u16 = 24;
+const READ_ONLY_MESSAGE: &str = "read-only mode";
+
+/// The most of a file that is copied to the clipboard, as terminals limit how much they accept
+const MAX_CLIPBOARD_BYTES: u64 = 1024 * 1024;
+/// Where `S` saves the directories visited this session, in the current directory
+const NAVIGATION_SCRIPT_NAME: &str = "navigation.sh";
+/// How long to wait for input before running background work and redrawing
+const TICK_RATE: Duration = Duration::from_millis(100);
 
 #[derive(Debug, Default)]
 pub struct App {
     exit: bool,
+    /// The directory the app was launched in
+    root: PathBuf,
     current_dir_path: PathBuf,
     current_dir_contents: Vec<PathBuf>,
+    /// The sorted and filtered entries last read from the current directory, before the search
+    /// narrows them, so each key typed into a search doesn't read the directory again
+    unsearched_contents: Vec<PathBuf>,
+    /// Why the current directory couldn't be listed, shown after its path
+    dir_error: Option<String>,
     cursor_positions: Vec<usize>,
     current_cursor_depth: usize,
     view_file: bool,
+    /// Keeps the preview open for whatever is under the cursor, independently of `view_file`
+    auto_preview: bool,
+    /// How many lines the preview of the selected file is scrolled down by
+    file_scroll_offset: usize,
+    /// How many characters of each line of the preview, or of Quick Look, are scrolled out of
+    /// view to the left
+    preview_horizontal_offset: usize,
+    status_message: Option<String>,
+    theme: Theme,
+    config: Config,
+    pending_confirmation: Option<Confirmation>,
+    favorites: Favorites,
+    show_favorites: bool,
+    favorites_focused: bool,
+    favorites_cursor_position: usize,
+    recent_dirs: RecentDirs,
+    navigation_history: NavigationHistory,
+    /// The cursor in the recent directories list, while it is open
+    recent_dirs_cursor_position: Option<usize>,
+    summarize_extensions: bool,
+    extension_groups: Vec<ExtensionGroup>,
+    /// Directories listed with their contents indented beneath them
+    expanded: HashSet<PathBuf>,
+    /// Drawn above each group of the listing, when `group_headers` is on and the sort order groups
+    group_headers: Vec<GroupHeader>,
+    /// Run with the TUI suspended once the current key event has been handled
+    pending_external_command: Option<Command>,
+    /// While multi-selecting, space marks entries instead of opening Quick Look
+    multi_select: bool,
+    marked: HashSet<PathBuf>,
+    /// Where the cursor was when shift was first held to mark a range, until a plain move
+    range_anchor: Option<usize>,
+    starred: Starred,
+    /// Lists only starred entries
+    starred_only: bool,
+    /// How far the Quick Look overlay is scrolled, while it is open
+    quick_look_scroll_offset: Option<usize>,
+    /// The lines of the text file open in Quick Look read so far, which `m` adds to
+    quick_look_text: Option<LoadedText>,
+    /// The lines of the file shown in the right pane read so far, which `m` adds to, read once
+    /// for each selection. `None` alongside the path when it isn't read as text
+    preview_text: Option<(PathBuf, Option<LoadedText>)>,
+    /// The image protocol of the terminal, detected when `run` starts, which image previews use
+    image_protocol: Option<ImageProtocol>,
+    /// The image the terminal last drew over the preview, until something else replaces it
+    shown_image: Option<ImagePlacement>,
+    /// The entry `y` last yanked, which `p` pastes copies of, or `x` last cut
+    yanked: Option<PathBuf>,
+    /// Whether the yanked entry was cut, so that `p` moves it rather than copying it
+    cut: bool,
+    /// How far the help overlay is scrolled, while it is open
+    help_scroll_offset: Option<usize>,
+    selection_hook: Option<SelectionHook>,
+    /// Registered by embedders, and told whenever the entry under the cursor changes
+    selection_listeners: SelectionListeners,
+    /// The entry under the cursor when selection listeners were last told about it
+    last_selected_path: Option<PathBuf>,
+    comparator: Comparator,
+    /// The built-in order the comparator was made from, which `s` cycles through
+    sort_mode: SortMode,
+    /// The comparator was given by an embedder rather than made from `sort_mode`, until `s` is
+    /// pressed
+    custom_order: bool,
+    /// Lists directories above files, each ordered by the comparator, which `g` toggles
+    dirs_first: bool,
+    /// Registered by embedders, and consulted before the built-in keys
+    key_handlers: KeyHandlers,
+    preview_cache: PreviewCache,
+    /// What `ffprobe` found about the audio or video file last previewed, found in the background
+    /// as it can take a while
+    media_probe: MediaProbe,
+    /// Shallow summaries of directories in the current listing, only read once they are selected
+    /// or (with `show_dir_counts`) on screen
+    dir_summaries: HashMap<PathBuf, DirSummary>,
+    /// Shows how many items each directory on screen holds after its name
+    show_dir_counts: bool,
+    /// While collecting, enter over a file adds it to (or removes it from) the selection list
+    collecting: bool,
+    selection_list: SelectionList,
+    /// While open, typed keys go to the prompt rather than the listing
+    cd_prompt: Option<CdPrompt>,
+    /// While open, typed keys go to the name being entered
+    name_prompt: Option<NamePrompt>,
+    /// Pattern typed after `B`, that the marked entries are renamed with
+    batch_rename_pattern: Option<String>,
+    /// While on, `+` and `-` resize the column the cursor is in
+    manual_width_mode: bool,
+    /// How much wider than its automatic width each column is, per directory
+    column_width_adjustments: HashMap<PathBuf, HashMap<usize, i16>>,
+    /// A recursive count of the selected directory, shown in the footer while it is selected
+    scan: Option<Scan>,
+    /// The names being searched for, which `n` and `N` step through once confirmed
+    search: Option<Search>,
+    /// Leaves entries whose names start with a `.` out of the listing
+    hide_dotfiles: bool,
+    /// When auto-refresh last re-read the current directory, or started counting towards it
+    last_auto_refresh: Option<Instant>,
+    /// When the key event being handled was read, which the navigation lock measures from
+    event_time: Option<Instant>,
+    /// When the directory last changed, or a change was last ignored by the navigation lock
+    last_navigation: Option<Instant>,
+    hide_gitignored: bool,
+    /// Listed directories that git ignores, when they are dimmed rather than hidden
+    gitignored_dirs: HashSet<PathBuf>,
+    /// Listed directories that another filesystem is mounted at
+    mount_points: HashSet<PathBuf>,
+    /// How many hard links each listed file with more than one has, with `show_hard_links`
+    hard_links: HashMap<PathBuf, u64>,
+    /// Listed entries that the `pinned` setting keeps at the top
+    pinned_entries: HashSet<PathBuf>,
+    right_pane_mode: RightPaneMode,
+    /// Refuses every action that would change anything on disk
+    read_only: bool,
+    /// Settings chosen in particular directories, restored whenever they are revisited
+    dir_view_settings: DirViewSettings,
+    /// The details of the selected entry, read once each time the selection changes
+    selected_details: Option<EntryDetails>,
+    /// The unstaged changes to the file they were worked out for, once each time `G` is pressed
+    /// or the selection changes while they are shown, as `git diff` can take a while
+    selected_diff: Option<(PathBuf, Option<Vec<String>>)>,
+    /// The listing of the selected directory shown by the peek pane, and how many lines it was
+    /// read for, read once each time the selection changes while it is shown rather than on
+    /// every draw, as a large or remote directory can take a while to list
+    peek_listing: Option<(PathBuf, usize, Vec<String>)>,
+    /// The size of the selected entry when it is a file, read once each time the selection
+    /// changes so that drawing its preview doesn't read the disk
+    selected_file_size: Option<(PathBuf, Option<u64>)>,
+    /// The preview the right pane shows when the selected entry isn't read as text, held back or
+    /// loaded in the background, and how many lines it was read for, read on tick
+    preview_fallback: Option<CachedPreview>,
+    /// The same for Quick Look, when what it has open isn't read as text
+    quick_look_fallback: Option<CachedPreview>,
+    /// The selected entry once `close_unreadable_preview` has decided whether its preview could be
+    /// read, which it does only once for each selection while the preview is open
+    readability_checked: Option<PathBuf>,
+    /// The file over `large_file_threshold` last agreed to be previewed, which other large files
+    /// wait behind a placeholder until they are too
+    large_preview_allowed: Option<PathBuf>,
+}
+
+/// A preview read on tick for `draw` to show, and what it was read for
+#[derive(Debug)]
+struct CachedPreview {
+    path: PathBuf,
+    max_lines: usize,
+    /// Whether the media probe had finished with the file, whose findings the preview then has
+    probed: bool,
+    lines: Vec<String>,
+}
+
+impl CachedPreview {
+    /// Whether `cache` holds `max_lines` lines of `path` read with the probe as it is now
+    fn is_current(cache: &Option<Self>, path: &Path, max_lines: usize, probed: bool) -> bool {
+        cache.as_ref().is_some_and(|cached| {
+            cached.path == path && cached.max_lines == max_lines && cached.probed == probed
+        })
+    }
+
+    /// The `height` lines of `cache` after the first `scroll_offset`, when it holds `path`, or
+    /// none until it is read on tick
+    fn window(
+        cache: &Option<Self>,
+        path: &Path,
+        scroll_offset: usize,
+        height: usize,
+    ) -> Vec<String> {
+        match cache {
+            Some(cached) if cached.path == path => cached
+                .lines
+                .iter()
+                .skip(scroll_offset)
+                .take(height)
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl App {
     pub fn new(current_dir_path: PathBuf) -> Self {
-        let current_dir_contents = sorted(
-            std::fs::read_dir(&current_dir_path)
-                .unwrap()
-                .filter_map(|maybe_dir_entry| {
-                    let dir_entry = maybe_dir_entry.ok()?;
-                    Some(dir_entry.path())
-                }),
+        App::with_stores(
+            current_dir_path,
+            Config::load_default(),
+            Favorites::load_default(),
+            Starred::load_default(),
+            DirViewSettings::load_default(),
         )
-        .collect();
+    }
 
+    /// Like `new`, but with the config and stores given rather than read from the config
+    /// directory, so nothing depends on the user's own settings
+    pub(crate) fn with_stores(
+        current_dir_path: PathBuf,
+        config: Config,
+        favorites: Favorites,
+        starred: Starred,
+        dir_view_settings: DirViewSettings,
+    ) -> Self {
+        let current_dir_path = normalize_dir_path(current_dir_path);
         let current_cursor_depth = current_dir_path.ancestors().count() - 1;
         let cursor_positions = vec![0; current_cursor_depth + 1];
 
-        App {
-            current_dir_contents,
+        let selection_hook = config.selection_hook.clone().map(|hook| {
+            SelectionHook::new(
+                hook,
+                Duration::from_millis(config.selection_hook_debounce_ms),
+            )
+        });
+
+        let mut theme = Theme::from_env();
+        if config.zebra_rows && theme.colors_enabled {
+            theme = theme.with_zebra_rows();
+        }
+        if config.right_to_left {
+            theme = theme.with_right_to_left();
+        }
+        let preview_border_style = match config.preview_border_color {
+            Some(color) if theme.colors_enabled => Style::new().fg(color),
+            _ => Style::new(),
+        };
+        theme = theme.with_preview_border(
+            config.preview_borders,
+            config.preview_border_type,
+            preview_border_style,
+        );
+
+        let mut app = App {
+            root: current_dir_path.clone(),
             current_dir_path,
             cursor_positions,
             current_cursor_depth,
+            theme,
+            favorites,
+            starred,
+            dir_view_settings,
+            comparator: config.sort_mode.comparator(config.sort_tiebreaker),
+            sort_mode: config.sort_mode,
+            config,
+            selection_hook,
             ..Default::default()
-        }
+        };
+        app.restore_view_settings();
+        app.update_current_dir_contents();
+        app.record_visit();
+        app.last_selected_path = app.selected_entry().cloned();
+        app
+    }
+
+    /// Renders everything as plain text, as if `NO_COLOR` were set
+    pub fn without_colors(mut self) -> Self {
+        self.theme = Theme::no_color();
+        self
+    }
+
+    /// Draws everything with `theme` in place of the one picked from the environment and config
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Refuses every action that would change anything on disk, such as deleting or renaming
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Lists entries in the order decided by `compare`, instead of by path
+    pub fn with_comparator(mut self, compare: impl Fn(&Path, &Path) -> Ordering + 'static) -> Self {
+        self.comparator = Comparator::new(compare);
+        self.custom_order = true;
+        self.update_current_dir_contents();
+        self
+    }
+
+    /// Runs `handler` when `key_event` is pressed in the listing, instead of any built-in action
+    /// for that key. Prompts and overlays still take their keys first, and `ctrl+c` always quits
+    pub fn with_key_handler(
+        mut self,
+        key_event: impl Into<KeyEvent>,
+        handler: impl Fn(&mut App) + 'static,
+    ) -> Self {
+        self.key_handlers
+            .register(key_event.into(), CUSTOM_ACTION_DESCRIPTION, handler);
+        self
+    }
+
+    /// Like `with_key_handler`, with `description` listed against the key in the help overlay
+    pub fn with_described_key_handler(
+        mut self,
+        key_event: impl Into<KeyEvent>,
+        description: impl Into<String>,
+        handler: impl Fn(&mut App) + 'static,
+    ) -> Self {
+        self.key_handlers
+            .register(key_event.into(), description, handler);
+        self
+    }
+
+    /// Runs `listener` with the old and new paths whenever the entry under the cursor changes,
+    /// whether from moving the cursor, changing directory or refreshing the listing
+    pub fn with_selection_listener(
+        mut self,
+        listener: impl Fn(&SelectionChange) + 'static,
+    ) -> Self {
+        self.selection_listeners.register(listener);
+        self
+    }
+
+    /// The path of the entry under the cursor, if the directory isn't empty
+    pub fn selected_path(&self) -> Option<&Path> {
+        self.selected_entry().map(PathBuf::as_path)
+    }
+
+    /// Shows `message` in the footer until the next key is pressed
+    pub fn show_status_message(&mut self, message: impl Into<String>) {
+        self.status_message = Some(message.into());
+    }
+
+    /// Moves the cursor onto the entry named `name` in the current listing, returning whether it was found
+    pub fn select_entry_by_name(&mut self, name: &OsStr) -> bool {
+        let Some(position) = self
+            .current_dir_contents
+            .iter()
+            .position(|entry| entry.file_name() == Some(name))
+        else {
+            return false;
+        };
+
+        self.cursor_positions[self.current_cursor_depth] = position;
+        self.notice_selection_change();
+        true
     }
 
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        self.image_protocol = ImageProtocol::from_env();
         while !self.exit {
-            terminal.draw(|frame| self.draw(frame))?;
+            let mut image_placement = None;
+            terminal.draw(|frame| image_placement = self.draw(frame))?;
+            self.show_inline_image(terminal, image_placement)?;
             self.handle_events(terminal.size().unwrap())?;
+            self.on_tick(Instant::now(), terminal.size()?);
+            if let Some(command) = self.pending_external_command.take() {
+                self.run_external_command(terminal, command)?;
+            }
+        }
+        self.dir_view_settings.save()
+    }
+
+    /// Hands the whole terminal over to `command` until it exits
+    fn run_external_command(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        mut command: Command,
+    ) -> io::Result<()> {
+        ratatui::restore();
+        let status = command.status();
+        *terminal = ratatui::init();
+        terminal.clear()?;
+        self.shown_image = None;
+
+        match status {
+            Ok(status) if !status.success() => {
+                self.status_message =
+                    Some(format!("{:?} exited with {status}", command.get_program()));
+            }
+            Err(err) => {
+                self.status_message =
+                    Some(format!("Unable to run {:?}: {err}", command.get_program()));
+            }
+            Ok(_) => {}
         }
         Ok(())
     }
 
-    fn draw(&self, frame: &mut Frame) {
+    /// Draws everything `run` would into a buffer covering `area`, without needing a terminal,
+    /// for tests and benchmarks
+    pub fn render_to_buffer(&self, area: Rect) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(area.width, area.height))
+            .expect("the test backend has nothing to fail on");
+        let mut buffer = terminal
+            .draw(|frame| {
+                self.draw(frame);
+            })
+            .expect("the test backend has nothing to fail on")
+            .buffer
+            .clone();
+        // Drawn at the origin, then moved into place
+        buffer.area = area;
+        buffer
+    }
+
+    /// Draws the app into `frame`, along with where the terminal should draw the previewed image
+    /// over it, when it is to
+    fn draw(&self, frame: &mut Frame) -> Option<ImagePlacement> {
         let area = frame.area();
         frame.render_widget(self, area);
         let column_height = frame.area().height.saturating_sub(3);
@@ -64,11 +511,60 @@ impl App {
         let internal_area = Rect {
             x: area.x + 1,
             y: area.y + 2,
-            width: area.width - 1,
+            width: area.width.saturating_sub(1),
             height: column_height,
         };
 
-        let frame_area = if self.view_file {
+        let internal_area = if let Some(cursor_position) = self.recent_dirs_cursor_position {
+            let [recent_dirs_area, remaining_area] = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(40), Constraint::Fill(1)])
+                .areas(internal_area);
+
+            frame.render_widget(
+                &RecentDirsView::new(self.recent_dirs.paths(), cursor_position),
+                recent_dirs_area,
+            );
+            remaining_area
+        } else if self.show_favorites {
+            let [favorites_area, remaining_area] = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(FAVORITES_WIDTH), Constraint::Fill(1)])
+                .areas(internal_area);
+
+            let cursor_position = self
+                .favorites_focused
+                .then_some(self.favorites_cursor_position);
+            frame.render_widget(
+                &FavoritesView::new(self.favorites.paths(), cursor_position),
+                favorites_area,
+            );
+            remaining_area
+        } else {
+            internal_area
+        };
+
+        let internal_area = if self.collecting {
+            let [remaining_area, selection_list_area] = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Fill(1),
+                    Constraint::Length(SELECTION_LIST_WIDTH),
+                ])
+                .areas(internal_area);
+
+            frame.render_widget(
+                &SelectionListView::new(self.selection_list.paths()),
+                selection_list_area,
+            );
+            remaining_area
+        } else {
+            internal_area
+        };
+
+        let mut image_placement = None;
+        let batch_rename_preview = self.batch_rename_preview();
+        let frame_area = if self.preview_visible() || batch_rename_preview.is_some() {
             Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
@@ -82,517 +578,6617 @@ impl App {
 
         let dir_view_area = *frame_area.first().unwrap();
 
-        let (cursor_column_index, cursor_row_index) =
-            self.current_cursor_column_and_row(column_height as usize);
-        frame.render_widget(
-            &DirectoryView::new(
-                self.current_dir_contents.clone(),
-                cursor_column_index,
-                cursor_row_index,
-            ),
-            dir_view_area,
-        );
+        frame.render_widget(&self.directory_view(column_height), dir_view_area);
+
+        if let Some((renames, collisions)) = &batch_rename_preview {
+            let batch_rename_area = *frame_area.get(1).unwrap();
 
-        if self.view_file {
+            frame.render_widget(Clear, batch_rename_area);
+            frame.render_widget(
+                &BatchRenameView::new(renames, collisions),
+                batch_rename_area,
+            );
+        } else if let Some(selected_path) = self.previewed_path() {
             let file_view_area = *frame_area.get(1).unwrap();
 
             frame.render_widget(Clear, file_view_area);
 
+            match self.right_pane_mode {
+                RightPaneMode::Details => {
+                    frame.render_widget(
+                        &DetailView::new(self.selected_details.as_ref()),
+                        file_view_area,
+                    );
+                }
+                RightPaneMode::Contents if self.shows_inline_image(selected_path) => {
+                    // Left empty for the terminal to draw the image over
+                    let file_view =
+                        FileView::with_contents(selected_path, Vec::new()).with_theme(&self.theme);
+                    image_placement = Some(ImagePlacement {
+                        path: selected_path.clone(),
+                        area: file_view.inner_area(file_view_area),
+                    });
+                    frame.render_widget(&file_view, file_view_area);
+                }
+                RightPaneMode::Peek | RightPaneMode::Diff | RightPaneMode::Contents => {
+                    if let Some(file_view) = self.right_pane_view(selected_path, column_height) {
+                        self.render_file_view(frame, file_view, file_view_area);
+                    }
+                }
+            }
+        }
+
+        if let (Some(scroll_offset), Some(selected_path)) =
+            (self.quick_look_scroll_offset, self.selected_entry())
+        {
+            let quick_look_area = centered_rect(area, 80, 80);
+            let lines = self.quick_look_lines(selected_path, scroll_offset, quick_look_area.height);
+            let file_view = FileView::with_contents(selected_path, lines)
+                .with_horizontal_offset(self.preview_horizontal_offset);
+            let file_view = match &self.quick_look_text {
+                Some(quick_look_text) if !quick_look_text.is_finished() => {
+                    file_view.with_more_after(quick_look_text.lines().len())
+                }
+                _ => file_view,
+            };
+
+            frame.render_widget(Clear, quick_look_area);
+            self.render_file_view(frame, file_view.bordered(), quick_look_area);
+        }
+
+        if let Some(scroll_offset) = self.help_scroll_offset {
+            let help_area = help_area(area);
+            frame.render_widget(Clear, help_area);
             frame.render_widget(
-                &FileView::new(self.currently_selected_file(), column_height as usize),
-                file_view_area,
+                &HelpView::new(&help_lines(&self.key_handlers), scroll_offset),
+                help_area,
             );
         }
+
+        // The borders, titles and side panels have fixed styles of their own
+        if !self.theme.colors_enabled {
+            frame.buffer_mut().set_style(area, Style::reset());
+        }
+        image_placement
     }
 
-    fn handle_events(&mut self, frame_size: Size) -> io::Result<()> {
-        // Blocks until an event is read
-        match event::read()? {
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                self.handle_key_event(key_event, frame_size)
+    /// The lines Quick Look shows of `selected_path`, `height` of them from `scroll_offset` on
+    fn quick_look_lines(
+        &self,
+        selected_path: &Path,
+        scroll_offset: usize,
+        height: u16,
+    ) -> Vec<String> {
+        match &self.quick_look_text {
+            Some(quick_look_text) => quick_look_text.visible_lines(scroll_offset, height as usize),
+            None => CachedPreview::window(
+                &self.quick_look_fallback,
+                selected_path,
+                scroll_offset,
+                height as usize,
+            ),
+        }
+    }
+
+    /// Whether the preview of `file_path` is left for the terminal to draw as an image, which
+    /// nothing may cover
+    fn shows_inline_image(&self, file_path: &Path) -> bool {
+        self.quick_look_scroll_offset.is_none()
+            && self.help_scroll_offset.is_none()
+            && self
+                .image_protocol
+                .is_some_and(|protocol| protocol.supports(file_path))
+            && self
+                .file_size(file_path)
+                .is_some_and(|size| size <= MAX_INLINE_IMAGE_BYTES)
+    }
+
+    /// Has the terminal draw the image of `placement` over the space left for it, after taking
+    /// down the one shown before. Images are sent whole, so only when the placement changes
+    fn show_inline_image(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        placement: Option<ImagePlacement>,
+    ) -> io::Result<()> {
+        let Some(protocol) = self.image_protocol else {
+            return Ok(());
+        };
+        if placement == self.shown_image {
+            return Ok(());
+        }
+
+        if self.shown_image.take().is_some() {
+            write!(terminal.backend_mut(), "{}", protocol.clear_sequence())?;
+            // iTerm2 draws images into the cells themselves, which only a full redraw replaces
+            terminal.clear()?;
+            terminal.draw(|frame| {
+                self.draw(frame);
+            })?;
+        }
+        if let Some(placement) = placement {
+            match std::fs::read(&placement.path) {
+                Ok(image) => {
+                    let backend = terminal.backend_mut();
+                    queue!(backend, MoveTo(placement.area.x, placement.area.y))?;
+                    write!(
+                        backend,
+                        "{}",
+                        protocol.escape_sequence(&image, placement.area)
+                    )?;
+                    backend.flush()?;
+                }
+                Err(err) => self.status_message = Some(format!("Unable to show image: {err}")),
             }
-            _ => {}
+            self.shown_image = Some(placement);
         }
         Ok(())
     }
 
-    fn handle_key_event(&mut self, key_event: KeyEvent, frame_size: Size) {
-        match key_event.code {
-            KeyCode::Char('q') => self.exit(),
-            KeyCode::Down => {
-                self.move_cursor_down();
-            }
-            KeyCode::Up => {
-                self.move_cursor_up();
-            }
-            KeyCode::Right => {
-                self.move_cursor_right(frame_size);
-            }
-            KeyCode::Left => {
-                self.move_cursor_left(frame_size);
-            }
-            KeyCode::Enter if self.currently_on_dir() => {
-                self.go_into_dir();
-            }
-            KeyCode::Backspace => {
-                self.go_out_of_dir();
-            }
-            KeyCode::Char('c') if self.view_file || self.currently_on_file() => {
-                self.view_file();
+    fn directory_view(&self, column_height: u16) -> DirectoryView<'_> {
+        let (cursor_column_index, cursor_row_index) =
+            self.current_cursor_column_and_row(column_height as usize);
+        let directory_view = DirectoryView::new(
+            self.current_dir_contents.clone(),
+            cursor_column_index,
+            cursor_row_index,
+            &self.theme,
+        )
+        .with_extension_groups(&self.extension_groups)
+        .with_group_headers(&self.group_headers)
+        .with_nested_under(&self.current_dir_path)
+        .with_marked(&self.marked)
+        .with_starred(self.starred.paths())
+        .with_dimmed(&self.gitignored_dirs)
+        .with_cut(self.yanked.as_deref().filter(|_| self.cut))
+        .with_mount_points(&self.mount_points)
+        .with_hard_links(&self.hard_links)
+        .with_pinned(&self.pinned_entries)
+        .with_max_columns(self.config.max_columns);
+        let directory_view = match &self.search {
+            Some(search) => directory_view.with_search(search),
+            None => directory_view,
+        };
+        let directory_view = if self.show_dir_counts {
+            directory_view.with_dir_summaries(&self.dir_summaries)
+        } else {
+            directory_view
+        };
+
+        match self.column_width_adjustments.get(&self.current_dir_path) {
+            Some(column_width_adjustments) => {
+                directory_view.with_column_width_adjustments(column_width_adjustments)
             }
-            _ => {}
+            None => directory_view,
         }
     }
 
-    fn currently_on_dir(&self) -> bool {
-        self.current_dir_contents[self.current_cursor_position()].is_dir()
+    /// Describes how the listing is laid out in a frame of `frame_size`, for filing layout bugs
+    fn layout_report(&self, frame_size: Size) -> String {
+        let listing_area = self.frame_listing_area(frame_size);
+        let (columns_needed, columns_that_fit) = self
+            .directory_view(listing_area.height)
+            .column_fit(listing_area);
+
+        format!(
+            "Layout: {columns_that_fit} of {columns_needed} columns fit in {} cells, {} rows per column",
+            listing_area.width, listing_area.height
+        )
     }
 
-    fn currently_on_file(&self) -> bool {
-        self.current_dir_contents[self.current_cursor_position()].is_file()
+    /// Where the listing goes in a frame of `frame_size`
+    fn frame_listing_area(&self, frame_size: Size) -> Rect {
+        self.frame_areas(frame_size).0
     }
 
-    fn currently_selected_file(&self) -> &PathBuf {
-        &self.current_dir_contents[self.current_cursor_position()]
+    /// Where the listing and the right pane go in a frame of `frame_size`
+    fn frame_areas(&self, frame_size: Size) -> (Rect, Rect) {
+        self.listing_and_right_pane_areas(Rect::new(
+            0,
+            0,
+            frame_size.width.saturating_sub(1),
+            frame_size.height.saturating_sub(3),
+        ))
     }
 
-    fn current_cursor_position(&self) -> usize {
-        self.cursor_positions[self.current_cursor_depth]
+    /// The parts of the area inside the border that the listing and the right pane get, once
+    /// side panels have taken their share, in the same way `draw` lays them out. The right pane
+    /// gets no room while nothing is shown beside the listing
+    fn listing_and_right_pane_areas(&self, internal_area: Rect) -> (Rect, Rect) {
+        let mut constraints = Vec::new();
+        if self.recent_dirs_cursor_position.is_some() {
+            constraints.push(Constraint::Percentage(40));
+        } else if self.show_favorites {
+            constraints.push(Constraint::Length(FAVORITES_WIDTH));
+        }
+        let listing_index = constraints.len();
+        constraints.push(Constraint::Fill(1));
+        if self.collecting {
+            constraints.push(Constraint::Length(SELECTION_LIST_WIDTH));
+        }
+        let remaining_area = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(constraints)
+            .split(internal_area)[listing_index];
+
+        if self.preview_visible() || self.batch_rename_preview().is_some() {
+            let [listing_area, right_pane_area] = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(remaining_area);
+            (listing_area, right_pane_area)
+        } else {
+            (remaining_area, Rect::default())
+        }
     }
 
-    fn current_cursor_column_and_row(&self, column_height: usize) -> (usize, usize) {
-        let current_cursor_pos = self.cursor_positions[self.current_cursor_depth];
-        (
-            current_cursor_pos / column_height,
-            current_cursor_pos % column_height,
-        )
+    /// The text the right pane shows for `selected_path` in its current mode, as drawn and as
+    /// copied, or `None` in the details mode, which lays its fields out in a table instead
+    fn right_pane_view(&self, selected_path: &Path, column_height: u16) -> Option<FileView> {
+        let file_view = match self.right_pane_mode {
+            RightPaneMode::Peek => match &self.peek_listing {
+                Some((listing_path, _, listing)) if listing_path == selected_path => {
+                    FileView::with_contents(
+                        selected_path,
+                        listing
+                            .iter()
+                            .take(column_height as usize)
+                            .cloned()
+                            .collect(),
+                    )
+                }
+                _ => FileView::with_contents(selected_path, Vec::new()),
+            },
+            RightPaneMode::Diff => match &self.selected_diff {
+                Some((diff_path, Some(diff))) if diff_path == selected_path => {
+                    FileView::with_contents(selected_path, diff.clone())
+                        .with_diff_styling(&self.theme)
+                }
+                _ => self.contents_view(selected_path, column_height),
+            },
+            RightPaneMode::Contents => self.contents_view(selected_path, column_height),
+            RightPaneMode::Details => return None,
+        };
+        Some(file_view)
     }
 
-    fn exit(&mut self) {
-        self.exit = true;
+    /// The preview of `selected_path`, preferring the selection hook's output when there is one
+    fn contents_view(&self, selected_path: &Path, column_height: u16) -> FileView {
+        let hook_output = self
+            .selection_hook
+            .as_ref()
+            .and_then(|selection_hook| selection_hook.output_for(selected_path));
+        let file_view = if let Some(hook_output) = hook_output {
+            FileView::with_contents(selected_path, hook_output.to_vec())
+        } else if let Some(size) = self.held_back_size(selected_path) {
+            FileView::with_contents(
+                selected_path,
+                vec![format!(
+                    "This file is {} — press c to preview it",
+                    human_size(size, self.config.size_units)
+                )],
+            )
+        } else if self.previews_in_background(selected_path) {
+            match self.preview_cache.state_for(selected_path) {
+                Some(PreviewState::Ready(contents)) => {
+                    FileView::with_contents(selected_path, contents.clone().into_lines())
+                }
+                _ => FileView::with_contents(selected_path, vec!["Loading preview…".to_string()]),
+            }
+        } else if let Some(preview_text) = self.selected_preview_text() {
+            let lines = preview_text.visible_lines(self.file_scroll_offset, column_height as usize);
+            let file_view = FileView::with_contents(selected_path, lines);
+            if preview_text.is_finished() {
+                file_view
+            } else {
+                file_view.with_more_after(preview_text.lines().len())
+            }
+        } else {
+            FileView::with_contents(
+                selected_path,
+                CachedPreview::window(
+                    &self.preview_fallback,
+                    selected_path,
+                    self.file_scroll_offset,
+                    column_height as usize,
+                ),
+            )
+        };
+
+        file_view.with_horizontal_offset(self.preview_horizontal_offset)
     }
 
-    fn view_file(&mut self) {
-        self.view_file = !self.view_file;
+    /// The preview of `file_path`, with what's shown for a file that can't be read decided by
+    /// `unreadable_preview`
+    fn file_preview_lines(&self, file_path: &Path, max_lines: usize) -> Vec<String> {
+        match self.preview_content(file_path, max_lines) {
+            PreviewContent::Unreadable(reason)
+                if self.config.unreadable_preview == UnreadablePreview::Metadata =>
+            {
+                match EntryDetails::of(file_path, self.config.size_units) {
+                    Ok(details) => details
+                        .fields()
+                        .iter()
+                        .map(|(label, value)| format!("{label}: {value}"))
+                        .collect(),
+                    Err(_) => vec![reason],
+                }
+            }
+            preview_content => preview_content.into_lines(),
+        }
     }
 
-    fn move_cursor_up(&mut self) {
-        if self.current_cursor_position() == 0 {
-            self.cursor_positions[self.current_cursor_depth] = self.current_dir_contents.len() - 1;
-        } else {
-            self.cursor_positions[self.current_cursor_depth] -= 1;
+    /// Every preview is drawn through here, so they all share the theme's preview border
+    fn render_file_view(&self, frame: &mut Frame, file_view: FileView, area: Rect) {
+        frame.render_widget(&file_view.with_theme(&self.theme), area);
+    }
+
+    /// Tells selection listeners if the entry under the cursor has changed since they were last
+    /// told, called after anything that can move it so each change is heard about on its own
+    fn notice_selection_change(&mut self) {
+        let selected_path = self.selected_entry().cloned();
+        // Also read again after a reload, which forgets it as the file may have changed
+        if self
+            .selected_file_size
+            .as_ref()
+            .map(|(size_path, _)| size_path)
+            != selected_path.as_ref()
+        {
+            self.selected_file_size = selected_path
+                .as_ref()
+                .map(|selected_path| (selected_path.clone(), file_size_on_disk(selected_path)));
+        }
+        if selected_path != self.last_selected_path {
+            self.file_scroll_offset = 0;
+            self.preview_horizontal_offset = 0;
+            // Only a refresh moves the selection under Quick Look, which then shows the new entry
+            // from its top rather than the text read from the old one
+            if self.quick_look_scroll_offset.is_some() {
+                self.quick_look_scroll_offset = Some(0);
+                self.quick_look_text = None;
+            }
+            let old_path = std::mem::replace(&mut self.last_selected_path, selected_path.clone());
+            self.selection_listeners.notify(&SelectionChange {
+                old_path,
+                new_path: selected_path,
+            });
         }
     }
 
-    fn move_cursor_down(&mut self) {
-        if self.current_cursor_position() == self.current_dir_contents.len() - 1 {
-            self.cursor_positions[self.current_cursor_depth] = 0;
-        } else {
-            self.cursor_positions[self.current_cursor_depth] += 1;
+    fn on_tick(&mut self, now: Instant, frame_size: Size) {
+        // Background work, like a refresh, can move the selection between key presses
+        self.notice_selection_change();
+        let selected_path = self.selected_entry().cloned();
+        if let Some(selection_hook) = &mut self.selection_hook {
+            selection_hook.update(selected_path.as_deref(), now);
+        }
+
+        if let Some(selected_path) = selected_path
+            && self.preview_visible()
+            && self.right_pane_mode == RightPaneMode::Contents
+            && self.held_back_size(&selected_path).is_none()
+            && self.previews_in_background(&selected_path)
+        {
+            let column_height = frame_size.height.saturating_sub(3) as usize;
+            self.preview_cache
+                .request(&selected_path, column_height, self.preview_limits());
+        }
+        self.preview_cache.poll();
+        self.probe_selected_media();
+        if self.preview_visible() && self.right_pane_mode == RightPaneMode::Details {
+            self.refresh_selected_details();
         }
+        if self.preview_visible() && self.right_pane_mode == RightPaneMode::Diff {
+            self.refresh_selected_diff();
+        }
+        self.refresh_peek_listing(frame_size);
+        self.refresh_preview_text();
+        self.refresh_preview_fallbacks(frame_size);
+        if let Some(scan) = &mut self.scan {
+            scan.poll();
+        }
+        self.auto_refresh(now);
+        self.expire_confirmation(now);
+        self.close_unreadable_preview(frame_size);
+        self.summarize_shown_dirs(frame_size);
     }
 
-    fn move_cursor_right(&mut self, frame_size: Size) {
-        let column_height = frame_size.height.saturating_sub(3) as usize;
-        let number_of_columns = self
-            .current_dir_contents
-            .iter()
-            .len()
-            .div_ceil(column_height);
+    /// Cancels a prompt once it has gone unanswered for `confirmation_timeout_secs`, so a key
+    /// pressed much later can't confirm it
+    fn expire_confirmation(&mut self, now: Instant) {
+        let timeout = Duration::from_secs(self.config.confirmation_timeout_secs);
+        let Some(confirmation) = self
+            .pending_confirmation
+            .as_mut()
+            .filter(|_| !timeout.is_zero())
+        else {
+            return;
+        };
 
-        let new_position = self.current_cursor_position() + column_height;
+        let shown_since = *confirmation.shown_since.get_or_insert(now);
+        if now.duration_since(shown_since) >= timeout {
+            self.pending_confirmation = None;
+            self.status_message = Some("Cancelled after no answer".to_string());
+        }
+    }
 
-        if new_position < self.current_dir_contents.len() {
-            // Can move cursor directly right
-            self.cursor_positions[self.current_cursor_depth] = new_position;
-        } else if new_position < column_height * number_of_columns {
-            // Moving cursor into last column, but no entry directly right, so go to lowest entry in column instead
-            self.cursor_positions[self.current_cursor_depth] = self.current_dir_contents.len() - 1;
+    /// Closes the preview once it is showing a file it can't read, when `unreadable_preview` is
+    /// `close`. Auto-preview is left open, as it would only open again on the next entry
+    fn close_unreadable_preview(&mut self, frame_size: Size) {
+        if self.config.unreadable_preview != UnreadablePreview::Close
+            || !self.view_file
+            || self.right_pane_mode != RightPaneMode::Contents
+        {
+            self.readability_checked = None;
+            return;
+        }
+        let Some(selected_path) = self.selected_entry().cloned() else {
+            return;
+        };
+        if self.readability_checked.as_ref() == Some(&selected_path) {
+            return;
+        }
+
+        let hook_output = self
+            .selection_hook
+            .as_ref()
+            .and_then(|selection_hook| selection_hook.output_for(&selected_path));
+        let reason = if hook_output.is_some() || self.held_back_size(&selected_path).is_some() {
+            // The hook's output or the placeholder is shown in place of the preview
+            None
+        } else if self.previews_in_background(&selected_path) {
+            match self.preview_cache.state_for(&selected_path) {
+                Some(PreviewState::Ready(PreviewContent::Unreadable(reason))) => {
+                    Some(reason.clone())
+                }
+                Some(PreviewState::Ready(_)) => None,
+                // Decided once the background preview has loaded
+                _ => return,
+            }
+        } else if media_kind(&selected_path).is_some_and(needs_probe)
+            && !matches!(
+                self.media_probe.state_for(&selected_path),
+                Some(ProbeState::Ready(_))
+            )
+        {
+            // Decided once the probe has finished, as the file is read instead if it fails
+            return;
         } else {
-            // Wrap arround from the last column to the first column
-            self.cursor_positions[self.current_cursor_depth] = new_position % number_of_columns;
+            let column_height = frame_size.height.saturating_sub(3) as usize;
+            match self.preview_content(&selected_path, column_height) {
+                PreviewContent::Unreadable(reason) => Some(reason),
+                _ => None,
+            }
+        };
+
+        self.readability_checked = Some(selected_path);
+        if let Some(reason) = reason {
+            self.view_file = false;
+            self.status_message = Some(format!("Closed the preview: {reason}"));
         }
     }
 
-    fn move_cursor_left(&mut self, frame_size: Size) {
-        let column_height = frame_size.height.saturating_sub(3) as usize;
-        let number_of_columns = self
-            .current_dir_contents
-            .iter()
-            .len()
-            .div_ceil(column_height);
+    /// Re-reads the current directory once every `auto_refresh_secs`, when that is set
+    fn auto_refresh(&mut self, now: Instant) {
+        if self.config.auto_refresh_secs == 0 {
+            return;
+        }
+
+        let interval = Duration::from_secs(self.config.auto_refresh_secs);
+        match self.last_auto_refresh {
+            Some(last_auto_refresh) if now.duration_since(last_auto_refresh) < interval => {}
+            Some(_) => {
+                // Left as it was if the directory has since been removed
+                if self.current_dir_path.is_dir() {
+                    self.refresh_keeping_selection();
+                    // The overlays and preview scroll follow whatever the refresh left selected
+                    self.notice_selection_change();
+                    if self.selected_entry().is_none() {
+                        self.close_quick_look();
+                    }
+                }
+                self.last_auto_refresh = Some(now);
+            }
+            None => self.last_auto_refresh = Some(now),
+        }
+    }
+
+    /// Summarizes the selected directory, and every directory on screen while counts are shown,
+    /// leaving the rest of a large listing unread until it scrolls into view
+    fn summarize_shown_dirs(&mut self, frame_size: Size) {
+        let mut shown_entries: Vec<PathBuf> = self.selected_entry().cloned().into_iter().collect();
+        if self.show_dir_counts {
+            let listing_area = self.frame_listing_area(frame_size);
+            shown_entries.extend(
+                self.directory_view(listing_area.height)
+                    .visible_entries(listing_area),
+            );
+        }
 
-        let new_position = self
-            .current_cursor_position()
-            .checked_sub(column_height)
-            .unwrap_or((number_of_columns - 1) * column_height + self.current_cursor_position());
+        for entity in shown_entries {
+            if !self.dir_summaries.contains_key(&entity) && entity.is_dir() {
+                let dir_summary = DirSummary::of(&entity);
+                self.dir_summaries.insert(entity, dir_summary);
+            }
+        }
+    }
+
+    /// Reads the first chunk of the file shown in the right pane, unless it was already read for
+    /// that file
+    fn refresh_preview_text(&mut self) {
+        let Some(selected_path) = self
+            .selected_entry()
+            .filter(|_| self.preview_scrollable())
+            .filter(|selected_path| self.held_back_size(selected_path).is_none())
+            .cloned()
+        else {
+            self.preview_text = None;
+            return;
+        };
+        if self
+            .preview_text
+            .as_ref()
+            .is_none_or(|(text_path, _)| *text_path != selected_path)
+        {
+            let preview_text = LoadedText::load(
+                &selected_path,
+                self.config.preview_chunk_lines,
+                self.preview_limits(),
+            );
+            self.preview_text = Some((selected_path, preview_text));
+        }
+    }
 
-        if new_position >= self.current_dir_contents.len() {
-            // Wrapped around to last column, but no entry in last column at this row, so go to lowest entry instead
-            self.cursor_positions[self.current_cursor_depth] = self.current_dir_contents.len();
+    /// The lines read so far of the text file shown in the right pane
+    fn selected_preview_text(&self) -> Option<&LoadedText> {
+        let (text_path, preview_text) = self.preview_text.as_ref()?;
+        if Some(text_path) == self.selected_entry() {
+            preview_text.as_ref()
         } else {
-            self.cursor_positions[self.current_cursor_depth] = new_position
+            None
         }
     }
 
-    fn go_into_dir(&mut self) {
-        self.current_dir_path
-            .push(&self.current_dir_contents[self.current_cursor_position()]);
-        self.update_current_dir_contents();
-        self.current_cursor_depth += 1;
-        if self.current_cursor_depth >= self.cursor_positions.len() {
-            self.cursor_positions.push(0);
+    /// Reads the next chunk of the text file shown in the right pane
+    fn load_more_preview_text(&mut self) {
+        self.refresh_preview_text();
+        let (chunk_lines, limits) = (self.config.preview_chunk_lines, self.preview_limits());
+        if let Some((_, Some(preview_text))) = &mut self.preview_text
+            && let Err(err) = preview_text.load_more(chunk_lines, limits)
+        {
+            self.status_message = Some(format!("Unable to read more: {err}"));
         }
     }
 
-    fn go_out_of_dir(&mut self) {
-        self.current_dir_path.pop();
-        self.update_current_dir_contents();
-        self.current_cursor_depth -= 1;
-        self.cursor_positions.pop();
+    /// Runs `git diff` on the selected entry, unless it was already for that entry
+    fn refresh_selected_diff(&mut self) {
+        let Some(selected_path) = self.selected_entry() else {
+            self.selected_diff = None;
+            return;
+        };
+        if self
+            .selected_diff
+            .as_ref()
+            .is_none_or(|(diff_path, _)| diff_path != selected_path)
+        {
+            let diff = unstaged_diff(selected_path);
+            self.selected_diff = Some((selected_path.clone(), diff));
+        }
     }
 
-    fn update_current_dir_contents(&mut self) {
-        self.current_dir_contents = sorted(
-            std::fs::read_dir(&self.current_dir_path)
-                .unwrap()
-                .filter_map(|maybe_dir_entry| {
-                    let dir_entry = maybe_dir_entry.ok()?;
-                    Some(dir_entry.path())
-                }),
-        )
-        .collect();
+    /// Lists the selected directory for the peek pane while it is shown, unless it was already
+    /// listed for that entry at the frame's height
+    fn refresh_peek_listing(&mut self, frame_size: Size) {
+        let Some(selected_path) = self
+            .selected_entry()
+            .filter(|_| self.preview_visible() && self.right_pane_mode == RightPaneMode::Peek)
+        else {
+            return;
+        };
+        let max_lines = frame_size.height.saturating_sub(3) as usize;
+        if self
+            .peek_listing
+            .as_ref()
+            .is_none_or(|(listing_path, listing_lines, _)| {
+                listing_path != selected_path || *listing_lines != max_lines
+            })
+        {
+            let listing = match self.preview_content(selected_path, max_lines) {
+                PreviewContent::Directory(listing) => listing,
+                _ => vec!["Not a directory".to_string()],
+            };
+            self.peek_listing = Some((selected_path.clone(), max_lines, listing));
+        }
     }
-}
 
-impl Widget for &App {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        let title = Line::from(" TUI File Explorer ".bold());
-        let dir_line = Line::from(self.current_dir_path.to_str().unwrap());
+    /// Reads the preview the right pane and Quick Look show of the selected entry when it isn't
+    /// read as text, unless it was already read for that entry at the lines they show
+    fn refresh_preview_fallbacks(&mut self, frame_size: Size) {
+        let Some(selected_path) = self.selected_entry().cloned() else {
+            return;
+        };
+        // Audio and video previews are read again once the probe has finished with them
+        let probed = matches!(
+            self.media_probe.state_for(&selected_path),
+            Some(ProbeState::Ready(_))
+        );
 
-        let block = Block::bordered()
-            .title(title.centered())
-            .border_set(border::THICK);
+        let shows_diff = matches!(
+            &self.selected_diff,
+            Some((diff_path, Some(_))) if *diff_path == selected_path
+        );
+        let right_pane_falls_back = self.preview_visible()
+            && match self.right_pane_mode {
+                RightPaneMode::Contents => !self.shows_inline_image(&selected_path),
+                RightPaneMode::Diff => !shows_diff,
+                RightPaneMode::Peek | RightPaneMode::Details => false,
+            }
+            && self
+                .selection_hook
+                .as_ref()
+                .and_then(|selection_hook| selection_hook.output_for(&selected_path))
+                .is_none()
+            && self.held_back_size(&selected_path).is_none()
+            && !self.previews_in_background(&selected_path)
+            && self.selected_preview_text().is_none();
+        if right_pane_falls_back {
+            let max_lines = self.file_scroll_offset + frame_size.height.saturating_sub(3) as usize;
+            if !CachedPreview::is_current(&self.preview_fallback, &selected_path, max_lines, probed)
+            {
+                self.preview_fallback = Some(CachedPreview {
+                    path: selected_path.clone(),
+                    max_lines,
+                    probed,
+                    lines: self.file_preview_lines(&selected_path, max_lines),
+                });
+            }
+        }
 
-        Paragraph::new(dir_line)
-            .left_aligned()
-            .block(block)
-            .render(area, buf);
+        if let Some(scroll_offset) = self.quick_look_scroll_offset
+            && self.quick_look_text.is_none()
+        {
+            let height = centered_rect(Rect::from((Position::ORIGIN, frame_size)), 80, 80).height;
+            let max_lines = scroll_offset + height as usize;
+            if !CachedPreview::is_current(
+                &self.quick_look_fallback,
+                &selected_path,
+                max_lines,
+                probed,
+            ) {
+                self.quick_look_fallback = Some(CachedPreview {
+                    lines: self.preview_content(&selected_path, max_lines).into_lines(),
+                    path: selected_path,
+                    max_lines,
+                    probed,
+                });
+            }
+        }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use std::fs::{File, create_dir};
+    fn refresh_selected_details(&mut self) {
+        let selected_path = self.selected_entry();
+        let is_current = match (&self.selected_details, selected_path) {
+            (Some(details), Some(selected_path)) => &details.path == selected_path,
+            (None, None) => true,
+            _ => false,
+        };
+        if !is_current {
+            self.selected_details = selected_path.and_then(|selected_path| {
+                EntryDetails::of(selected_path, self.config.size_units).ok()
+            });
+        }
+    }
 
-    use ratatui::style::Style;
-    use tempdir::TempDir;
+    fn preview_limits(&self) -> PreviewLimits {
+        PreviewLimits {
+            max_line_bytes: self.config.preview_max_line_bytes,
+            max_total_bytes: self.config.preview_max_bytes,
+        }
+    }
+
+    /// The preview of `path`, `max_lines` long at most, describing audio and video with whatever
+    /// the background probe has found so far rather than waiting on `ffprobe`
+    fn preview_content(&self, path: &Path, max_lines: usize) -> PreviewContent {
+        preview_lines_with_media(path, max_lines, self.preview_limits(), |path, kind| {
+            if !needs_probe(kind) {
+                return Some(get_media_metadata(path, kind));
+            }
+            match self.media_probe.state_for(path) {
+                Some(ProbeState::Ready(metadata)) => Some(metadata.clone()),
+                _ => None,
+            }
+        })
+    }
+
+    /// The `height` lines of the preview of `path` after the first `scroll_offset`
+    fn preview_window(&self, path: &Path, scroll_offset: usize, height: usize) -> Vec<String> {
+        self.preview_content(path, scroll_offset + height)
+            .into_lines()
+            .into_iter()
+            .skip(scroll_offset)
+            .collect()
+    }
+
+    /// Starts probing the selected audio or video file while its preview, or Quick Look, is open
+    fn probe_selected_media(&mut self) {
+        if let Some(selected_path) = self.selected_entry()
+            && (self.preview_visible() || self.quick_look_scroll_offset.is_some())
+            && media_kind(selected_path).is_some_and(needs_probe)
+        {
+            let selected_path = selected_path.clone();
+            self.media_probe.request(&selected_path);
+        }
+        self.media_probe.poll();
+    }
+
+    /// The size of `file_path` when it is over `large_file_threshold` and hasn't been agreed to,
+    /// so the right pane shows a placeholder rather than reading it
+    fn held_back_size(&self, file_path: &Path) -> Option<u64> {
+        if self.large_preview_allowed.as_deref() == Some(file_path) {
+            return None;
+        }
+        self.file_size(file_path)
+            .filter(|&size| size > self.config.large_file_threshold)
+    }
+
+    fn previews_in_background(&self, file_path: &Path) -> bool {
+        self.file_size(file_path)
+            .is_some_and(|size| size >= self.config.background_preview_min_bytes)
+    }
+
+    /// The size of `file_path` when it is a file, kept for the selected entry rather than read
+    /// again on every draw
+    fn file_size(&self, file_path: &Path) -> Option<u64> {
+        match &self.selected_file_size {
+            Some((size_path, size)) if size_path == file_path => *size,
+            _ => file_size_on_disk(file_path),
+        }
+    }
+
+    fn handle_events(&mut self, frame_size: Size) -> io::Result<()> {
+        // Wait a short while for an event, so background work still gets a chance to run
+        if !event::poll(TICK_RATE)? {
+            return Ok(());
+        }
+
+        match event::read()? {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                self.event_time = Some(Instant::now());
+                self.handle_key_event(key_event, frame_size)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent, frame_size: Size) {
+        self.dispatch_key_event(key_event, frame_size);
+        self.notice_selection_change();
+    }
+
+    fn dispatch_key_event(&mut self, key_event: KeyEvent, frame_size: Size) {
+        self.status_message = None;
+
+        // Ctrl-C quits no matter what else is going on
+        if key_event.code == KeyCode::Char('c')
+            && key_event.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.exit();
+            return;
+        }
+
+        // Any key other than `y` cancels a pending confirmation
+        if let Some(confirmation) = self.pending_confirmation.take() {
+            if key_event.code == KeyCode::Char('y') {
+                self.run_pending_action(confirmation.action);
+            }
+            return;
+        }
+
+        if let Some(cd_prompt) = self.cd_prompt.take() {
+            self.handle_cd_prompt_key_event(key_event, cd_prompt);
+            return;
+        }
+
+        if let Some(search) = self.search.take_if(|search| search.editing) {
+            self.handle_search_key_event(key_event, search);
+            return;
+        }
+
+        if let Some(name_prompt) = self.name_prompt.take() {
+            self.handle_name_prompt_key_event(key_event, name_prompt);
+            return;
+        }
+
+        if let Some(pattern) = self.batch_rename_pattern.take() {
+            self.handle_batch_rename_key_event(key_event, pattern);
+            return;
+        }
+
+        if let Some(scroll_offset) = self.quick_look_scroll_offset.take() {
+            self.handle_quick_look_key_event(key_event, scroll_offset, frame_size);
+            return;
+        }
+
+        if let Some(scroll_offset) = self.help_scroll_offset.take() {
+            self.handle_help_key_event(key_event, scroll_offset, frame_size);
+            return;
+        }
+
+        if let Some(cursor_position) = self.recent_dirs_cursor_position.take() {
+            self.handle_recent_dirs_key_event(key_event, cursor_position);
+            return;
+        }
+
+        if self.favorites_focused {
+            self.handle_favorites_key_event(key_event);
+            return;
+        }
+
+        if let Some(handler) = self.key_handlers.get(&key_event) {
+            handler(self);
+            return;
+        }
+
+        match key_event.code {
+            KeyCode::Char('r')
+                if key_event.modifiers.contains(KeyModifiers::CONTROL)
+                    && !self.current_dir_contents.is_empty() =>
+            {
+                self.copy_relative_path();
+            }
+            KeyCode::Char('y')
+                if key_event.modifiers.contains(KeyModifiers::CONTROL)
+                    && !self.current_dir_contents.is_empty()
+                    && self.currently_on_file() =>
+            {
+                self.copy_file_contents();
+            }
+            KeyCode::Char('y')
+                if !key_event.modifiers.contains(KeyModifiers::CONTROL)
+                    && !self.current_dir_contents.is_empty() =>
+            {
+                let selected = self.currently_selected_file().clone();
+                self.status_message = Some(format!("Yanked {}", favorite_label(&selected)));
+                self.yanked = Some(selected);
+                self.cut = false;
+            }
+            KeyCode::Char('x') if !self.current_dir_contents.is_empty() => {
+                self.cut_selected();
+            }
+            KeyCode::Char('p') => {
+                self.paste_yanked();
+            }
+            KeyCode::Char('X') if !self.current_dir_contents.is_empty() => {
+                self.swap_with_yanked();
+            }
+            KeyCode::Char('t') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.copy_tree();
+            }
+            KeyCode::Char('d') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.copy_current_dir_path();
+            }
+            KeyCode::Char('l') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.copy_listing(frame_size);
+            }
+            KeyCode::Char('q') => self.request_quit(),
+            KeyCode::Char('?') => {
+                self.help_scroll_offset = Some(0);
+            }
+            KeyCode::Down | KeyCode::Up
+                if key_event.modifiers.contains(KeyModifiers::SHIFT)
+                    && !self.current_dir_contents.is_empty() =>
+            {
+                self.extend_marked_range(key_event.code == KeyCode::Up);
+            }
+            KeyCode::Down => {
+                self.range_anchor = None;
+                self.move_cursor_down();
+            }
+            KeyCode::Up => {
+                self.range_anchor = None;
+                self.move_cursor_up();
+            }
+            KeyCode::Left | KeyCode::Right
+                if key_event.modifiers.contains(KeyModifiers::SHIFT)
+                    && self.preview_scrollable() =>
+            {
+                let visible_lines = self.preview_window(
+                    self.currently_selected_file(),
+                    self.file_scroll_offset,
+                    frame_size.height.saturating_sub(3) as usize,
+                );
+                let step = if key_event.code == KeyCode::Left {
+                    -1
+                } else {
+                    1
+                };
+                self.scroll_preview_sideways(step, &visible_lines);
+            }
+            KeyCode::Left if key_event.modifiers.contains(KeyModifiers::ALT) => {
+                self.go_back();
+            }
+            KeyCode::Right if key_event.modifiers.contains(KeyModifiers::ALT) => {
+                self.go_forward();
+            }
+            KeyCode::Left | KeyCode::Right => {
+                self.range_anchor = None;
+                // Columns are laid out from the right edge in right-to-left mode, so each arrow
+                // moves towards the column drawn on its side
+                if (key_event.code == KeyCode::Right) != self.theme.right_to_left {
+                    self.move_cursor_right(frame_size);
+                } else {
+                    self.move_cursor_left(frame_size);
+                }
+            }
+            KeyCode::Char('[') => {
+                self.go_back();
+            }
+            KeyCode::Char(']') => {
+                self.go_forward();
+            }
+            KeyCode::Enter if self.currently_on_collapsed_group().is_some() => {
+                self.expand_selected_group();
+            }
+            KeyCode::Enter if self.currently_on_dir() => match self.config.dir_enter_behavior {
+                DirEnterBehavior::Peek if !self.view_file => {
+                    self.view_file = true;
+                    self.right_pane_mode = RightPaneMode::Peek;
+                    self.refresh_peek_listing(frame_size);
+                }
+                DirEnterBehavior::Peek => {
+                    self.view_file = false;
+                    self.go_into_dir();
+                }
+                DirEnterBehavior::Navigate => {
+                    self.go_into_dir();
+                }
+            },
+            KeyCode::Enter if self.collecting && self.currently_on_file() => {
+                self.toggle_selected_file_in_selection_list();
+            }
+            KeyCode::Enter if self.currently_on_file() => {
+                if let Some(file_enter_action) = self.config.file_enter_action {
+                    self.open_selected_file(file_enter_action);
+                }
+            }
+            KeyCode::Backspace => {
+                self.go_out_of_dir();
+            }
+            KeyCode::Char('c') if self.view_file || self.currently_on_file() => {
+                self.request_preview();
+            }
+            KeyCode::Char('D') => {
+                self.status_message = Some(self.layout_report(frame_size));
+            }
+            KeyCode::Char('e') if self.currently_on_file() => {
+                self.edit_selected_file_at_preview_line();
+            }
+            KeyCode::Char('Y') if self.preview_visible() => {
+                self.copy_visible_preview(frame_size);
+            }
+            KeyCode::Char('F') if self.currently_on_dir() => {
+                self.add_selected_dir_to_favorites();
+            }
+            KeyCode::Char('b') => {
+                self.show_favorites = true;
+                self.favorites_focused = true;
+            }
+            KeyCode::Char('H') if !self.recent_dirs.paths().is_empty() => {
+                self.recent_dirs_cursor_position = Some(0);
+            }
+            KeyCode::Char('z') => {
+                self.toggle_summarize_extensions();
+            }
+            KeyCode::Char('V') => {
+                self.multi_select = !self.multi_select;
+            }
+            KeyCode::Char(' ') if self.multi_select && !self.current_dir_contents.is_empty() => {
+                self.toggle_mark();
+            }
+            KeyCode::Char(' ') if !self.current_dir_contents.is_empty() => {
+                self.quick_look_scroll_offset = Some(0);
+                self.preview_horizontal_offset = 0;
+                self.quick_look_text = LoadedText::load(
+                    self.currently_selected_file(),
+                    self.config.preview_chunk_lines,
+                    self.preview_limits(),
+                );
+            }
+            KeyCode::Char('P') => {
+                let available_space = available_space(&self.current_dir_path).ok();
+                self.request_paste_marked_files(available_space);
+            }
+            KeyCode::Char('M') => {
+                self.request_move_marked_files();
+            }
+            KeyCode::Char('s') => {
+                self.cycle_sort_mode();
+            }
+            KeyCode::Char('g') => {
+                self.dirs_first = !self.dirs_first;
+                self.remember_view_settings();
+                self.sort_contents();
+            }
+            KeyCode::Char('o') if self.currently_on_dir() => {
+                self.toggle_expanded();
+            }
+            KeyCode::Char('O') if !self.current_dir_contents.is_empty() => {
+                self.pending_external_command = Some(reveal_command(
+                    self.currently_selected_file(),
+                    Platform::current(),
+                ));
+            }
+            KeyCode::Char('v') if self.currently_on_file() => {
+                self.open_selected_file(FileOpenAction::Pager);
+            }
+            KeyCode::Char('d') if !self.current_dir_contents.is_empty() => {
+                self.request_delete_selected();
+            }
+            KeyCode::Char('u') if self.currently_on_dir() => {
+                self.scan = Some(Scan::start(self.currently_selected_file().clone()));
+            }
+            KeyCode::Char('I') => {
+                self.toggle_hide_gitignored();
+            }
+            KeyCode::Char('.') => {
+                self.toggle_hide_dotfiles();
+            }
+            KeyCode::Char('/') => {
+                self.search = Some(Search::new());
+            }
+            KeyCode::Char('n') if self.search.is_some() => {
+                self.step_search(false);
+            }
+            KeyCode::Char('N') if self.search.is_some() => {
+                self.step_search(true);
+            }
+            KeyCode::Esc if self.search.is_some() => {
+                self.close_search();
+            }
+            KeyCode::Char('*') if !self.current_dir_contents.is_empty() => {
+                self.toggle_star_on_selected_file();
+            }
+            KeyCode::Char('f') => {
+                self.toggle_starred_only();
+            }
+            KeyCode::Char('#') => {
+                self.show_dir_counts = !self.show_dir_counts;
+                self.summarize_shown_dirs(frame_size);
+            }
+            KeyCode::Char('w') => {
+                self.manual_width_mode = !self.manual_width_mode;
+                if self.manual_width_mode {
+                    self.status_message = Some(
+                        "Manual column widths: + and - resize the cursor's column".to_string(),
+                    );
+                }
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') if self.manual_width_mode => {
+                self.adjust_cursor_column_width(1, frame_size);
+            }
+            KeyCode::Char('-') if self.manual_width_mode => {
+                self.adjust_cursor_column_width(-1, frame_size);
+            }
+            KeyCode::Char('a') => {
+                self.auto_preview = !self.auto_preview;
+            }
+            KeyCode::Char(':') => {
+                self.cd_prompt = Some(CdPrompt::default());
+            }
+            KeyCode::Char('n') => {
+                self.open_name_prompt(NamePrompt::new(NamePurpose::NewFile));
+            }
+            KeyCode::Char('S') => {
+                self.save_navigation_script();
+            }
+            KeyCode::Char('R') if !self.current_dir_contents.is_empty() => {
+                self.open_name_prompt(NamePrompt::rename(self.currently_selected_file().clone()));
+            }
+            KeyCode::Char('B') => {
+                self.start_batch_rename();
+            }
+            KeyCode::Char('C') => {
+                self.collecting = !self.collecting;
+            }
+            KeyCode::Char('i') => {
+                self.show_right_pane(RightPaneMode::Details);
+            }
+            KeyCode::Char('j') if self.preview_scrollable() => {
+                self.scroll_preview_down();
+            }
+            KeyCode::Char('m') if self.preview_scrollable() => {
+                self.load_more_preview_text();
+            }
+            KeyCode::Char('k') if self.preview_scrollable() => {
+                self.file_scroll_offset = self.file_scroll_offset.saturating_sub(1);
+            }
+            KeyCode::Char('G') if self.currently_on_file() => {
+                // Worked out again, as the file may have changed since
+                self.selected_diff = None;
+                self.show_right_pane(RightPaneMode::Diff);
+                self.refresh_selected_diff();
+            }
+            KeyCode::Tab if self.preview_visible() => {
+                self.right_pane_mode = self.right_pane_mode.next();
+                self.refresh_selected_details();
+                self.refresh_peek_listing(frame_size);
+            }
+            KeyCode::Char('E') => {
+                let available_space = available_space(&self.current_dir_path).ok();
+                self.request_export_selection_list(available_space);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_favorites_key_event(&mut self, key_event: KeyEvent) {
+        let number_of_favorites = self.favorites.paths().len();
+
+        match key_event.code {
+            KeyCode::Char('r')
+                if key_event.modifiers.contains(KeyModifiers::CONTROL)
+                    && !self.current_dir_contents.is_empty() =>
+            {
+                self.copy_relative_path();
+            }
+            KeyCode::Char('d') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.copy_current_dir_path();
+            }
+            KeyCode::Char('q') => self.request_quit(),
+            KeyCode::Down if number_of_favorites > 0 => {
+                self.favorites_cursor_position =
+                    (self.favorites_cursor_position + 1) % number_of_favorites;
+            }
+            KeyCode::Up if number_of_favorites > 0 => {
+                self.favorites_cursor_position = self
+                    .favorites_cursor_position
+                    .checked_sub(1)
+                    .unwrap_or(number_of_favorites - 1);
+            }
+            KeyCode::Enter if number_of_favorites > 0 => {
+                let favorite = self.favorites.paths()[self.favorites_cursor_position].clone();
+                self.jump_to_dir(favorite);
+                self.favorites_focused = false;
+            }
+            // Leaves the sidebar open, but returns focus to the listing
+            KeyCode::Esc => {
+                self.favorites_focused = false;
+            }
+            KeyCode::Char('b') => {
+                self.show_favorites = false;
+                self.favorites_focused = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// Keys while Quick Look is open, where the arrows scroll rather than move the cursor
+    fn handle_quick_look_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        scroll_offset: usize,
+        frame_size: Size,
+    ) {
+        // The listing can empty under the overlay, as when a refresh finds the file gone
+        let Some(selected_path) = self.selected_entry().cloned() else {
+            self.close_quick_look();
+            return;
+        };
+        let scroll_offset = match key_event.code {
+            KeyCode::Char(' ') | KeyCode::Esc => {
+                self.close_quick_look();
+                return;
+            }
+            KeyCode::Up => scroll_offset.saturating_sub(1),
+            KeyCode::Left | KeyCode::Right => {
+                let height =
+                    centered_rect(Rect::from((Position::ORIGIN, frame_size)), 80, 80).height;
+                let visible_lines = self.quick_look_lines(&selected_path, scroll_offset, height);
+                let step = if key_event.code == KeyCode::Left {
+                    -1
+                } else {
+                    1
+                };
+                self.scroll_preview_sideways(step, &visible_lines);
+                scroll_offset
+            }
+            KeyCode::Char('m') => {
+                let (chunk_lines, limits) =
+                    (self.config.preview_chunk_lines, self.preview_limits());
+                if let Some(quick_look_text) = &mut self.quick_look_text
+                    && let Err(err) = quick_look_text.load_more(chunk_lines, limits)
+                {
+                    self.status_message = Some(format!("Unable to read more: {err}"));
+                }
+                scroll_offset
+            }
+            // Stops with the last loaded line at the top
+            KeyCode::Down if self.quick_look_text.is_some() => {
+                let lines_loaded = self
+                    .quick_look_text
+                    .as_ref()
+                    .map_or(0, |quick_look_text| quick_look_text.lines().len());
+                if lines_loaded > scroll_offset + 1 {
+                    scroll_offset + 1
+                } else {
+                    scroll_offset
+                }
+            }
+            // Stops with the last line at the top, without reading any more of the file than that
+            KeyCode::Down
+                if self
+                    .preview_content(&selected_path, scroll_offset + 2)
+                    .into_lines()
+                    .len()
+                    > scroll_offset + 1 =>
+            {
+                scroll_offset + 1
+            }
+            _ => scroll_offset,
+        };
+        self.quick_look_scroll_offset = Some(scroll_offset);
+    }
+
+    fn close_quick_look(&mut self) {
+        self.quick_look_scroll_offset = None;
+        self.quick_look_text = None;
+        self.preview_horizontal_offset = 0;
+    }
+
+    /// Keys while the help overlay is open, which scrolls until its last line is in view
+    fn handle_help_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        scroll_offset: usize,
+        frame_size: Size,
+    ) {
+        let visible_lines = help_area(Rect::new(0, 0, frame_size.width, frame_size.height))
+            .height
+            .saturating_sub(2) as usize;
+        let max_scroll_offset = help_lines(&self.key_handlers)
+            .len()
+            .saturating_sub(visible_lines);
+
+        let scroll_offset = match key_event.code {
+            KeyCode::Char('?') | KeyCode::Char('q') | KeyCode::Esc => return,
+            KeyCode::Up => scroll_offset.saturating_sub(1),
+            KeyCode::Down => (scroll_offset + 1).min(max_scroll_offset),
+            _ => scroll_offset,
+        };
+        self.help_scroll_offset = Some(scroll_offset);
+    }
+
+    /// Keys while the recent directories list is open, which closes once one is picked
+    fn handle_recent_dirs_key_event(&mut self, key_event: KeyEvent, cursor_position: usize) {
+        let number_of_recent_dirs = self.recent_dirs.paths().len();
+
+        let cursor_position = match key_event.code {
+            KeyCode::Down => (cursor_position + 1) % number_of_recent_dirs,
+            KeyCode::Up => cursor_position
+                .checked_sub(1)
+                .unwrap_or(number_of_recent_dirs - 1),
+            KeyCode::Enter => {
+                let recent_dir = self.recent_dirs.paths()[cursor_position].clone();
+                self.jump_to_dir(recent_dir);
+                return;
+            }
+            KeyCode::Esc | KeyCode::Char('H') => return,
+            _ => cursor_position,
+        };
+        self.recent_dirs_cursor_position = Some(cursor_position);
+    }
+
+    fn handle_cd_prompt_key_event(&mut self, key_event: KeyEvent, mut cd_prompt: CdPrompt) {
+        match key_event.code {
+            KeyCode::Enter => {
+                self.cd_into_child(&cd_prompt.input);
+                return;
+            }
+            KeyCode::Esc => return,
+            KeyCode::Tab => cd_prompt.complete(&self.subdirectory_names()),
+            KeyCode::Backspace => {
+                cd_prompt.input.pop();
+            }
+            KeyCode::Char(character) => cd_prompt.input.push(character),
+            _ => {}
+        }
+        self.cd_prompt = Some(cd_prompt);
+    }
+
+    /// Keys while the search query is typed, which narrows the listing as it changes and is kept
+    /// once confirmed with `enter`
+    fn handle_search_key_event(&mut self, key_event: KeyEvent, mut search: Search) {
+        match key_event.code {
+            KeyCode::Enter => {
+                search.editing = false;
+                match search.best_match(&self.current_dir_contents) {
+                    Some(position) => {
+                        self.cursor_positions[self.current_cursor_depth] = position;
+                        self.search = Some(search);
+                        self.show_search_position();
+                    }
+                    None => {
+                        self.status_message = Some(format!("No matches for {}", search.query));
+                        self.search = Some(search);
+                    }
+                }
+            }
+            KeyCode::Esc => {
+                self.search = Some(search);
+                self.close_search();
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+                self.search = Some(search);
+                self.lay_out_keeping_selection();
+            }
+            KeyCode::Char(character)
+                if !key_event
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                search.query.push(character);
+                self.search = Some(search);
+                self.lay_out_keeping_selection();
+            }
+            _ => self.search = Some(search),
+        }
+    }
+
+    /// Clears the search, listing everything it hid again
+    fn close_search(&mut self) {
+        if self.search.take().is_some() {
+            self.lay_out_keeping_selection();
+        }
+    }
+
+    /// Lists what was last read again, narrowed by the search as it is now, with the cursor left
+    /// on the same entry if it is still listed
+    fn lay_out_keeping_selection(&mut self) {
+        let selected_entry = self.selected_entry().cloned();
+        self.lay_out_contents();
+        self.select_or_clamp(selected_entry);
+    }
+
+    /// Moves the cursor to the next match of the search, or the previous one going `backwards`
+    fn step_search(&mut self, backwards: bool) {
+        let Some(search) = &self.search else {
+            return;
+        };
+
+        match search.step(
+            &self.current_dir_contents,
+            self.current_cursor_position(),
+            backwards,
+        ) {
+            Some(position) => {
+                self.cursor_positions[self.current_cursor_depth] = position;
+                self.show_search_position();
+            }
+            None => self.status_message = Some(format!("No matches for {}", search.query)),
+        }
+    }
+
+    fn show_search_position(&mut self) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let matches = search.matches(&self.current_dir_contents);
+        if let Some(index) = matches
+            .iter()
+            .position(|&matched| matched == self.current_cursor_position())
+        {
+            self.status_message = Some(format!("Match {} of {}", index + 1, matches.len()));
+        }
+    }
+
+    /// Asks for a name to create or rename an entry with
+    fn open_name_prompt(&mut self, name_prompt: NamePrompt) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        self.name_prompt = Some(name_prompt);
+    }
+
+    fn handle_name_prompt_key_event(&mut self, key_event: KeyEvent, mut name_prompt: NamePrompt) {
+        match key_event.code {
+            KeyCode::Enter => {
+                match &name_prompt.purpose {
+                    NamePurpose::NewFile => self.create_file(name_prompt),
+                    NamePurpose::Rename(path) => self.rename(path.clone(), name_prompt),
+                }
+                return;
+            }
+            KeyCode::Esc => return,
+            KeyCode::Backspace => {
+                name_prompt.input.pop();
+            }
+            KeyCode::Char(character) => name_prompt.input.push(character),
+            _ => {}
+        }
+        self.name_prompt = Some(name_prompt);
+    }
+
+    /// Creates an empty file named by `name_prompt` and selects it, keeping the prompt open to
+    /// try another name if it can't be created
+    fn create_file(&mut self, name_prompt: NamePrompt) {
+        let name = name_prompt.input.as_str();
+        if let Some(reason) = invalid_name_reason(name) {
+            self.status_message = Some(reason.to_string());
+            self.name_prompt = Some(name_prompt);
+            return;
+        }
+
+        // Unlike `File::create`, never truncates a file that already has the name
+        if let Err(err) = std::fs::File::create_new(self.current_dir_path.join(name)) {
+            self.status_message = Some(match err.kind() {
+                io::ErrorKind::AlreadyExists => format!("{name} already exists"),
+                io::ErrorKind::InvalidFilename => format!("{name} is too long or not allowed"),
+                _ => format!("Unable to create {name}: {err}"),
+            });
+            self.name_prompt = Some(name_prompt);
+            return;
+        }
+
+        self.update_current_dir_contents();
+        self.select_entry_by_name(OsStr::new(name));
+        self.status_message = Some(format!("Created {name}"));
+    }
+
+    /// Renames `path` within its directory to the name typed into `name_prompt` and selects it,
+    /// keeping the prompt open to try another name if it can't be renamed
+    fn rename(&mut self, path: PathBuf, name_prompt: NamePrompt) {
+        let name = name_prompt.input.as_str();
+        if let Some(reason) = invalid_name_reason(name) {
+            self.status_message = Some(reason.to_string());
+            self.name_prompt = Some(name_prompt);
+            return;
+        }
+        if path.file_name() == Some(OsStr::new(name)) {
+            return;
+        }
+
+        // `fs::rename` would replace whatever already has the name
+        let new_path = path.with_file_name(name);
+        let result = if new_path.symlink_metadata().is_ok() {
+            Err(io::Error::from(io::ErrorKind::AlreadyExists))
+        } else {
+            std::fs::rename(&path, &new_path)
+        };
+        if let Err(err) = result {
+            self.status_message = Some(match err.kind() {
+                io::ErrorKind::AlreadyExists => format!("{name} already exists"),
+                io::ErrorKind::InvalidFilename => format!("{name} is too long or not allowed"),
+                _ => format!("Unable to rename to {name}: {err}"),
+            });
+            self.name_prompt = Some(name_prompt);
+            return;
+        }
+
+        self.update_current_dir_contents();
+        self.select_entry_by_name(OsStr::new(name));
+        self.status_message = Some(format!("Renamed to {name}"));
+    }
+
+    /// Asks for the pattern to rename the marked entries with
+    fn start_batch_rename(&mut self) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        if self.marked.is_empty() {
+            self.status_message = Some("Nothing is marked".to_string());
+            return;
+        }
+        self.batch_rename_pattern = Some(String::new());
+    }
+
+    fn handle_batch_rename_key_event(&mut self, key_event: KeyEvent, mut pattern: String) {
+        match key_event.code {
+            KeyCode::Enter => {
+                self.request_batch_rename(pattern);
+                return;
+            }
+            KeyCode::Esc => return,
+            KeyCode::Backspace => {
+                pattern.pop();
+            }
+            KeyCode::Char(character) => pattern.push(character),
+            _ => {}
+        }
+        self.batch_rename_pattern = Some(pattern);
+    }
+
+    /// Asks to rename the marked entries with `pattern`, keeping the prompt open if it can't be used
+    fn request_batch_rename(&mut self, pattern: String) {
+        let renames = match RenamePattern::parse(&pattern) {
+            Ok(rename_pattern) => plan_renames(&self.sorted_marked_paths(), &rename_pattern),
+            Err(err) => {
+                self.status_message = Some(err);
+                self.batch_rename_pattern = Some(pattern);
+                return;
+            }
+        };
+
+        let collisions = collisions(&renames);
+        if !collisions.is_empty() {
+            let colliding_names: Vec<String> = sorted(&collisions)
+                .map(|path| favorite_label(path))
+                .collect();
+            self.status_message = Some(format!(
+                "Nothing renamed, these new names collide: {}",
+                colliding_names.join(", ")
+            ));
+            self.batch_rename_pattern = Some(pattern);
+            return;
+        }
+
+        self.request_confirmation(
+            format!("Rename {} marked items?", renames.len()),
+            PendingAction::BatchRename(renames),
+        );
+    }
+
+    fn batch_rename(&mut self, renames: &[Rename]) {
+        // Entries may have appeared since the names were checked
+        self.status_message = Some(if !collisions(renames).is_empty() {
+            "New names collide, nothing renamed".to_string()
+        } else if let Err(err) = apply_renames(renames) {
+            format!("Unable to rename: {err}")
+        } else {
+            self.marked.clear();
+            format!("Renamed {} items", renames.len())
+        });
+        self.update_current_dir_contents();
+        self.clamp_cursor_position();
+    }
+
+    /// The renames shown while typing a batch rename pattern or confirming it, along with their collisions
+    fn batch_rename_preview(&self) -> Option<(Vec<Rename>, HashSet<PathBuf>)> {
+        let renames = match (&self.pending_confirmation, &self.batch_rename_pattern) {
+            (
+                Some(Confirmation {
+                    action: PendingAction::BatchRename(renames),
+                    ..
+                }),
+                _,
+            ) => renames.clone(),
+            (None, Some(pattern)) => {
+                let rename_pattern = RenamePattern::parse(pattern).ok()?;
+                plan_renames(&self.sorted_marked_paths(), &rename_pattern)
+            }
+            _ => return None,
+        };
+        let collisions = collisions(&renames);
+        Some((renames, collisions))
+    }
+
+    fn subdirectory_names(&self) -> Vec<String> {
+        self.current_dir_contents
+            .iter()
+            .filter(|entity| entity.is_dir())
+            .filter_map(|dir_path| dir_path.file_name()?.to_str().map(str::to_string))
+            .collect()
+    }
+
+    /// Descends into the child directory called `name`, like a shell's `cd name`
+    fn cd_into_child(&mut self, name: &str) {
+        let is_child_dir = !name.is_empty()
+            && self.subdirectory_names().iter().any(|child| child == name)
+            && self.select_entry_by_name(OsStr::new(name));
+
+        if is_child_dir {
+            self.go_into_dir();
+        } else {
+            self.status_message = Some(format!("No directory named {name:?}"));
+        }
+    }
+
+    fn currently_on_dir(&self) -> bool {
+        self.selected_entry().is_some_and(|entity| entity.is_dir())
+    }
+
+    fn currently_on_file(&self) -> bool {
+        self.selected_entry().is_some_and(|entity| entity.is_file())
+    }
+
+    fn currently_on_collapsed_group(&self) -> Option<usize> {
+        let selected = self.selected_entry()?;
+        self.extension_groups
+            .iter()
+            .position(|group| !group.expanded && group.representative() == selected)
+    }
+
+    /// A compact token for each active view modifier, e.g. `[f:git][summary]`, shown in the footer
+    fn view_modifiers(&self) -> String {
+        let sort = format!(
+            "[sort:{}{}]",
+            self.sort_mode.label(),
+            self.sort_mode.arrow()
+        );
+        [
+            (self.read_only, "[ro]"),
+            (self.multi_select, "[sel]"),
+            (self.hide_dotfiles, "[.h]"),
+            (self.hide_gitignored, "[f:git]"),
+            (self.starred_only, "[f:star]"),
+            (self.sort_mode != self.config.sort_mode, sort.as_str()),
+            (self.summarize_extensions, "[summary]"),
+            (self.manual_width_mode, "[widths]"),
+            (self.auto_preview, "[auto]"),
+            (self.show_dir_counts, "[counts]"),
+        ]
+        .into_iter()
+        .filter(|(active, _)| *active)
+        .map(|(_, token)| token)
+        .collect()
+    }
+
+    /// The summary to show in the footer while the cursor is on a directory
+    fn selected_dir_summary(&self) -> Option<&DirSummary> {
+        self.dir_summaries.get(self.selected_entry()?)
+    }
+
+    /// The entry under the cursor, or `None` when the listing is empty
+    fn selected_entry(&self) -> Option<&PathBuf> {
+        self.current_dir_contents
+            .get(self.current_cursor_position())
+    }
+
+    /// Only call while something is selected, see `selected_entry`
+    fn currently_selected_file(&self) -> &PathBuf {
+        &self.current_dir_contents[self.current_cursor_position()]
+    }
+
+    fn current_cursor_position(&self) -> usize {
+        self.cursor_positions
+            .get(self.current_cursor_depth)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn current_cursor_column_and_row(&self, column_height: usize) -> (usize, usize) {
+        let current_cursor_row = self.row_of(self.current_cursor_position());
+        let column_height = column_height.max(1);
+        (
+            current_cursor_row / column_height,
+            current_cursor_row % column_height,
+        )
+    }
+
+    /// The rows of the listing that group headers are drawn on, in order
+    fn header_rows(&self) -> Vec<usize> {
+        self.group_headers
+            .iter()
+            .filter_map(|group_header| {
+                self.current_dir_contents
+                    .iter()
+                    .position(|entity| *entity == group_header.first)
+            })
+            .enumerate()
+            .map(|(headers_above, position)| position + headers_above)
+            .collect()
+    }
+
+    /// How many rows the listing takes up, headers included
+    fn number_of_rows(&self) -> usize {
+        self.current_dir_contents.len() + self.header_rows().len()
+    }
+
+    /// The row of the listing the entry at `position` is drawn on, below any headers above it
+    fn row_of(&self, position: usize) -> usize {
+        let mut row = position;
+        for header_row in self.header_rows() {
+            if header_row <= row {
+                row += 1;
+            }
+        }
+        row
+    }
+
+    /// The entry drawn on `row`, or the one just below when `row` is a header's
+    fn position_at_row(&self, row: usize) -> usize {
+        let headers_above = self
+            .header_rows()
+            .into_iter()
+            .filter(|&header_row| header_row < row)
+            .count();
+        (row - headers_above).min(self.current_dir_contents.len().saturating_sub(1))
+    }
+
+    fn exit(&mut self) {
+        self.exit = true;
+    }
+
+    fn preview_visible(&self) -> bool {
+        self.view_file || self.auto_preview
+    }
+
+    /// The entry shown in the preview pane, if it is open
+    fn previewed_path(&self) -> Option<&PathBuf> {
+        if self.preview_visible() {
+            self.selected_entry()
+        } else {
+            None
+        }
+    }
+
+    /// Opens the right pane in `mode`, or closes it if it is already showing that
+    fn show_right_pane(&mut self, mode: RightPaneMode) {
+        if self.view_file && self.right_pane_mode == mode {
+            self.view_file = false;
+        } else {
+            self.view_file = true;
+            self.right_pane_mode = mode;
+            self.refresh_selected_details();
+        }
+    }
+
+    /// Opens (or closes) the preview of the file under the cursor, asking first if the file is
+    /// larger than `large_file_threshold`, including when the open preview is holding it back
+    fn request_preview(&mut self) {
+        let held_back_size = self
+            .selected_entry()
+            .and_then(|selected_entry| self.held_back_size(selected_entry));
+
+        match held_back_size {
+            Some(size) => {
+                self.request_confirmation(
+                    format!(
+                        "This file is {} — preview anyway?",
+                        human_size(size, self.config.size_units)
+                    ),
+                    PendingAction::Preview,
+                );
+            }
+            _ => self.show_right_pane(RightPaneMode::Contents),
+        }
+    }
+
+    fn copy_visible_preview(&mut self, frame_size: Size) {
+        let Some(visible_text) = self.visible_preview_text(frame_size) else {
+            self.status_message = Some("Nothing to copy from this pane".to_string());
+            return;
+        };
+
+        self.status_message = Some(match copy_to_clipboard(&visible_text) {
+            Ok(()) => "Copied preview to clipboard".to_string(),
+            Err(err) => format!("Unable to copy preview: {err}"),
+        });
+    }
+
+    /// The text the right pane shows in a frame of `frame_size`, built from the same view `draw`
+    /// renders, or `None` while it shows something other than text
+    fn visible_preview_text(&self, frame_size: Size) -> Option<String> {
+        let selected_path = self.previewed_path()?;
+        if self.right_pane_mode == RightPaneMode::Contents && self.shows_inline_image(selected_path)
+        {
+            return None;
+        }
+        let column_height = frame_size.height.saturating_sub(3);
+        let file_view = self.right_pane_view(selected_path, column_height)?;
+        Some(
+            file_view
+                .with_theme(&self.theme)
+                .visible_text(self.frame_areas(frame_size).1),
+        )
+    }
+
+    fn copy_file_contents(&mut self) {
+        let file_path = self.currently_selected_file();
+        let label = favorite_label(file_path);
+
+        self.status_message = Some(
+            match read_text_capped(file_path, MAX_CLIPBOARD_BYTES)
+                .and_then(|capped_text| copy_to_clipboard(&capped_text.text).map(|()| capped_text))
+            {
+                Ok(capped_text) if capped_text.truncated => format!(
+                    "Copied only the first {} of {label}",
+                    human_size(MAX_CLIPBOARD_BYTES, self.config.size_units)
+                ),
+                Ok(_) => format!("Copied the contents of {label}"),
+                Err(err) => format!("Unable to copy {label}: {err}"),
+            },
+        );
+    }
+
+    fn copy_tree(&mut self) {
+        // Hidden entries are included only while they are listed
+        let tree = tree_text(
+            &self.current_dir_path,
+            self.config.tree_copy_depth,
+            !self.hide_dotfiles,
+        );
+
+        self.status_message = Some(match copy_to_clipboard(&tree) {
+            Ok(()) => format!("Copied tree ({} lines) to clipboard", tree.lines().count()),
+            Err(err) => format!("Unable to copy tree: {err}"),
+        });
+    }
+
+    fn add_selected_dir_to_favorites(&mut self) {
+        let dir_path = self.currently_selected_file().clone();
+        let label = favorite_label(&dir_path);
+
+        self.status_message = Some(if !self.favorites.add(&dir_path) {
+            format!("{label} is already a favorite")
+        } else if let Err(err) = self.favorites.save() {
+            format!("Unable to save favorites: {err}")
+        } else {
+            format!("Added {label} to favorites")
+        });
+    }
+
+    fn toggle_star_on_selected_file(&mut self) {
+        let file_path = self.currently_selected_file().clone();
+        if file_path.is_dir() {
+            self.status_message = Some("Only files can be starred".to_string());
+            return;
+        }
+
+        let label = file_path.file_name().unwrap_or_default().to_string_lossy();
+        let now_starred = self.starred.toggle(&file_path);
+        self.status_message = Some(if let Err(err) = self.starred.save() {
+            format!("Unable to save stars: {err}")
+        } else if now_starred {
+            format!("Starred {label}")
+        } else {
+            format!("Unstarred {label}")
+        });
+
+        // Unstarring removes the entry from the starred-only listing
+        if self.starred_only && !now_starred {
+            self.update_current_dir_contents();
+            self.clamp_cursor_position();
+        }
+    }
+
+    fn toggle_starred_only(&mut self) {
+        self.starred_only = !self.starred_only;
+        self.refresh_keeping_selection();
+    }
+
+    /// Whether the right pane shows the selected file's contents read straight from it, rather
+    /// than the single screen a background preview or selection hook gives
+    fn preview_scrollable(&self) -> bool {
+        self.preview_visible()
+            && self.right_pane_mode == RightPaneMode::Contents
+            && self.selected_entry().is_some_and(|selected_path| {
+                !self.previews_in_background(selected_path)
+                    && self
+                        .selection_hook
+                        .as_ref()
+                        .and_then(|selection_hook| selection_hook.output_for(selected_path))
+                        .is_none()
+            })
+    }
+
+    /// Scrolls the preview `step` characters to the right, or left when negative, stopping with
+    /// the last character of the longest of `visible_lines` at the left edge
+    fn scroll_preview_sideways(&mut self, step: isize, visible_lines: &[String]) {
+        let longest = visible_lines
+            .iter()
+            .map(|line| character_count(line))
+            .max()
+            .unwrap_or(0);
+        self.preview_horizontal_offset = self
+            .preview_horizontal_offset
+            .saturating_add_signed(step)
+            .min(longest.saturating_sub(1));
+    }
+
+    /// Scrolls the preview down a line, stopping once its last line, or the last line read so
+    /// far, is at the top
+    fn scroll_preview_down(&mut self) {
+        self.refresh_preview_text();
+        let next_offset = self.file_scroll_offset + 1;
+        let has_more = match self.selected_preview_text() {
+            Some(preview_text) => preview_text.lines().len() > next_offset,
+            None => !self
+                .preview_window(self.currently_selected_file(), next_offset, 1)
+                .is_empty(),
+        };
+        if has_more {
+            self.file_scroll_offset = next_offset;
+        }
+    }
+
+    /// Opens the editor on the line at the top of the preview, or the first line if it isn't open
+    fn edit_selected_file_at_preview_line(&mut self) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        let line = if self.preview_visible() {
+            self.file_scroll_offset + 1
+        } else {
+            1
+        };
+        let editor = std::env::var_os("VISUAL").or_else(|| std::env::var_os("EDITOR"));
+        self.pending_external_command = Some(editor_command_at_line(
+            editor,
+            self.currently_selected_file(),
+            line,
+            self.config.editor_line_format,
+        ));
+    }
+
+    fn open_selected_file(&mut self, action: FileOpenAction) {
+        if action == FileOpenAction::Editor && self.refuse_in_read_only() {
+            return;
+        }
+        let file_path = self.currently_selected_file();
+
+        match action {
+            FileOpenAction::Preview => self.request_preview(),
+            FileOpenAction::Pager => {
+                self.pending_external_command =
+                    Some(pager_command(std::env::var_os("PAGER"), file_path));
+            }
+            FileOpenAction::Editor => {
+                let editor = std::env::var_os("VISUAL").or_else(|| std::env::var_os("EDITOR"));
+                self.pending_external_command = Some(editor_command(editor, file_path));
+            }
+            FileOpenAction::SystemOpener => {
+                self.pending_external_command = Some(system_opener_command(file_path));
+            }
+        }
+    }
+
+    fn copy_relative_path(&mut self) {
+        let relative_path = relative_to(self.currently_selected_file(), &self.root);
+        let relative_path = relative_path.to_string_lossy();
+
+        self.status_message = Some(match copy_to_clipboard(&relative_path) {
+            Ok(()) => format!("Copied {relative_path}"),
+            Err(err) => format!("Unable to copy path: {err}"),
+        });
+    }
+
+    fn copy_current_dir_path(&mut self) {
+        let dir_path = dir_path_text(&self.current_dir_path);
+
+        self.status_message = Some(match copy_to_clipboard(&dir_path) {
+            Ok(()) => format!("Copied {dir_path}"),
+            Err(err) => format!("Unable to copy path: {err}"),
+        });
+    }
+
+    /// Copies the names in the listing as they are shown, one per line
+    fn copy_listing(&mut self, frame_size: Size) {
+        let listing = self
+            .directory_view(self.frame_listing_area(frame_size).height)
+            .listing_text();
+
+        self.status_message = Some(match copy_to_clipboard(&listing) {
+            Ok(()) => format!(
+                "Copied listing ({} lines) to clipboard",
+                listing.lines().count()
+            ),
+            Err(err) => format!("Unable to copy listing: {err}"),
+        });
+    }
+
+    fn toggle_mark(&mut self) {
+        let selected = self.currently_selected_file().clone();
+        if !self.marked.remove(&selected) {
+            self.marked.insert(selected);
+        }
+    }
+
+    /// Moves the cursor a row without wrapping, marking every entry between it and the anchor
+    /// and unmarking those the range no longer reaches
+    fn extend_marked_range(&mut self, upwards: bool) {
+        let position = self.current_cursor_position();
+        let anchor = *self.range_anchor.get_or_insert(position);
+        let new_position = if upwards {
+            position.saturating_sub(1)
+        } else {
+            (position + 1).min(self.current_dir_contents.len() - 1)
+        };
+
+        for entity in &self.current_dir_contents[anchor.min(position)..=anchor.max(position)] {
+            self.marked.remove(entity);
+        }
+        self.marked.extend(
+            self.current_dir_contents[anchor.min(new_position)..=anchor.max(new_position)]
+                .iter()
+                .cloned(),
+        );
+        self.cursor_positions[self.current_cursor_depth] = new_position;
+    }
+
+    fn sorted_marked_paths(&self) -> Vec<PathBuf> {
+        sorted(self.marked.iter().cloned()).collect()
+    }
+
+    /// Asks to copy the marked entries into the current directory
+    fn request_paste_marked_files(&mut self, available_space: Option<u64>) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        if self.marked.is_empty() {
+            self.status_message = Some("Nothing is marked".to_string());
+            return;
+        }
+
+        self.request_copy_here(
+            &self.sorted_marked_paths(),
+            "marked",
+            PendingAction::PasteMarked,
+            available_space,
+        );
+    }
+
+    fn paste_marked_files(&mut self) {
+        let marked_paths = self.sorted_marked_paths();
+        self.marked.clear();
+        self.copy_here(&marked_paths);
+    }
+
+    /// Asks to move the marked entries into the current directory
+    fn request_move_marked_files(&mut self) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        if self.marked.is_empty() {
+            self.status_message = Some("Nothing is marked".to_string());
+            return;
+        }
+
+        self.request_confirmation(
+            format!("Move {} marked items here?", self.marked.len()),
+            PendingAction::MoveMarked,
+        );
+    }
+
+    fn move_marked_files(&mut self) {
+        let marked_paths = self.sorted_marked_paths();
+        self.marked.clear();
+
+        let failed_moves = marked_paths
+            .iter()
+            .filter(|source| {
+                let Some(file_name) = source.file_name() else {
+                    return true;
+                };
+                move_path(source, &self.current_dir_path.join(file_name)).is_err()
+            })
+            .count();
+
+        self.update_current_dir_contents();
+        self.status_message = Some(if failed_moves == 0 {
+            format!("Moved {} items", marked_paths.len())
+        } else {
+            format!(
+                "Unable to move {failed_moves} of {} items",
+                marked_paths.len()
+            )
+        });
+    }
+
+    fn toggle_selected_file_in_selection_list(&mut self) {
+        let file_path = self.currently_selected_file().clone();
+        let label = favorite_label(&file_path);
+
+        self.status_message = Some(if self.selection_list.remove(&file_path) {
+            format!("Removed {label} from the collection")
+        } else {
+            self.selection_list.add(&file_path);
+            format!(
+                "Collected {label} ({} collected)",
+                self.selection_list.paths().len()
+            )
+        });
+    }
+
+    /// Asks to copy the collected files, in the order they were collected, into the current directory
+    fn request_export_selection_list(&mut self, available_space: Option<u64>) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        if self.selection_list.is_empty() {
+            self.status_message = Some("Nothing has been collected".to_string());
+            return;
+        }
+
+        let collected_paths = self.selection_list.paths().to_vec();
+        self.request_copy_here(
+            &collected_paths,
+            "collected",
+            PendingAction::ExportSelectionList,
+            available_space,
+        );
+    }
+
+    fn export_selection_list(&mut self) {
+        let collected_paths = self.selection_list.paths().to_vec();
+        self.selection_list.clear();
+        self.copy_here(&collected_paths);
+    }
+
+    /// Asks to run `action`, copying `paths` into the current directory, unless they won't fit in
+    /// the `available_space` of its filesystem (when known)
+    fn request_copy_here(
+        &mut self,
+        paths: &[PathBuf],
+        description: &str,
+        action: PendingAction,
+        available_space: Option<u64>,
+    ) {
+        let needed_space = total_size(paths);
+        if let Some(warning) = available_space.and_then(|available_space| {
+            insufficient_space_warning(needed_space, available_space, self.config.size_units)
+        }) {
+            self.status_message = Some(warning);
+            return;
+        }
+
+        self.request_confirmation(
+            format!(
+                "Copy {} {description} items ({}) here?",
+                paths.len(),
+                human_size(needed_space, self.config.size_units)
+            ),
+            action,
+        );
+    }
+
+    /// Copies the yanked entry into the current directory, under a new name if its own is taken,
+    /// and selects the copy, or moves it there when it was cut
+    fn paste_yanked(&mut self) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        let Some(source) = self.yanked.clone() else {
+            self.status_message = Some("Nothing is yanked".to_string());
+            return;
+        };
+        let label = favorite_label(&source);
+        if source.is_dir() && self.current_dir_path.starts_with(&source) {
+            self.status_message = Some(format!("Can't paste {label} inside itself"));
+            return;
+        }
+        if self.cut && source.parent() == Some(self.current_dir_path.as_path()) {
+            self.status_message = Some(format!("{label} is already here"));
+            return;
+        }
+        let Some(destination) = copy_destination(&source, &self.current_dir_path) else {
+            return;
+        };
+
+        if self.cut {
+            let result = move_path(&source, &destination);
+            self.update_current_dir_contents();
+            self.status_message = Some(match result {
+                Ok(()) => {
+                    self.yanked = None;
+                    self.cut = false;
+                    let moved_name = destination.file_name().unwrap_or_default();
+                    self.select_entry_by_name(moved_name);
+                    format!("Moved {}", moved_name.to_string_lossy())
+                }
+                Err(err) => format!("Unable to move {label}: {err}"),
+            });
+            return;
+        }
+
+        let result = copy_recursively(&source, &destination);
+        self.update_current_dir_contents();
+        self.status_message = Some(match result {
+            Ok(()) => {
+                let copy_name = destination.file_name().unwrap_or_default();
+                self.select_entry_by_name(copy_name);
+                format!("Pasted {}", copy_name.to_string_lossy())
+            }
+            Err(err) => format!("Unable to paste {label}: {err}"),
+        });
+    }
+
+    /// Puts the yanked entry where the selected one is and the selected one where the yanked
+    /// one was, leaving the cursor on the yanked entry in its new place
+    fn swap_with_yanked(&mut self) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        let Some(yanked) = self.yanked.clone() else {
+            self.status_message = Some("Nothing is yanked".to_string());
+            return;
+        };
+        let selected = self.currently_selected_file().clone();
+        let (yanked_label, selected_label) = (favorite_label(&yanked), favorite_label(&selected));
+        if resolve_parents(&yanked) == resolve_parents(&selected) {
+            self.status_message = Some(format!("{selected_label} is the yanked entry"));
+            return;
+        }
+
+        let result = swap_paths(&yanked, &selected);
+        self.update_current_dir_contents();
+        self.status_message = Some(match result {
+            Ok(()) => {
+                self.yanked = Some(selected);
+                format!("Swapped {yanked_label} and {selected_label}")
+            }
+            Err(err) => format!("Unable to swap {yanked_label} and {selected_label}: {err}"),
+        });
+    }
+
+    /// Copies `paths` into the current directory, removing again whatever a copy that fails part
+    /// way had already written
+    fn copy_here(&mut self, paths: &[PathBuf]) {
+        let mut failed_copies = 0;
+        let mut removed_copies = 0;
+        for source in paths {
+            let Some(file_name) = source.file_name() else {
+                failed_copies += 1;
+                continue;
+            };
+            let destination = self.current_dir_path.join(file_name);
+            // A copy refused because the name is taken mustn't take what was there with it
+            let was_taken = destination.symlink_metadata().is_ok();
+            if copy_recursively(source, &destination).is_err() {
+                failed_copies += 1;
+                if !was_taken
+                    && destination.symlink_metadata().is_ok()
+                    && remove_recursively(&destination).is_ok()
+                {
+                    removed_copies += 1;
+                }
+            }
+        }
+
+        self.update_current_dir_contents();
+        self.status_message = Some(match (failed_copies, removed_copies) {
+            (0, _) => format!("Copied {} items", paths.len()),
+            (_, 0) => format!("Unable to copy {failed_copies} of {} items", paths.len()),
+            _ => format!(
+                "Unable to copy {failed_copies} of {} items, removed {removed_copies} partial copies",
+                paths.len()
+            ),
+        });
+    }
+
+    fn adjust_cursor_column_width(&mut self, adjustment: i16, frame_size: Size) {
+        let column_height = frame_size.height.saturating_sub(3) as usize;
+        let (cursor_column_index, _) = self.current_cursor_column_and_row(column_height);
+
+        *self
+            .column_width_adjustments
+            .entry(self.current_dir_path.clone())
+            .or_default()
+            .entry(cursor_column_index)
+            .or_default() += adjustment;
+    }
+
+    /// Marks the selected entry to be moved by the next paste
+    fn cut_selected(&mut self) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        let selected = self.currently_selected_file().clone();
+        self.status_message = Some(format!("Cut {}", favorite_label(&selected)));
+        self.yanked = Some(selected);
+        self.cut = true;
+    }
+
+    fn request_delete_selected(&mut self) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        let path = self.currently_selected_file().clone();
+        let label = favorite_label(&path);
+        let message = if path.is_dir() {
+            format!("Delete {label} and everything in it?")
+        } else {
+            format!("Delete {label}?")
+        };
+        self.request_confirmation(message, PendingAction::Delete(path));
+    }
+
+    fn delete(&mut self, path: &Path) {
+        let label = favorite_label(path);
+        self.status_message = Some(match remove_recursively(path) {
+            Ok(()) => format!("Deleted {label}"),
+            Err(err) => format!("Unable to delete {label}: {err}"),
+        });
+
+        self.marked.remove(path);
+        self.update_current_dir_contents();
+        self.clamp_cursor_position();
+    }
+
+    fn toggle_hide_dotfiles(&mut self) {
+        self.hide_dotfiles = !self.hide_dotfiles;
+        self.remember_view_settings();
+        self.refresh_keeping_selection();
+    }
+
+    fn toggle_hide_gitignored(&mut self) {
+        self.hide_gitignored = !self.hide_gitignored;
+        self.remember_view_settings();
+        self.refresh_keeping_selection();
+    }
+
+    fn toggle_summarize_extensions(&mut self) {
+        self.summarize_extensions = !self.summarize_extensions;
+        self.remember_view_settings();
+        self.refresh_keeping_selection();
+    }
+
+    /// Reloads the listing with the cursor left on the same entry if it is still listed, rather
+    /// than at the same position, or clamped to the listing when it has gone
+    fn refresh_keeping_selection(&mut self) {
+        let selected_entry = self.selected_entry().cloned();
+        self.update_current_dir_contents();
+        self.select_or_clamp(selected_entry);
+    }
+
+    /// Puts the cursor back on `selected_entry` if it is still listed, or keeps it in range if not
+    fn select_or_clamp(&mut self, selected_entry: Option<PathBuf>) {
+        match selected_entry.and_then(|selected_entry| {
+            self.current_dir_contents
+                .iter()
+                .position(|entry| *entry == selected_entry)
+        }) {
+            Some(position) => self.cursor_positions[self.current_cursor_depth] = position,
+            None => self.clamp_cursor_position(),
+        }
+    }
+
+    /// Switches to the next built-in order, re-sorting what is already listed
+    fn cycle_sort_mode(&mut self) {
+        self.sort_mode = self.sort_mode.next(self.config.sort_mode);
+        self.comparator = self.sort_mode.comparator(self.config.sort_tiebreaker);
+        self.custom_order = false;
+        self.remember_view_settings();
+        self.sort_contents();
+        self.status_message = Some(format!("Sorted by {}", self.sort_mode.label()));
+    }
+
+    /// Re-sorts the listing without reading the directory again, keeping the cursor on the same
+    /// entry
+    fn sort_contents(&mut self) {
+        let mut unsearched_contents = std::mem::take(&mut self.unsearched_contents);
+        self.sort_entries(&mut unsearched_contents);
+        self.unsearched_contents = unsearched_contents;
+        self.lay_out_keeping_selection();
+    }
+
+    /// Orders pinned entries before the rest, then by the group a header is drawn above, then
+    /// directories before files if `dirs_first` is on, and otherwise by the comparator. Grouping
+    /// comes first so that each header is only drawn once
+    fn sort_entries(&self, entries: &mut Vec<PathBuf>) {
+        *entries = self
+            .sorted_entries(std::mem::take(entries))
+            .into_iter()
+            .map(|entity| entity.path)
+            .collect();
+    }
+
+    /// `entries` in the order they are listed in, keeping whatever metadata sorting them read
+    fn sorted_entries(&self, entries: Vec<PathBuf>) -> Vec<SortEntry> {
+        let now = SystemTime::now();
+        let grouped = self.config.group_headers && !self.custom_order;
+        // Each entry is only looked at once, rather than on every comparison
+        let mut keyed_entries: Vec<_> = entries
+            .into_iter()
+            .map(|entity| {
+                let entity = SortEntry::new(entity);
+                let key = (
+                    Reverse(is_pinned(&entity.path, &self.config.pinned)),
+                    grouped
+                        .then(|| self.sort_mode.group_rank(&entity, now))
+                        .flatten(),
+                    Reverse(self.dirs_first && entity.is_dir()),
+                );
+                (key, entity)
+            })
+            .collect();
+        keyed_entries.sort_by(|(a_key, a), (b_key, b)| {
+            a_key.cmp(b_key).then_with(|| self.comparator.compare(a, b))
+        });
+        keyed_entries
+            .into_iter()
+            .map(|(_, entity)| entity)
+            .collect()
+    }
+
+    fn update_group_headers(&mut self) {
+        self.group_headers = if self.config.group_headers {
+            let now = SystemTime::now();
+            group_headers(&self.current_dir_contents, |entity| {
+                self.sort_mode.group_label(entity, now)
+            })
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Lists the selected directory's contents indented beneath it, or stops listing them
+    fn toggle_expanded(&mut self) {
+        let dir_path = self.currently_selected_file().clone();
+        if !self.expanded.remove(&dir_path) {
+            self.expanded.insert(dir_path);
+        }
+        self.refresh_keeping_selection();
+    }
+
+    /// `contents` with the contents of each expanded directory after it, and theirs after them
+    fn with_expanded_dirs(&mut self, contents: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut listed = Vec::with_capacity(contents.len());
+        for entity in contents {
+            let expanded = self.expanded.contains(&entity);
+            listed.push(entity.clone());
+            if !expanded {
+                continue;
+            }
+
+            let mut children: Vec<PathBuf> = std::fs::read_dir(&entity)
+                .into_iter()
+                .flatten()
+                .filter_map(|maybe_dir_entry| Some(maybe_dir_entry.ok()?.path()))
+                .collect();
+            // Filtered the same way as the top of the listing
+            self.retain_shown_entries(&entity, &mut children);
+            self.sort_entries(&mut children);
+            listed.extend(self.with_expanded_dirs(children));
+        }
+        listed
+    }
+
+    /// Stores the current view settings against the current directory
+    fn remember_view_settings(&mut self) {
+        let view_settings = ViewSettings {
+            summarize_extensions: self.summarize_extensions,
+            hide_gitignored: self.hide_gitignored,
+            sort_mode: self.sort_mode,
+            dirs_first: self.dirs_first,
+            hide_dotfiles: self.hide_dotfiles,
+        };
+        self.dir_view_settings
+            .set(&self.current_dir_path, view_settings);
+    }
+
+    /// Switches to the settings last used in the current directory, or the defaults if there are
+    /// none. Only done on arriving in a directory, so reloading it keeps whatever is in use
+    fn restore_view_settings(&mut self) {
+        let view_settings = self
+            .dir_view_settings
+            .get(&self.current_dir_path)
+            .unwrap_or(ViewSettings {
+                summarize_extensions: false,
+                hide_gitignored: self.config.hide_gitignored,
+                sort_mode: self.config.sort_mode,
+                dirs_first: self.config.dirs_first
+                    && self.config.sort_mode != SortMode::AlphaNaturalMixed,
+                // Hidden dotfiles stay hidden going in and out of directories that weren't told
+                // otherwise
+                hide_dotfiles: self.hide_dotfiles,
+            });
+        self.summarize_extensions = view_settings.summarize_extensions;
+        self.hide_gitignored = view_settings.hide_gitignored;
+        self.dirs_first = view_settings.dirs_first;
+        self.hide_dotfiles = view_settings.hide_dotfiles;
+        // An embedder's order stays until another is picked with `s`
+        if !self.custom_order {
+            self.sort_mode = view_settings.sort_mode;
+            self.comparator = self.sort_mode.comparator(self.config.sort_tiebreaker);
+        }
+    }
+
+    /// Keeps the cursor within the listing after entries are removed from it
+    fn clamp_cursor_position(&mut self) {
+        let last_position = self.current_dir_contents.len().saturating_sub(1);
+        let cursor_position = &mut self.cursor_positions[self.current_cursor_depth];
+        *cursor_position = (*cursor_position).min(last_position);
+    }
+
+    fn expand_selected_group(&mut self) {
+        let Some(group_index) = self.currently_on_collapsed_group() else {
+            return;
+        };
+        let insert_position = self.current_cursor_position() + 1;
+        let group = &mut self.extension_groups[group_index];
+        group.expanded = true;
+
+        self.current_dir_contents.splice(
+            insert_position..insert_position,
+            group.members[1..].iter().cloned(),
+        );
+    }
+
+    fn move_cursor_up(&mut self) {
+        if self.current_dir_contents.is_empty() {
+            return;
+        }
+        if self.current_cursor_position() == 0 {
+            self.cursor_positions[self.current_cursor_depth] = self.current_dir_contents.len() - 1;
+        } else {
+            self.cursor_positions[self.current_cursor_depth] -= 1;
+        }
+    }
+
+    fn move_cursor_down(&mut self) {
+        if self.current_dir_contents.is_empty() {
+            return;
+        }
+        if self.current_cursor_position() == self.current_dir_contents.len() - 1 {
+            self.cursor_positions[self.current_cursor_depth] = 0;
+        } else {
+            self.cursor_positions[self.current_cursor_depth] += 1;
+        }
+    }
+
+    fn move_cursor_right(&mut self, frame_size: Size) {
+        if self.current_dir_contents.is_empty() {
+            return;
+        }
+        let column_height = (frame_size.height.saturating_sub(3) as usize).max(1);
+        let number_of_rows = self.number_of_rows();
+        let number_of_columns = number_of_rows.div_ceil(column_height);
+        let row = self.row_of(self.current_cursor_position());
+
+        let new_row = if row / column_height + 1 < number_of_columns {
+            // The last column can be short, in which case go to its lowest entry instead
+            (row + column_height).min(number_of_rows - 1)
+        } else if self.config.wrap_columns {
+            // Wrap around to the same row of the first column, which is always full
+            row % column_height
+        } else {
+            row
+        };
+        self.cursor_positions[self.current_cursor_depth] = self.position_at_row(new_row);
+    }
+
+    fn move_cursor_left(&mut self, frame_size: Size) {
+        if self.current_dir_contents.is_empty() {
+            return;
+        }
+        let column_height = (frame_size.height.saturating_sub(3) as usize).max(1);
+        let number_of_rows = self.number_of_rows();
+        let number_of_columns = number_of_rows.div_ceil(column_height);
+        let row = self.row_of(self.current_cursor_position());
+
+        let new_row = if row >= column_height {
+            row - column_height
+        } else if self.config.wrap_columns {
+            // Wrap around to the last column, going to its lowest entry if it doesn't reach this row
+            ((number_of_columns - 1) * column_height + row).min(number_of_rows - 1)
+        } else {
+            row
+        };
+        self.cursor_positions[self.current_cursor_depth] = self.position_at_row(new_row);
+    }
+
+    fn request_quit(&mut self) {
+        if self.config.confirm_quit {
+            self.request_confirmation("Quit?".to_string(), PendingAction::Quit);
+        } else {
+            self.exit();
+        }
+    }
+
+    /// Whether read-only mode refuses the operation about to start, saying so if it does
+    ///
+    /// Every operation that changes the disk, or opens something that could, checks this first
+    fn refuse_in_read_only(&mut self) -> bool {
+        if self.read_only {
+            self.status_message = Some(READ_ONLY_MESSAGE.to_string());
+        }
+        self.read_only
+    }
+
+    /// Asks before running `action`, unless it is destructive and confirming those is turned off
+    fn request_confirmation(&mut self, message: String, action: PendingAction) {
+        if action.is_destructive() && !self.config.confirm_destructive {
+            self.run_pending_action(action);
+        } else {
+            self.pending_confirmation = Some(Confirmation::new(message, action));
+        }
+    }
+
+    fn run_pending_action(&mut self, action: PendingAction) {
+        match action {
+            PendingAction::EnterDir(dir_path) => self.enter_dir(&dir_path),
+            PendingAction::Preview => {
+                self.large_preview_allowed = self.selected_entry().cloned();
+                // Left open when it was showing the placeholder
+                if !(self.view_file && self.right_pane_mode == RightPaneMode::Contents) {
+                    self.show_right_pane(RightPaneMode::Contents);
+                }
+            }
+            PendingAction::PasteMarked => self.paste_marked_files(),
+            PendingAction::MoveMarked => self.move_marked_files(),
+            PendingAction::ExportSelectionList => self.export_selection_list(),
+            PendingAction::Delete(path) => self.delete(&path),
+            PendingAction::Quit => self.exit(),
+            PendingAction::BatchRename(renames) => self.batch_rename(&renames),
+        }
+    }
+
+    fn go_into_dir(&mut self) {
+        if self.navigation_locked() {
+            return;
+        }
+        let dir_path = self.current_dir_contents[self.current_cursor_position()].clone();
+
+        // Counting is much cheaper than the sort and render of a full load
+        let number_of_entries = std::fs::read_dir(&dir_path)
+            .map(|entries| entries.count())
+            .unwrap_or(0);
+
+        if number_of_entries > self.config.large_dir_threshold {
+            self.request_confirmation(
+                format!("This directory has {number_of_entries} items — continue?"),
+                PendingAction::EnterDir(dir_path),
+            );
+        } else {
+            self.enter_dir(&dir_path);
+        }
+    }
+
+    fn enter_dir(&mut self, dir_path: &PathBuf) {
+        let previous_dir_path = self.current_dir_path.clone();
+        self.current_dir_path = normalize_dir_path(self.current_dir_path.join(dir_path));
+        // A search only narrows the directory it was started in
+        let search = self.search.take();
+        let scan = self.scan.take();
+        self.restore_view_settings();
+
+        // The directory may have been removed or replaced since it was listed
+        if let Err(err) = self.load_current_dir_contents() {
+            self.current_dir_path = previous_dir_path;
+            self.restore_view_settings();
+            self.search = search;
+            self.scan = scan;
+            self.status_message = Some(format!(
+                "Unable to open {}: {err}",
+                favorite_label(dir_path)
+            ));
+            return;
+        }
+        self.sync_cursor_depth();
+        self.record_visit();
+    }
+
+    fn go_out_of_dir(&mut self) {
+        if self.navigation_locked() || !self.current_dir_path.pop() {
+            return;
+        }
+        self.search = None;
+        self.scan = None;
+        self.sync_cursor_depth();
+        self.restore_view_settings();
+        self.update_current_dir_contents();
+        self.record_visit();
+    }
+
+    /// Matches the cursor depth to the current directory, as entering a child listed under an
+    /// expanded directory goes down more than one level at once
+    fn sync_cursor_depth(&mut self) {
+        self.current_cursor_depth = self.current_dir_path.ancestors().count() - 1;
+        self.cursor_positions
+            .resize(self.current_cursor_depth + 1, 0);
+    }
+
+    /// Whether a directory change now comes sooner after the last than `navigation_lock_ms` allows
+    ///
+    /// Ignored changes restart the wait too, so holding a key down only ever moves once
+    fn navigation_locked(&mut self) -> bool {
+        let navigation_lock = Duration::from_millis(self.config.navigation_lock_ms);
+        let Some(now) = self.event_time.filter(|_| !navigation_lock.is_zero()) else {
+            return false;
+        };
+
+        let locked = self
+            .last_navigation
+            .is_some_and(|last_navigation| now.duration_since(last_navigation) < navigation_lock);
+        self.last_navigation = Some(now);
+        locked
+    }
+
+    /// Moves straight to `dir_path`, which doesn't need to be related to the current directory
+    fn jump_to_dir(&mut self, dir_path: PathBuf) {
+        if self.show_dir(dir_path) {
+            self.record_visit();
+        }
+    }
+
+    fn go_back(&mut self) {
+        let Some(dir_path) = self.navigation_history.back().map(Path::to_path_buf) else {
+            return;
+        };
+        if self.show_dir(dir_path) {
+            self.recent_dirs.visit(&self.current_dir_path);
+            self.navigation_history.arrived(&self.current_dir_path);
+        } else {
+            self.navigation_history.forward();
+        }
+    }
+
+    fn go_forward(&mut self) {
+        let Some(dir_path) = self.navigation_history.forward().map(Path::to_path_buf) else {
+            return;
+        };
+        if self.show_dir(dir_path) {
+            self.recent_dirs.visit(&self.current_dir_path);
+            self.navigation_history.arrived(&self.current_dir_path);
+        } else {
+            self.navigation_history.back();
+        }
+    }
+
+    /// Lists `dir_path` in place of the current directory, returning whether it could be opened
+    fn show_dir(&mut self, dir_path: PathBuf) -> bool {
+        let previous_dir_path =
+            std::mem::replace(&mut self.current_dir_path, normalize_dir_path(dir_path));
+        // A search only narrows the directory it was started in
+        let search = self.search.take();
+        self.restore_view_settings();
+
+        // Remembered directories may have been removed since
+        if let Err(err) = self.load_current_dir_contents() {
+            let dir_path = std::mem::replace(&mut self.current_dir_path, previous_dir_path);
+            self.restore_view_settings();
+            self.search = search;
+            self.status_message = Some(format!(
+                "Unable to open {}: {err}",
+                favorite_label(&dir_path)
+            ));
+            return false;
+        }
+        self.scan = None;
+        self.current_cursor_depth = self.current_dir_path.ancestors().count() - 1;
+        self.cursor_positions = vec![0; self.current_cursor_depth + 1];
+        true
+    }
+
+    /// Writes a script replaying this session's directory changes into the current directory,
+    /// never over an existing file
+    fn save_navigation_script(&mut self) {
+        if self.refuse_in_read_only() {
+            return;
+        }
+        let script_path = self.current_dir_path.join(NAVIGATION_SCRIPT_NAME);
+        let result = std::fs::File::create_new(&script_path)
+            .and_then(|mut file| file.write_all(self.navigation_history.cd_script().as_bytes()));
+
+        self.status_message = Some(match result {
+            Ok(()) => format!(
+                "Saved {} directories to {NAVIGATION_SCRIPT_NAME}",
+                self.navigation_history.visit_count()
+            ),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                format!("{NAVIGATION_SCRIPT_NAME} already exists")
+            }
+            Err(err) => format!("Unable to save {NAVIGATION_SCRIPT_NAME}: {err}"),
+        });
+        self.refresh_keeping_selection();
+    }
+
+    fn record_visit(&mut self) {
+        self.recent_dirs.visit(&self.current_dir_path);
+        self.navigation_history.push(&self.current_dir_path);
+    }
+
+    /// The current path, with the launch directory's name marked while somewhere below it
+    fn breadcrumb(&self) -> Line<'_> {
+        let current_dir = self.current_dir_path.to_str().unwrap();
+        let (Some(root), Some(root_name)) = (
+            self.root.to_str(),
+            self.root.file_name().and_then(|name| name.to_str()),
+        ) else {
+            return Line::from(current_dir);
+        };
+        if self.current_dir_path == self.root || !self.current_dir_path.starts_with(&self.root) {
+            return Line::from(current_dir);
+        }
+
+        let root_name_start = root.len() - root_name.len();
+        Line::from(vec![
+            Span::raw(&current_dir[..root_name_start]),
+            Span::styled(root_name, self.theme.root_style),
+            Span::raw(&current_dir[root.len()..]),
+        ])
+    }
+
+    /// Lists the current directory, or lists nothing and says why when it can't be read
+    fn update_current_dir_contents(&mut self) {
+        self.dir_error = match self.load_current_dir_contents() {
+            Ok(()) => None,
+            Err(err) => {
+                self.current_dir_contents.clear();
+                self.unsearched_contents.clear();
+                self.extension_groups.clear();
+                self.group_headers.clear();
+                Some(dir_error_reason(&err))
+            }
+        };
+    }
+
+    fn load_current_dir_contents(&mut self) -> io::Result<()> {
+        let current_dir_contents: Vec<PathBuf> = std::fs::read_dir(&self.current_dir_path)?
+            .filter_map(|maybe_dir_entry| {
+                let dir_entry = maybe_dir_entry.ok()?;
+                Some(dir_entry.path())
+            })
+            .collect();
+        let mut current_dir_contents = self.sorted_entries(current_dir_contents);
+        // Entries may have changed on disk since their details were read
+        self.selected_details = None;
+        self.peek_listing = None;
+        self.selected_file_size = None;
+        self.preview_fallback = None;
+        self.quick_look_fallback = None;
+
+        self.gitignored_dirs.clear();
+        let current_dir_path = self.current_dir_path.clone();
+        self.retain_shown_entries(&current_dir_path, &mut current_dir_contents);
+
+        self.dir_summaries.clear();
+        // Every entry shares the one parent, so its device is only read once
+        let parent_device = device_id(&current_dir_path).ok();
+        self.mount_points = current_dir_contents
+            .iter()
+            .filter(|entity| {
+                matches!(
+                    (entity.symlink_metadata(), parent_device),
+                    (Some(metadata), Some(parent_device)) if is_mount_point(metadata, parent_device)
+                )
+            })
+            .map(|entity| entity.path.clone())
+            .collect();
+        self.hard_links = if self.config.show_hard_links {
+            current_dir_contents
+                .iter()
+                .filter_map(|entity| {
+                    Some((
+                        entity.path.clone(),
+                        hard_link_count(entity.symlink_metadata()?)?,
+                    ))
+                })
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        self.pinned_entries = current_dir_contents
+            .iter()
+            .filter(|entity| is_pinned(&entity.path, &self.config.pinned))
+            .map(|entity| entity.path.clone())
+            .collect();
+
+        self.unsearched_contents = current_dir_contents
+            .into_iter()
+            .map(|entity| entity.path)
+            .collect();
+        self.lay_out_contents();
+        Ok(())
+    }
+
+    /// Drops the entries read from `dir_path` that the dotfile, gitignore and starred-only
+    /// filters hide, noting the ignored directories that are dimmed rather than hidden
+    fn retain_shown_entries<T: AsRef<Path>>(&mut self, dir_path: &Path, entries: &mut Vec<T>) {
+        if self.hide_dotfiles {
+            entries.retain(|entity| !is_hidden(entity.as_ref()));
+        }
+
+        if self.hide_gitignored
+            && let Some(gitignore_filter) = GitignoreFilter::for_dir(dir_path)
+        {
+            let dim_dirs = self.config.gitignored_dirs == IgnoredDirDisplay::Dim;
+            entries.retain(|entity| {
+                let entity = entity.as_ref();
+                if !gitignore_filter.is_ignored(entity) {
+                    true
+                } else if dim_dirs && entity.is_dir() {
+                    self.gitignored_dirs.insert(entity.to_path_buf());
+                    true
+                } else {
+                    false
+                }
+            });
+        }
+
+        if self.starred_only {
+            entries.retain(|entity| self.starred.contains(entity.as_ref()));
+        }
+    }
+
+    /// Lists what was last read from the current directory, narrowed by the search, with groups
+    /// collapsed and expanded directories' contents beneath them
+    fn lay_out_contents(&mut self) {
+        // The anchor is a row, which the new layout may have moved or removed
+        self.range_anchor = None;
+        let mut current_dir_contents = self.unsearched_contents.clone();
+        if let Some(search) = &self.search {
+            current_dir_contents.retain(|entity| search.score(entity).is_some());
+        }
+
+        if self.summarize_extensions {
+            self.extension_groups = group_consecutive_extensions(
+                &current_dir_contents,
+                self.config.min_extension_group_size,
+            );
+            self.current_dir_contents =
+                collapse_groups(&current_dir_contents, &self.extension_groups);
+        } else {
+            self.extension_groups.clear();
+            self.current_dir_contents = current_dir_contents;
+        }
+        if !self.expanded.is_empty() {
+            let current_dir_contents = std::mem::take(&mut self.current_dir_contents);
+            self.current_dir_contents = self.with_expanded_dirs(current_dir_contents);
+        }
+
+        self.update_group_headers();
+    }
+}
+
+/// The part of `area` taking up the given percentages of its width and height, in its middle
+fn centered_rect(area: Rect, width_percentage: u16, height_percentage: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Percentage(height_percentage)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Percentage(width_percentage)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// The size of `file_path` when it is a file, or `None` for anything else or when it can't be read
+fn file_size_on_disk(file_path: &Path) -> Option<u64> {
+    std::fs::metadata(file_path)
+        .ok()
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+}
+
+/// Where the help overlay goes in a frame covering `area`
+fn help_area(area: Rect) -> Rect {
+    centered_rect(area, 60, 80)
+}
+
+fn dir_error_reason(err: &io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::NotFound => "Directory not found".to_string(),
+        io::ErrorKind::PermissionDenied => "Permission denied".to_string(),
+        _ => format!("Unable to read directory: {err}"),
+    }
+}
+
+impl Widget for &App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let title = Line::from(" TUI File Explorer ".bold());
+        let mut dir_line = self.breadcrumb();
+        if let Some(dir_error) = &self.dir_error {
+            dir_line.push_span(format!(" — {dir_error}"));
+        }
+
+        let mut block = Block::bordered()
+            .title(title.centered())
+            .border_set(border::THICK);
+        // An embedder's order has no name to show
+        if !self.custom_order {
+            block = block.title(
+                Line::from(format!(" sorted by {} ", self.sort_mode.label())).right_aligned(),
+            );
+        }
+
+        if let Some(confirmation) = &self.pending_confirmation {
+            block = block.title_bottom(Line::from(format!(" {} ", confirmation.prompt())).bold());
+        } else if let Some(cd_prompt) = &self.cd_prompt {
+            block = block.title_bottom(Line::from(format!(" {} ", cd_prompt.prompt())).bold());
+        } else if let Some(status_message) = &self.status_message {
+            block = block.title_bottom(Line::from(format!(" {status_message} ")));
+        } else if let Some(name_prompt) = &self.name_prompt {
+            block = block.title_bottom(Line::from(format!(" {} ", name_prompt.prompt())).bold());
+        } else if let Some(pattern) = &self.batch_rename_pattern {
+            block = block.title_bottom(Line::from(format!(" rename to {pattern}_ ")).bold());
+        } else if let Some(search) = &self.search {
+            block = block.title_bottom(Line::from(format!(" {} ", search.prompt())).bold());
+        }
+
+        let view_modifiers = self.view_modifiers();
+        if !view_modifiers.is_empty() {
+            block = block.title_bottom(Line::from(format!(" {view_modifiers} ")).right_aligned());
+        }
+
+        if let Some(scan) = &self.scan
+            && self
+                .selected_entry()
+                .is_some_and(|selected| selected == scan.path())
+        {
+            block = block.title_bottom(
+                Line::from(format!(" {} ", scan.status(self.config.size_units))).right_aligned(),
+            );
+        } else if let Some(dir_summary) = self.selected_dir_summary() {
+            block = block.title_bottom(Line::from(format!(" {dir_summary} ")).right_aligned());
+        }
+
+        Paragraph::new(dir_line)
+            .left_aligned()
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cell::{Cell, RefCell},
+        fs::{File, create_dir},
+        rc::Rc,
+    };
+
+    use ratatui::style::Style;
+    use tempdir::TempDir;
 
     use super::*;
+    use crate::{components::media::file_version, scan::ScanTotals};
+
+    /// An app in `current_dir_path` with the default config and nothing stored, whatever the
+    /// machine running the tests has saved
+    fn new_app(current_dir_path: PathBuf) -> App {
+        App::with_stores(
+            current_dir_path,
+            Config::default(),
+            Favorites::default(),
+            Starred::default(),
+            DirViewSettings::default(),
+        )
+    }
+
+    /// Every cell of the app drawn at `width` by `height`, row after row
+    fn rendered_text(app: &App, width: u16, height: u16) -> String {
+        app.render_to_buffer(Rect::new(0, 0, width, height))
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn collapsed_terminal_is_drawn_without_panicking() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        File::create(tmp_dir.path().join("notes.txt")).unwrap();
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('c').into(), Size::default());
+
+        for (width, height) in [(0, 0), (0, 10), (1, 1), (2, 3)] {
+            assert_eq!(
+                rendered_text(&app, width, height).chars().count(),
+                usize::from(width * height)
+            );
+        }
+    }
+
+    #[test]
+    fn can_exit() {
+        let mut app = App::default();
+        assert!(!app.exit);
+
+        app.handle_key_event(KeyCode::Char('q').into(), Size::default());
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn registered_key_handler_runs_instead_of_built_in() {
+        let fired = Rc::new(Cell::new(false));
+        let handler_fired = Rc::clone(&fired);
+        let mut app = App::default().with_key_handler(KeyCode::Char('q'), move |app| {
+            handler_fired.set(true);
+            app.show_status_message("sent");
+        });
+
+        app.handle_key_event(KeyCode::Char('q').into(), Size::default());
+        assert!(fired.get());
+        assert!(!app.exit);
+        assert_eq!(app.status_message.as_deref(), Some("sent"));
+
+        // Only the exact key combination is overridden
+        app.handle_key_event(
+            KeyEvent::new(KeyCode::Char('q'), KeyModifiers::ALT),
+            Size::default(),
+        );
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn quit_waits_for_confirmation_when_enabled() {
+        let mut app = App::default();
+        app.config.confirm_quit = true;
+
+        app.handle_key_event(KeyCode::Char('q').into(), Size::default());
+        assert!(!app.exit);
+        assert_eq!(
+            app.pending_confirmation,
+            Some(Confirmation::new("Quit?".to_string(), PendingAction::Quit))
+        );
+
+        app.handle_key_event(KeyCode::Char('n').into(), Size::default());
+        assert!(!app.exit);
+
+        app.handle_key_event(KeyCode::Char('q').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('y').into(), Size::default());
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn quit_is_immediate_when_confirmation_disabled() {
+        let mut app = App::default();
+        app.config.confirm_quit = false;
+
+        app.handle_key_event(KeyCode::Char('q').into(), Size::default());
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn ctrl_c_always_exits() {
+        let mut app = App::default();
+        app.config.confirm_quit = true;
+        app.handle_key_event(KeyCode::Char('q').into(), Size::default());
+
+        app.handle_key_event(
+            KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Size::default(),
+        );
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn default_app_has_nothing_selected() {
+        let app = App::default();
+
+        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(app.current_cursor_column_and_row(0), (0, 0));
+        assert_eq!(app.selected_entry(), None);
+        assert!(!app.currently_on_dir());
+        assert!(!app.currently_on_file());
+        assert_eq!(app.currently_on_collapsed_group(), None);
+        assert_eq!(app.selected_dir_summary(), None);
+        assert_eq!(app.previewed_path(), None);
+    }
+
+    #[test]
+    fn default_app_ignores_movement_and_enter() {
+        let mut app = App::default();
+
+        for key_code in [
+            KeyCode::Up,
+            KeyCode::Down,
+            KeyCode::Left,
+            KeyCode::Right,
+            KeyCode::Enter,
+        ] {
+            app.handle_key_event(key_code.into(), Size::default());
+        }
+
+        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(app.selected_entry(), None);
+    }
+
+    #[test]
+    fn can_move_cursor() {
+        let mut app = App {
+            current_dir_contents: vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")],
+            current_dir_path: PathBuf::from("./"),
+            cursor_positions: vec![0],
+            ..Default::default()
+        };
+
+        let frame_size = Size {
+            width: 1,
+            height: 6,
+        };
+
+        assert_eq!(app.current_cursor_position(), 0);
+
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 1);
+
+        app.handle_key_event(KeyCode::Up.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+    }
+
+    #[test]
+    fn can_cursor_wraps_around_vertically() {
+        /*
+        Dir looks like this
+        a    c
+        b
+        */
+        let mut app = App {
+            current_dir_contents: vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")],
+            current_dir_path: PathBuf::from("./"),
+            cursor_positions: vec![0],
+            ..Default::default()
+        };
+
+        let frame_size = Size {
+            width: 2,
+            height: 5,
+        };
+
+        assert_eq!(app.current_cursor_position(), 0);
+
+        app.handle_key_event(KeyCode::Up.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 2);
+
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+    }
+
+    #[test]
+    fn can_cursor_wraps_around_horizontally() {
+        /*
+        Dir looks like this
+        a    c
+        b
+        */
+        let mut app = App {
+            current_dir_contents: vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")],
+            current_dir_path: PathBuf::from("./"),
+            cursor_positions: vec![0],
+            ..Default::default()
+        };
+
+        let frame_size = Size {
+            width: 2,
+            height: 5,
+        };
+
+        assert_eq!(app.current_cursor_position(), 0);
+
+        app.handle_key_event(KeyCode::Left.into(), frame_size);
+
+        assert_eq!(app.current_cursor_position(), 2);
+
+        app.handle_key_event(KeyCode::Right.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+    }
+
+    fn app_with_entries(number_of_entries: usize) -> App {
+        App {
+            current_dir_contents: (0..number_of_entries)
+                .map(|index| PathBuf::from(index.to_string()))
+                .collect(),
+            current_dir_path: PathBuf::from("./"),
+            cursor_positions: vec![0],
+            ..Default::default()
+        }
+    }
+
+    /// Presses `key_code` with the cursor at `position`, returning where the cursor ends up
+    fn move_from(app: &mut App, position: usize, key_code: KeyCode, frame_size: Size) -> usize {
+        app.cursor_positions[0] = position;
+        app.handle_key_event(key_code.into(), frame_size);
+        app.current_cursor_position()
+    }
+
+    #[test]
+    fn horizontal_movement_in_a_single_column_stays_put() {
+        let mut app = app_with_entries(3);
+        let frame_size = Size {
+            width: 20,
+            height: 13,
+        };
+
+        for wrap_columns in [true, false] {
+            app.config.wrap_columns = wrap_columns;
+            assert_eq!(move_from(&mut app, 1, KeyCode::Right, frame_size), 1);
+            assert_eq!(move_from(&mut app, 1, KeyCode::Left, frame_size), 1);
+            assert_eq!(move_from(&mut app, 2, KeyCode::Left, frame_size), 2);
+        }
+    }
+
+    #[test]
+    fn horizontal_movement_with_a_short_last_column() {
+        /*
+        Dir looks like this
+        0    2    4
+        1    3
+        */
+        let mut app = app_with_entries(5);
+        let frame_size = Size {
+            width: 20,
+            height: 5,
+        };
+
+        // Away from the edges, wrapping makes no difference
+        for wrap_columns in [true, false] {
+            app.config.wrap_columns = wrap_columns;
+            assert_eq!(move_from(&mut app, 1, KeyCode::Right, frame_size), 3);
+            assert_eq!(move_from(&mut app, 2, KeyCode::Right, frame_size), 4);
+            // Nothing directly right, so the lowest entry of the last column
+            assert_eq!(move_from(&mut app, 3, KeyCode::Right, frame_size), 4);
+            assert_eq!(move_from(&mut app, 4, KeyCode::Left, frame_size), 2);
+            assert_eq!(move_from(&mut app, 3, KeyCode::Left, frame_size), 1);
+        }
+
+        app.config.wrap_columns = true;
+        assert_eq!(move_from(&mut app, 4, KeyCode::Right, frame_size), 0);
+        assert_eq!(move_from(&mut app, 0, KeyCode::Left, frame_size), 4);
+        assert_eq!(move_from(&mut app, 1, KeyCode::Left, frame_size), 4);
+
+        app.config.wrap_columns = false;
+        assert_eq!(move_from(&mut app, 4, KeyCode::Right, frame_size), 4);
+        assert_eq!(move_from(&mut app, 0, KeyCode::Left, frame_size), 0);
+        assert_eq!(move_from(&mut app, 1, KeyCode::Left, frame_size), 1);
+    }
+
+    #[test]
+    fn arrows_follow_the_mirrored_columns_right_to_left() {
+        /*
+        Dir is drawn like this
+        4    2    0
+             3    1
+        */
+        let mut app = app_with_entries(5).with_theme(Theme::default().with_right_to_left());
+        let frame_size = Size {
+            width: 20,
+            height: 5,
+        };
+
+        assert_eq!(move_from(&mut app, 1, KeyCode::Left, frame_size), 3);
+        assert_eq!(move_from(&mut app, 2, KeyCode::Left, frame_size), 4);
+        assert_eq!(move_from(&mut app, 4, KeyCode::Right, frame_size), 2);
+        assert_eq!(move_from(&mut app, 3, KeyCode::Right, frame_size), 1);
+    }
+
+    #[test]
+    fn wrapping_left_below_the_last_entry_stays_in_bounds() {
+        /*
+        Dir looks like this, with the cursor on b.txt
+        a.txt    c.txt    e.txt
+        b.txt    d.txt
+        */
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        for file_name in ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"] {
+            File::create(tmp_dir.path().join(file_name)).unwrap();
+        }
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let frame_size = Size {
+            width: 40,
+            height: 5,
+        };
+
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        app.handle_key_event(KeyCode::Left.into(), frame_size);
+
+        // Used to land on 5, one past the end, panicking on the next lookup
+        assert_eq!(app.current_cursor_position(), 4);
+        assert!(app.currently_on_file());
+        assert_eq!(app.selected_entry(), Some(&tmp_dir.path().join("e.txt")));
+        assert!(rendered_text(&app, 40, 5).contains("> e.txt"));
+    }
+
+    #[test]
+    fn wrapping_right_keeps_the_row() {
+        /*
+        Dir looks like this
+        0    3    6
+        1    4    7
+        2    5
+        */
+        let mut app = app_with_entries(8);
+        let frame_size = Size {
+            width: 20,
+            height: 6,
+        };
+
+        assert_eq!(move_from(&mut app, 7, KeyCode::Right, frame_size), 1);
+        assert_eq!(move_from(&mut app, 5, KeyCode::Right, frame_size), 7);
+    }
+
+    #[test]
+    fn can_enter_dir() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path =
+            PathBuf::from(format!("{}/nested_dir", tmp_dir.path().to_str().unwrap()));
+        let _nested_dir = create_dir(&nested_dir_path);
+        let file_path = tmp_dir.path().join("file.txt");
+        let _tmp_file = File::create(&file_path).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+
+        let frame_size = Size {
+            width: 1,
+            height: 5,
+        };
+
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        assert_eq!(
+            app.current_dir_contents,
+            vec![nested_dir_path.clone(), file_path.clone()]
+        );
+        assert_eq!(app.current_cursor_position(), 0);
+
+        // Current dir does not change when attempting to enter file
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        assert_eq!(
+            app.current_dir_contents,
+            vec![nested_dir_path.clone(), file_path.clone()]
+        );
+        assert_eq!(app.current_cursor_position(), 1);
+
+        // But does change if entering dir
+        app.handle_key_event(KeyCode::Up.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(app.current_dir_path, nested_dir_path);
+    }
+
+    #[test]
+    fn can_exit_dir() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        let _tmp_file = File::create(&file_path).unwrap();
+        let nested_dir_path =
+            PathBuf::from(format!("{}/nested_dir", tmp_dir.path().to_str().unwrap()));
+        let _nested_dir = create_dir(&nested_dir_path);
+        let nested_file_path_0 = nested_dir_path.join("file_a.txt");
+        let nested_file_path_1 = nested_dir_path.join("file_b.txt");
+        let _nested_file_0 = File::create(&nested_file_path_0).unwrap();
+        let _nested_file_1 = File::create(&nested_file_path_1).unwrap();
+
+        let mut app = new_app(nested_dir_path.clone());
+
+        let frame_size = Size {
+            width: 1,
+            height: 5,
+        };
+
+        assert_eq!(app.current_dir_path, nested_dir_path);
+        assert_eq!(
+            app.current_dir_contents,
+            vec![nested_file_path_0.clone(), nested_file_path_1.clone()]
+        );
+        assert_eq!(app.current_cursor_position(), 0);
+
+        // Go up a dir when left key pressed
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        assert_eq!(app.current_dir_path, nested_dir_path);
+        assert_eq!(
+            app.current_dir_contents,
+            vec![nested_file_path_0.clone(), nested_file_path_1.clone()]
+        );
+        assert_eq!(app.current_cursor_position(), 1);
+
+        app.handle_key_event(KeyCode::Backspace.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(
+            app.current_dir_contents,
+            vec![nested_dir_path.clone(), file_path.clone()]
+        );
+    }
+
+    #[test]
+    fn cursor_position_retained_after_entering_then_exiting_dir() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path =
+            PathBuf::from(format!("{}/nested_dir", tmp_dir.path().to_str().unwrap()));
+        let _nested_dir = create_dir(&nested_dir_path);
+        let _other_dir = create_dir(tmp_dir.path().join("another_dir"));
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+
+        let frame_size = Size {
+            width: 1,
+            height: 5,
+        };
+
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        assert_eq!(app.current_cursor_position(), 0);
+
+        // Change cursor position to 1
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 1);
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+
+        // Entering directory sets cursor position to 0, as this is the first time entering
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(app.current_dir_path, nested_dir_path);
+
+        // Exiting directory sets cursor position back to 1
+        app.handle_key_event(KeyCode::Backspace.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 1);
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+    }
+
+    #[test]
+    fn entering_a_new_sub_directory_starts_cursor_position_at_0() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path_0 =
+            PathBuf::from(format!("{}/nested_dir_0", tmp_dir.path().to_str().unwrap()));
+        let nested_dir_path_1 =
+            PathBuf::from(format!("{}/nested_dir_1", tmp_dir.path().to_str().unwrap()));
+        let _nested_dir_0 = create_dir(&nested_dir_path_0);
+        let _nested_dir_1 = create_dir(&nested_dir_path_1);
+
+        let nested_file_path_0 = nested_dir_path_0.join("file_a.txt");
+        let nested_file_path_1 = nested_dir_path_0.join("file_b.txt");
+        let _nested_file_0 = File::create(&nested_file_path_0).unwrap();
+        let _nested_file_1 = File::create(&nested_file_path_1).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+
+        let frame_size = Size {
+            width: 1,
+            height: 5,
+        };
+
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        assert_eq!(app.current_cursor_position(), 0);
+
+        // Entering directory sets cursor position to 0, as this is the first time entering
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(app.current_dir_path, nested_dir_path_0);
+
+        // Can change this directories cursor position
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 1);
+
+        // Exiting directory sets cursor position back to 0
+        app.handle_key_event(KeyCode::Backspace.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+
+        // Move cursor to other directory
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 1);
+
+        // Go into this new directory
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(app.current_dir_path, nested_dir_path_1);
+
+        // Exiting directory again sets cursor position back to 1
+        app.handle_key_event(KeyCode::Backspace.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 1);
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+
+        // Entering first sub directory and cursor position is 0
+        app.handle_key_event(KeyCode::Up.into(), frame_size);
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(app.current_dir_path, nested_dir_path_0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_linked_files_show_their_link_count() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let original_path = tmp_dir.path().join("original.txt");
+        let link_path = tmp_dir.path().join("link.txt");
+        std::fs::write(&original_path, "shared").unwrap();
+        std::fs::hard_link(&original_path, &link_path).unwrap();
+        std::fs::write(tmp_dir.path().join("single.txt"), "alone").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        assert!(app.hard_links.is_empty());
+
+        app.config.show_hard_links = true;
+        app.update_current_dir_contents();
+        assert_eq!(
+            app.hard_links,
+            HashMap::from([(original_path, 2), (link_path, 2)])
+        );
+        let rendered_text = rendered_text(&app, 60, 6);
+        assert!(rendered_text.contains("link.txt ⇄2"));
+        assert!(rendered_text.contains("original.txt ⇄2"));
+        assert!(!rendered_text.contains("single.txt ⇄"));
+    }
+
+    #[test]
+    fn previewing_large_file_asks_for_confirmation() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let large_path = tmp_dir.path().join("large.log");
+        File::create(&large_path).unwrap().set_len(2048).unwrap();
+        std::fs::write(tmp_dir.path().join("small.txt"), "small").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.large_file_threshold = 1024;
+
+        // Above the threshold, nothing is read until confirmed
+        app.handle_key_event(KeyCode::Char('c').into(), Size::default());
+        assert!(!app.view_file);
+        assert_eq!(
+            app.pending_confirmation,
+            Some(Confirmation::new(
+                "This file is 2.0K — preview anyway?".to_string(),
+                PendingAction::Preview
+            ))
+        );
+        app.handle_key_event(KeyCode::Char('y').into(), Size::default());
+        assert_eq!(app.previewed_path(), Some(&large_path));
+
+        // Closing it doesn't ask
+        app.handle_key_event(KeyCode::Char('c').into(), Size::default());
+        assert!(!app.view_file);
+        assert_eq!(app.pending_confirmation, None);
+
+        // Below it, the preview opens straight away
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.handle_key_event(KeyCode::Char('c').into(), Size::default());
+        assert_eq!(app.pending_confirmation, None);
+        assert_eq!(
+            app.previewed_path(),
+            Some(&tmp_dir.path().join("small.txt"))
+        );
+    }
+
+    #[test]
+    fn open_preview_holds_back_large_files_the_cursor_moves_onto() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        std::fs::write(tmp_dir.path().join("a_small.txt"), "small").unwrap();
+        std::fs::write(tmp_dir.path().join("b_big.log"), "secret\n".repeat(500)).unwrap();
+        let frame_size = Size::new(120, 10);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.large_file_threshold = 1024;
+        app.handle_key_event(KeyCode::Char('c').into(), frame_size);
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        app.on_tick(Instant::now(), frame_size);
+        let text = rendered_text(&app, frame_size.width, frame_size.height);
+        assert!(text.contains("This file is 3.4K — press c to preview it"));
+        assert!(!text.contains("secret"));
+        assert_eq!(app.selected_preview_text(), None);
+
+        app.handle_key_event(KeyCode::Char('c').into(), frame_size);
+        assert_eq!(
+            app.pending_confirmation,
+            Some(Confirmation::new(
+                "This file is 3.4K — preview anyway?".to_string(),
+                PendingAction::Preview
+            ))
+        );
+        app.handle_key_event(KeyCode::Char('y').into(), frame_size);
+        app.on_tick(Instant::now(), frame_size);
+        assert!(app.view_file);
+        let text = rendered_text(&app, frame_size.width, frame_size.height);
+        assert!(!text.contains("press c to preview"));
+        assert!(text.contains("secret"));
+    }
+
+    #[test]
+    fn entering_large_dir_asks_for_confirmation() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+        for file_name in ["a.txt", "b.txt", "c.txt"] {
+            let _tmp_file = File::create(nested_dir_path.join(file_name)).unwrap();
+        }
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.large_dir_threshold = 2;
+
+        let frame_size = Size {
+            width: 1,
+            height: 5,
+        };
+
+        // Above the threshold, the directory is not entered until confirmed
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        assert_eq!(
+            app.pending_confirmation,
+            Some(Confirmation::new(
+                "This directory has 3 items — continue?".to_string(),
+                PendingAction::EnterDir(nested_dir_path.clone())
+            ))
+        );
+
+        // Any other key cancels
+        app.handle_key_event(KeyCode::Char('n').into(), frame_size);
+        assert_eq!(app.pending_confirmation, None);
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        app.handle_key_event(KeyCode::Char('y').into(), frame_size);
+        assert_eq!(app.pending_confirmation, None);
+        assert_eq!(app.current_dir_path, nested_dir_path);
+        assert_eq!(app.current_dir_contents.len(), 3);
+    }
+
+    #[test]
+    fn can_add_selected_dir_to_favorites() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+        let favorites_path = tmp_dir.path().join("favorites");
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.favorites = Favorites::load(favorites_path.clone());
+
+        app.handle_key_event(KeyCode::Char('F').into(), Size::default());
+        assert!(app.favorites.paths().contains(&nested_dir_path));
+
+        // The favorite is persisted straight away
+        assert!(
+            Favorites::load(favorites_path)
+                .paths()
+                .contains(&nested_dir_path)
+        );
+    }
+
+    #[test]
+    fn starring_files_and_filtering_to_them() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let a_path = tmp_dir.path().join("a.txt");
+        File::create(&a_path).unwrap();
+        File::create(tmp_dir.path().join("b.txt")).unwrap();
+        create_dir(tmp_dir.path().join("nested_dir")).unwrap();
+        let storage_dir = TempDir::new("storage_dir").unwrap();
+        let starred_path = storage_dir.path().join("starred");
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.starred = Starred::load(starred_path.clone());
+
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.handle_key_event(KeyCode::Char('*').into(), Size::default());
+        assert!(app.starred.contains(&a_path));
+        assert!(Starred::load(starred_path.clone()).contains(&a_path));
+
+        let rendered_text = rendered_text(&app, 40, 6);
+        assert!(rendered_text.contains("> ★ a.txt"));
+        assert!(rendered_text.contains("    b.txt"));
+
+        // Directories have favorites instead
+        app.cursor_positions[app.current_cursor_depth] = 0;
+        app.handle_key_event(KeyCode::Char('*').into(), Size::default());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Only files can be starred")
+        );
+
+        app.handle_key_event(KeyCode::Char('f').into(), Size::default());
+        assert_eq!(app.current_dir_contents, vec![a_path.clone()]);
+        assert_eq!(app.view_modifiers(), "[f:star]");
+
+        // Unstarring leaves nothing to list
+        app.handle_key_event(KeyCode::Char('*').into(), Size::default());
+        assert!(app.current_dir_contents.is_empty());
+        assert!(!Starred::load(starred_path).contains(&a_path));
+    }
+
+    #[test]
+    fn selecting_a_favorite_jumps_to_it() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path_0 = tmp_dir.path().join("nested_dir_0");
+        let nested_dir_path_1 = tmp_dir.path().join("nested_dir_1");
+        let _nested_dir_0 = create_dir(&nested_dir_path_0);
+        let _nested_dir_1 = create_dir(&nested_dir_path_1);
+        let nested_file_path = nested_dir_path_1.join("file.txt");
+        let _nested_file = File::create(&nested_file_path).unwrap();
+
+        let mut app = new_app(nested_dir_path_0.clone());
+        app.favorites = Favorites::default();
+        app.favorites.add(tmp_dir.path());
+        app.favorites.add(&nested_dir_path_1);
+
+        let frame_size = Size {
+            width: 1,
+            height: 5,
+        };
+
+        app.handle_key_event(KeyCode::Char('b').into(), frame_size);
+        assert!(app.show_favorites);
+        assert!(app.favorites_focused);
+
+        // Navigation keys move within the sidebar while it has focus
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(app.current_dir_path, nested_dir_path_1);
+        assert_eq!(app.current_dir_contents, vec![nested_file_path]);
+        assert!(app.show_favorites);
+        assert!(!app.favorites_focused);
+
+        // Can still leave the favorite's directory as normal
+        app.handle_key_event(KeyCode::Backspace.into(), frame_size);
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+    }
+
+    #[test]
+    fn summarized_view_collapses_runs_of_extensions_until_expanded() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("a.txt");
+        let _tmp_file = File::create(&file_path).unwrap();
+        let image_paths: Vec<PathBuf> = (0..4)
+            .map(|i| tmp_dir.path().join(format!("img_{i}.jpg")))
+            .collect();
+        for image_path in &image_paths {
+            let _image_file = File::create(image_path).unwrap();
+        }
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.min_extension_group_size = 3;
+
+        let frame_size = Size {
+            width: 1,
+            height: 10,
+        };
+
+        app.handle_key_event(KeyCode::Char('z').into(), frame_size);
+        assert_eq!(
+            app.current_dir_contents,
+            vec![file_path.clone(), image_paths[0].clone()]
+        );
+
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(app.current_dir_contents.len(), 5);
+        assert_eq!(app.current_dir_contents[1..], image_paths);
+        assert_eq!(app.current_cursor_position(), 1);
+
+        // Leaving summarized mode shows everything
+        app.handle_key_event(KeyCode::Char('z').into(), frame_size);
+        assert_eq!(app.current_dir_contents.len(), 5);
+        assert!(app.extension_groups.is_empty());
+    }
+
+    #[test]
+    fn enter_on_file_runs_configured_open_action() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+        let file_path = tmp_dir.path().join("file.txt");
+        let _tmp_file = File::create(&file_path).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.file_enter_action = Some(FileOpenAction::Preview);
+
+        let frame_size = Size {
+            width: 1,
+            height: 5,
+        };
+
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert!(app.view_file);
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+
+        app.config.file_enter_action = Some(FileOpenAction::SystemOpener);
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(
+            app.pending_external_command
+                .take()
+                .unwrap()
+                .get_args()
+                .last()
+                .unwrap(),
+            file_path.as_os_str()
+        );
+
+        // Directories are still navigated into
+        app.handle_key_event(KeyCode::Up.into(), frame_size);
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(app.current_dir_path, nested_dir_path);
+    }
+
+    #[test]
+    fn enter_on_dir_navigates_by_default() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.dir_enter_behavior = DirEnterBehavior::Navigate;
+
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(app.current_dir_path, nested_dir_path);
+        assert!(!app.view_file);
+    }
+
+    #[test]
+    fn enter_on_dir_peeks_before_navigating_in_peek_mode() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.dir_enter_behavior = DirEnterBehavior::Peek;
+
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        assert!(app.view_file);
+
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(app.current_dir_path, nested_dir_path);
+        assert!(!app.view_file);
+    }
+
+    #[test]
+    fn enter_on_file_does_nothing_without_open_action() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        let _tmp_file = File::create(&file_path).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert!(!app.view_file);
+        assert!(app.pending_external_command.is_none());
+    }
+
+    #[test]
+    fn pasting_marked_files_copies_them_after_confirmation() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source_dir_path = tmp_dir.path().join("source");
+        let _source_dir = create_dir(&source_dir_path);
+        std::fs::write(source_dir_path.join("a.txt"), "aaaa").unwrap();
+        std::fs::write(source_dir_path.join("b.txt"), "bb").unwrap();
+        let destination_dir_path = tmp_dir.path().join("destination");
+        let _destination_dir = create_dir(&destination_dir_path);
+
+        let mut app = new_app(source_dir_path.clone());
+
+        let frame_size = Size {
+            width: 1,
+            height: 5,
+        };
+
+        app.handle_key_event(KeyCode::Char('V').into(), frame_size);
+        app.handle_key_event(KeyCode::Char(' ').into(), frame_size);
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        app.handle_key_event(KeyCode::Char(' ').into(), frame_size);
+        assert_eq!(app.marked.len(), 2);
+
+        app.jump_to_dir(destination_dir_path.clone());
+        app.request_paste_marked_files(Some(1024));
+        assert_eq!(
+            app.pending_confirmation,
+            Some(Confirmation::new(
+                "Copy 2 marked items (6B) here?".to_string(),
+                PendingAction::PasteMarked
+            ))
+        );
+
+        app.handle_key_event(KeyCode::Char('y').into(), frame_size);
+        assert_eq!(
+            app.current_dir_contents,
+            vec![
+                destination_dir_path.join("a.txt"),
+                destination_dir_path.join("b.txt")
+            ]
+        );
+        assert!(app.marked.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_that_fails_part_way_is_removed_again() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source_dir_path = tmp_dir.path().join("source");
+        let nested_dir_path = source_dir_path.join("nested");
+        std::fs::create_dir_all(&nested_dir_path).unwrap();
+        std::fs::write(nested_dir_path.join("a.txt"), "aaaa").unwrap();
+        let locked_path = nested_dir_path.join("locked.txt");
+        std::fs::write(&locked_path, "secret").unwrap();
+        std::fs::set_permissions(&locked_path, std::fs::Permissions::from_mode(0o000)).unwrap();
+        let destination_dir_path = tmp_dir.path().join("destination");
+        create_dir(&destination_dir_path).unwrap();
+
+        let mut app = new_app(destination_dir_path.clone());
+        app.copy_here(&[nested_dir_path]);
+
+        // Root can read it regardless, so this only checks the rollback when the copy really fails
+        if File::open(&locked_path).is_err() {
+            assert!(!destination_dir_path.join("nested").exists());
+            assert!(app.current_dir_contents.is_empty());
+            assert_eq!(
+                app.status_message.as_deref(),
+                Some("Unable to copy 1 of 1 items, removed 1 partial copies")
+            );
+        }
+        std::fs::set_permissions(&locked_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+    }
+
+    #[test]
+    fn moving_marked_files_removes_them_from_their_old_directory() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source_dir_path = tmp_dir.path().join("source");
+        let _source_dir = create_dir(&source_dir_path);
+        std::fs::write(source_dir_path.join("a.txt"), "aaaa").unwrap();
+        let destination_dir_path = tmp_dir.path().join("destination");
+        let _destination_dir = create_dir(&destination_dir_path);
+
+        let mut app = new_app(source_dir_path.clone());
+        let frame_size = Size::default();
+
+        app.handle_key_event(KeyCode::Char('V').into(), frame_size);
+        app.handle_key_event(KeyCode::Char(' ').into(), frame_size);
+        app.jump_to_dir(destination_dir_path.clone());
+        app.handle_key_event(KeyCode::Char('M').into(), frame_size);
+        assert_eq!(
+            app.pending_confirmation,
+            Some(Confirmation::new(
+                "Move 1 marked items here?".to_string(),
+                PendingAction::MoveMarked
+            ))
+        );
+
+        app.handle_key_event(KeyCode::Char('y').into(), frame_size);
+        assert_eq!(
+            app.current_dir_contents,
+            vec![destination_dir_path.join("a.txt")]
+        );
+        assert!(!source_dir_path.join("a.txt").exists());
+        assert_eq!(app.status_message.as_deref(), Some("Moved 1 items"));
+    }
+
+    #[test]
+    fn moving_marked_files_skips_the_prompt_when_confirmations_are_off() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source_dir_path = tmp_dir.path().join("source");
+        let _source_dir = create_dir(&source_dir_path);
+        std::fs::write(source_dir_path.join("a.txt"), "aaaa").unwrap();
+        let destination_dir_path = tmp_dir.path().join("destination");
+        let _destination_dir = create_dir(&destination_dir_path);
+
+        let mut app = new_app(source_dir_path.clone());
+        app.config.confirm_destructive = false;
+        let frame_size = Size::default();
+
+        app.handle_key_event(KeyCode::Char('V').into(), frame_size);
+        app.handle_key_event(KeyCode::Char(' ').into(), frame_size);
+        app.jump_to_dir(destination_dir_path.clone());
+        app.handle_key_event(KeyCode::Char('M').into(), frame_size);
+        assert_eq!(app.pending_confirmation, None);
+        assert!(destination_dir_path.join("a.txt").exists());
+        assert!(!source_dir_path.join("a.txt").exists());
+    }
+
+    #[test]
+    fn pasting_over_existing_files_still_asks_and_leaves_them_alone() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source_dir_path = tmp_dir.path().join("source");
+        let _source_dir = create_dir(&source_dir_path);
+        std::fs::write(source_dir_path.join("a.txt"), "new").unwrap();
+        let destination_dir_path = tmp_dir.path().join("destination");
+        let _destination_dir = create_dir(&destination_dir_path);
+        std::fs::write(destination_dir_path.join("a.txt"), "old").unwrap();
+
+        let mut app = new_app(source_dir_path.clone());
+        app.config.confirm_destructive = false;
+        let frame_size = Size::default();
+
+        app.handle_key_event(KeyCode::Char('V').into(), frame_size);
+        app.handle_key_event(KeyCode::Char(' ').into(), frame_size);
+        app.jump_to_dir(destination_dir_path.clone());
+        app.request_paste_marked_files(None);
+        assert!(app.pending_confirmation.is_some());
+
+        app.handle_key_event(KeyCode::Char('y').into(), frame_size);
+        assert_eq!(
+            std::fs::read_to_string(destination_dir_path.join("a.txt")).unwrap(),
+            "old"
+        );
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Unable to copy 1 of 1 items")
+        );
+    }
+
+    #[test]
+    fn collected_files_are_exported_in_collection_order() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let first_dir_path = tmp_dir.path().join("first");
+        let _first_dir = create_dir(&first_dir_path);
+        std::fs::write(first_dir_path.join("a.txt"), "aaaa").unwrap();
+        std::fs::write(first_dir_path.join("b.txt"), "bb").unwrap();
+        let second_dir_path = tmp_dir.path().join("second");
+        let _second_dir = create_dir(&second_dir_path);
+        std::fs::write(second_dir_path.join("c.txt"), "c").unwrap();
+        let destination_dir_path = tmp_dir.path().join("destination");
+        let _destination_dir = create_dir(&destination_dir_path);
+
+        let mut app = new_app(second_dir_path.clone());
+        let frame_size = Size::default();
+
+        app.handle_key_event(KeyCode::Char('C').into(), frame_size);
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+
+        app.jump_to_dir(first_dir_path.clone());
+        app.handle_key_event(KeyCode::Down.into(), frame_size);
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        app.handle_key_event(KeyCode::Up.into(), frame_size);
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(
+            app.selection_list.paths(),
+            [
+                second_dir_path.join("c.txt"),
+                first_dir_path.join("b.txt"),
+                first_dir_path.join("a.txt"),
+            ]
+        );
+
+        // Enter over a collected file removes it
+        app.handle_key_event(KeyCode::Enter.into(), frame_size);
+        assert_eq!(
+            app.selection_list.paths(),
+            [second_dir_path.join("c.txt"), first_dir_path.join("b.txt")]
+        );
+
+        app.jump_to_dir(destination_dir_path.clone());
+        app.request_export_selection_list(Some(1024));
+        assert_eq!(
+            app.pending_confirmation,
+            Some(Confirmation::new(
+                "Copy 2 collected items (3B) here?".to_string(),
+                PendingAction::ExportSelectionList
+            ))
+        );
+
+        app.handle_key_event(KeyCode::Char('y').into(), frame_size);
+        assert_eq!(
+            app.current_dir_contents,
+            vec![
+                destination_dir_path.join("b.txt"),
+                destination_dir_path.join("c.txt")
+            ]
+        );
+        assert!(app.selection_list.is_empty());
+    }
+
+    #[test]
+    fn pasting_marked_files_is_blocked_without_enough_space() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        std::fs::write(tmp_dir.path().join("big.txt"), "x".repeat(2048)).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('V').into(), Size::default());
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+
+        app.request_paste_marked_files(Some(1024));
+        assert_eq!(app.pending_confirmation, None);
+        assert_eq!(
+            app.status_message,
+            Some("Not enough space: need 2.0K, have 1.0K".to_string())
+        );
+    }
+
+    #[test]
+    fn delete_waits_for_confirmation_by_default() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        let _file = File::create(&file_path).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.confirm_destructive = true;
+
+        app.handle_key_event(KeyCode::Char('d').into(), Size::default());
+        assert_eq!(
+            app.pending_confirmation,
+            Some(Confirmation::new(
+                "Delete file.txt?".to_string(),
+                PendingAction::Delete(file_path.clone())
+            ))
+        );
+        assert!(file_path.exists());
+
+        app.handle_key_event(KeyCode::Char('y').into(), Size::default());
+        assert!(!file_path.exists());
+        assert!(app.current_dir_contents.is_empty());
+    }
+
+    #[test]
+    fn delete_runs_immediately_without_confirmation() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+        let _nested_file = File::create(nested_dir_path.join("file.txt")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.confirm_destructive = false;
+
+        app.handle_key_event(KeyCode::Char('d').into(), Size::default());
+        assert_eq!(app.pending_confirmation, None);
+        assert!(!nested_dir_path.exists());
+        assert_eq!(app.status_message, Some("Deleted nested_dir".to_string()));
+    }
+
+    #[test]
+    fn custom_comparator_decides_order() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_paths: Vec<PathBuf> = ["a.txt", "b.txt", "README.md", "z.txt"]
+            .iter()
+            .map(|file_name| tmp_dir.path().join(file_name))
+            .collect();
+        for file_path in &file_paths {
+            let _tmp_file = File::create(file_path).unwrap();
+        }
+
+        // Pins the README to the top, leaving everything else alphabetical
+        let app = new_app(tmp_dir.path().to_path_buf()).with_comparator(|a, b| {
+            let is_readme = |path: &Path| path.file_name() == Some(OsStr::new("README.md"));
+            is_readme(b).cmp(&is_readme(a)).then_with(|| a.cmp(b))
+        });
+
+        assert_eq!(
+            app.current_dir_contents,
+            vec![
+                file_paths[2].clone(),
+                file_paths[0].clone(),
+                file_paths[1].clone(),
+                file_paths[3].clone()
+            ]
+        );
+    }
+
+    #[test]
+    fn footer_shows_summary_of_selected_dir() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+        let _nested_nested_dir = create_dir(nested_dir_path.join("nested_nested_dir"));
+        let _nested_file = File::create(nested_dir_path.join("file.txt")).unwrap();
+        let _tmp_file = File::create(tmp_dir.path().join("z.txt")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.on_tick(Instant::now(), Size::new(40, 10));
+
+        assert_eq!(
+            app.selected_dir_summary(),
+            Some(&DirSummary::Readable { items: 2, dirs: 1 })
+        );
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 40, 3));
+        app.render(buf.area, &mut buf);
+        let footer: String = (0..40)
+            .map(|x| buf.cell((x, 2)).unwrap().symbol())
+            .collect();
+        assert!(footer.ends_with(" 2 items, 1 dir ┛"));
+
+        // Files have no summary
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        assert_eq!(app.selected_dir_summary(), None);
+    }
+
+    #[test]
+    fn only_dirs_on_screen_are_counted() {
+        /*
+        With two rows per column and room for two columns
+        dir_00    dir_02    dir_04 ...
+        dir_01    dir_03    dir_05 ...
+        */
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let dir_paths: Vec<PathBuf> = (0..20)
+            .map(|index| tmp_dir.path().join(format!("dir_{index:02}")))
+            .collect();
+        for dir_path in &dir_paths {
+            create_dir(dir_path).unwrap();
+            File::create(dir_path.join("inner.txt")).unwrap();
+        }
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.max_columns = Some(2);
+        let frame_size = Size::new(40, 5);
+
+        // Only the selected directory is summarized for the footer
+        app.on_tick(Instant::now(), frame_size);
+        assert_eq!(
+            sorted(app.dir_summaries.keys()).collect::<Vec<_>>(),
+            vec![&dir_paths[0]]
+        );
+
+        app.handle_key_event(KeyCode::Char('#').into(), frame_size);
+        assert_eq!(
+            sorted(app.dir_summaries.keys()).collect::<Vec<_>>(),
+            dir_paths[..4].iter().collect::<Vec<_>>()
+        );
+        assert!(rendered_text(&app, 40, 5).contains("> dir_00 1"));
+
+        // Scrolling right counts the newly shown column, keeping the counts already read
+        app.handle_key_event(KeyCode::Right.into(), frame_size);
+        app.handle_key_event(KeyCode::Right.into(), frame_size);
+        app.on_tick(Instant::now(), frame_size);
+        assert_eq!(
+            sorted(app.dir_summaries.keys()).collect::<Vec<_>>(),
+            dir_paths[..6].iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn new_file_is_created_and_selected() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        std::fs::write(tmp_dir.path().join("a.txt"), "keep me").unwrap();
+        let _file = File::create(tmp_dir.path().join("c.txt")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let type_name = |app: &mut App, name: &str| {
+            app.handle_key_event(KeyCode::Char('n').into(), Size::default());
+            for character in name.chars() {
+                app.handle_key_event(KeyCode::Char(character).into(), Size::default());
+            }
+            app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        };
+
+        type_name(&mut app, "b.txt");
+        let new_file_path = tmp_dir.path().join("b.txt");
+        assert!(new_file_path.is_file());
+        assert_eq!(app.selected_entry(), Some(&new_file_path));
+        assert_eq!(app.name_prompt, None);
+
+        // An existing file is left alone, and the prompt stays open to try again
+        type_name(&mut app, "a.txt");
+        assert_eq!(app.status_message.as_deref(), Some("a.txt already exists"));
+        assert_eq!(
+            std::fs::read_to_string(tmp_dir.path().join("a.txt")).unwrap(),
+            "keep me"
+        );
+        assert!(app.name_prompt.is_some());
+        app.handle_key_event(KeyCode::Esc.into(), Size::default());
+
+        type_name(&mut app, &"x".repeat(300));
+        assert!(
+            app.status_message
+                .as_deref()
+                .is_some_and(|message| message.ends_with("is too long or not allowed"))
+        );
+    }
+
+    #[test]
+    fn navigation_is_saved_as_a_cd_script() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let docs_path = tmp_dir.path().join("docs");
+        let music_path = tmp_dir.path().join("music");
+        create_dir(&docs_path).unwrap();
+        create_dir(&music_path).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        app.handle_key_event(KeyCode::Char('[').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('S').into(), Size::default());
+
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Saved 5 directories to navigation.sh")
+        );
+        let cd = |path: &Path| format!("cd '{}'", path.display());
+        assert_eq!(
+            std::fs::read_to_string(tmp_dir.path().join("navigation.sh"))
+                .unwrap()
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "#!/bin/sh".to_string(),
+                cd(tmp_dir.path()),
+                cd(&docs_path),
+                cd(tmp_dir.path()),
+                cd(&music_path),
+                cd(tmp_dir.path()),
+            ]
+        );
+
+        // Saving again leaves the first script alone
+        app.handle_key_event(KeyCode::Char('S').into(), Size::default());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("navigation.sh already exists")
+        );
+    }
+
+    #[test]
+    fn yanked_file_is_pasted_as_a_copy() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        create_dir(&nested_dir_path).unwrap();
+        std::fs::write(tmp_dir.path().join("notes.txt"), "notes").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('p').into(), Size::default());
+        assert_eq!(app.status_message.as_deref(), Some("Nothing is yanked"));
+
+        app.select_entry_by_name(OsStr::new("notes.txt"));
+        app.handle_key_event(KeyCode::Char('y').into(), Size::default());
+        assert_eq!(app.status_message.as_deref(), Some("Yanked notes.txt"));
+
+        app.select_entry_by_name(OsStr::new("nested_dir"));
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        app.handle_key_event(KeyCode::Char('p').into(), Size::default());
+        let pasted_path = nested_dir_path.join("notes.txt");
+        assert_eq!(std::fs::read_to_string(&pasted_path).unwrap(), "notes");
+        assert_eq!(app.selected_entry(), Some(&pasted_path));
+        assert_eq!(app.status_message.as_deref(), Some("Pasted notes.txt"));
+
+        // Pasting where the name is taken keeps both
+        app.handle_key_event(KeyCode::Char('p').into(), Size::default());
+        let copy_path = nested_dir_path.join("notes (copy).txt");
+        assert_eq!(std::fs::read_to_string(&copy_path).unwrap(), "notes");
+        assert_eq!(app.selected_entry(), Some(&copy_path));
+        assert_eq!(std::fs::read_to_string(&pasted_path).unwrap(), "notes");
+    }
+
+    #[test]
+    fn yanked_directory_is_pasted_with_everything_in_it() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let photos_path = tmp_dir.path().join("photos");
+        create_dir(&photos_path).unwrap();
+        create_dir(photos_path.join("2024")).unwrap();
+        std::fs::write(photos_path.join("2024").join("beach.jpg"), "beach").unwrap();
+        std::fs::write(photos_path.join("cat.jpg"), "cat").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('y').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('p').into(), Size::default());
+
+        let copy_path = tmp_dir.path().join("photos (copy)");
+        assert_eq!(
+            std::fs::read_to_string(copy_path.join("2024").join("beach.jpg")).unwrap(),
+            "beach"
+        );
+        assert_eq!(
+            std::fs::read_to_string(copy_path.join("cat.jpg")).unwrap(),
+            "cat"
+        );
+        assert_eq!(app.selected_entry(), Some(&copy_path));
+
+        // Pasting a directory inside itself would never finish
+        app.select_entry_by_name(OsStr::new("photos"));
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        app.handle_key_event(KeyCode::Char('p').into(), Size::default());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Can't paste photos inside itself")
+        );
+        assert_eq!(std::fs::read_dir(&photos_path).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn preview_scrolls_to_its_last_line_and_resets_for_the_next_file() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let lines: Vec<String> = (1..=30).map(|number| format!("line {number}")).collect();
+        std::fs::write(tmp_dir.path().join("a.txt"), lines.join("\n")).unwrap();
+        std::fs::write(tmp_dir.path().join("b.txt"), "b").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('c').into(), Size::default());
+        for _ in 0..3 {
+            app.handle_key_event(KeyCode::Char('j').into(), Size::default());
+        }
+        app.handle_key_event(KeyCode::Char('k').into(), Size::default());
+        assert_eq!(app.file_scroll_offset, 2);
+        let text = rendered_text(&app, 80, 10);
+        assert!(text.contains("line 3"));
+        assert!(!text.contains("line 2 "));
+
+        for _ in 0..50 {
+            app.handle_key_event(KeyCode::Char('j').into(), Size::default());
+        }
+        assert_eq!(app.file_scroll_offset, 29);
+        assert!(rendered_text(&app, 80, 10).contains("line 30"));
+
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.on_tick(Instant::now(), Size::new(80, 10));
+        assert_eq!(app.file_scroll_offset, 0);
+    }
+
+    #[test]
+    fn preview_scrolls_sideways_up_to_the_end_of_the_longest_line() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        std::fs::write(tmp_dir.path().join("wide.txt"), "short\nabcdefghij").unwrap();
+        let frame_size = Size::new(80, 10);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char(' ').into(), frame_size);
+        for _ in 0..3 {
+            app.handle_key_event(KeyCode::Right.into(), frame_size);
+        }
+        app.handle_key_event(KeyCode::Left.into(), frame_size);
+        assert_eq!(app.preview_horizontal_offset, 2);
+        let text = rendered_text(&app, 80, 10);
+        assert!(text.contains("cdefghij") && !text.contains("bcdefghij"));
+        assert!(text.contains("ort") && !text.contains("short"));
+
+        for _ in 0..20 {
+            app.handle_key_event(KeyCode::Right.into(), frame_size);
+        }
+        assert_eq!(app.preview_horizontal_offset, 9);
+
+        // Closing Quick Look starts the next preview at the left edge again
+        app.handle_key_event(KeyCode::Esc.into(), frame_size);
+        assert_eq!(app.preview_horizontal_offset, 0);
+
+        // The right pane scrolls with shift held, as the arrows alone move the cursor
+        app.handle_key_event(KeyCode::Char('c').into(), frame_size);
+        let shift_right = KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT);
+        app.handle_key_event(shift_right, frame_size);
+        app.handle_key_event(shift_right, frame_size);
+        assert_eq!(app.preview_horizontal_offset, 2);
+        app.on_tick(Instant::now(), frame_size);
+        assert!(rendered_text(&app, 80, 10).contains("cdefghij"));
+    }
+
+    #[test]
+    fn image_preview_is_left_for_the_terminal_to_draw() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let image_path = tmp_dir.path().join("pixel.png");
+        let mut contents = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        contents.extend(3u32.to_be_bytes());
+        contents.extend(2u32.to_be_bytes());
+        std::fs::write(&image_path, contents).unwrap();
+        let draw = |app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+            let mut image_placement = None;
+            terminal
+                .draw(|frame| image_placement = app.draw(frame))
+                .unwrap();
+            image_placement
+        };
+
+        // Without an image protocol, the usual preview is drawn
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('c').into(), Size::default());
+        app.on_tick(Instant::now(), Size::new(80, 10));
+        assert_eq!(draw(&app), None);
+        assert!(rendered_text(&app, 80, 10).contains("Dimensions"));
+
+        app.image_protocol = Some(ImageProtocol::Kitty);
+        let image_placement = draw(&app).unwrap();
+        assert_eq!(image_placement.path, image_path);
+        assert!(image_placement.area.x >= 40 && image_placement.area.height > 0);
+        assert!(!rendered_text(&app, 80, 10).contains("Dimensions"));
+
+        // Nothing may be drawn over the image
+        app.handle_key_event(KeyCode::Char('?').into(), Size::default());
+        assert_eq!(draw(&app), None);
+    }
+
+    #[test]
+    fn cut_file_is_moved_where_it_is_pasted() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let inbox_path = tmp_dir.path().join("inbox");
+        create_dir(&inbox_path).unwrap();
+        std::fs::write(tmp_dir.path().join("notes.txt"), "notes").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.select_entry_by_name(OsStr::new("notes.txt"));
+        app.handle_key_event(KeyCode::Char('x').into(), Size::default());
+        assert_eq!(app.status_message.as_deref(), Some("Cut notes.txt"));
+
+        // The cut entry is dimmed until it is pasted
+        let theme = Theme::default();
+        let buffer = app.render_to_buffer(Rect::new(0, 0, 80, 10));
+        let (column, row) = (0..10)
+            .find_map(|y| {
+                let row: String = (0..80).map(|x| buffer[(x, y)].symbol()).collect();
+                row.find("notes.txt")
+                    .map(|byte| (row[..byte].chars().count() as u16, y))
+            })
+            .unwrap();
+        assert!(
+            buffer[(column, row)]
+                .style()
+                .add_modifier
+                .contains(theme.cut_style.add_modifier)
+        );
+
+        app.handle_key_event(KeyCode::Char('p').into(), Size::default());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("notes.txt is already here")
+        );
+
+        app.select_entry_by_name(OsStr::new("inbox"));
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        app.handle_key_event(KeyCode::Char('p').into(), Size::default());
+        let moved_path = inbox_path.join("notes.txt");
+        assert_eq!(app.status_message.as_deref(), Some("Moved notes.txt"));
+        assert_eq!(std::fs::read_to_string(&moved_path).unwrap(), "notes");
+        assert_eq!(app.selected_entry(), Some(&moved_path));
+        assert_eq!(app.yanked, None);
+
+        // The old directory no longer lists it once returned to
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        assert_eq!(app.current_dir_contents, vec![inbox_path]);
+    }
+
+    #[test]
+    fn yanked_file_swaps_places_with_the_selected_one() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let drafts_path = tmp_dir.path().join("drafts");
+        let final_path = tmp_dir.path().join("final");
+        create_dir(&drafts_path).unwrap();
+        create_dir(&final_path).unwrap();
+        std::fs::write(drafts_path.join("draft.txt"), "better").unwrap();
+        std::fs::write(final_path.join("report.txt"), "worse").unwrap();
+
+        let mut app = new_app(drafts_path.clone());
+        app.handle_key_event(KeyCode::Char('X').into(), Size::default());
+        assert_eq!(app.status_message.as_deref(), Some("Nothing is yanked"));
+
+        app.handle_key_event(KeyCode::Char('y').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('X').into(), Size::default());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("draft.txt is the yanked entry")
+        );
+
+        // The same entry reached another way
+        app.yanked = Some(final_path.join("..").join("drafts").join("draft.txt"));
+        app.status_message = None;
+        app.handle_key_event(KeyCode::Char('X').into(), Size::default());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("draft.txt is the yanked entry")
+        );
+
+        let mut app = new_app(final_path.clone());
+        app.yanked = Some(drafts_path.join("draft.txt"));
+        app.handle_key_event(KeyCode::Char('X').into(), Size::default());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Swapped draft.txt and report.txt")
+        );
+        assert_eq!(
+            std::fs::read_to_string(final_path.join("report.txt")).unwrap(),
+            "better"
+        );
+        assert_eq!(
+            std::fs::read_to_string(drafts_path.join("draft.txt")).unwrap(),
+            "worse"
+        );
+        assert_eq!(std::fs::read_dir(&drafts_path).unwrap().count(), 1);
+        assert_eq!(std::fs::read_dir(&final_path).unwrap().count(), 1);
+        assert_eq!(app.yanked, Some(final_path.join("report.txt")));
+    }
+
+    #[test]
+    fn diff_of_a_modified_file_is_shown_with_its_changes_colored() {
+        if Command::new("git").arg("--version").output().is_err() {
+            // Nothing to diff with
+            return;
+        }
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(tmp_dir.path())
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        std::fs::write(tmp_dir.path().join("notes.txt"), "old\n").unwrap();
+        git(&["init", "-q"]);
+        git(&["add", "notes.txt"]);
+        git(&["commit", "-q", "-m", "notes"]);
+        std::fs::write(tmp_dir.path().join("notes.txt"), "new\n").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.select_entry_by_name(OsStr::new("notes.txt"));
+        app.handle_key_event(KeyCode::Char('G').into(), Size::default());
+        assert_eq!(app.right_pane_mode, RightPaneMode::Diff);
+
+        // Drawing uses the diff worked out when G was pressed, rather than running git itself
+        let notes_path = tmp_dir.path().join("notes.txt");
+        assert!(matches!(&app.selected_diff, Some((path, Some(_))) if *path == notes_path));
+        let buffer = app.render_to_buffer(Rect::new(0, 0, 80, 20));
+        let style_of_line = |line: &str| {
+            (0..buffer.area.height).find_map(|y| {
+                let row: String = (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect();
+                // Borders take several bytes, so count characters to find the column
+                row.find(line)
+                    .map(|byte| row[..byte].chars().count() as u16)
+                    .map(|x| buffer[(x, y)].style().fg)
+            })
+        };
+        assert_eq!(style_of_line("+new"), Some(app.theme.diff_added_style.fg));
+        assert_eq!(style_of_line("-old"), Some(app.theme.diff_removed_style.fg));
+    }
+
+    #[test]
+    fn renamed_entry_stays_selected() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        std::fs::write(tmp_dir.path().join("a.txt"), "contents").unwrap();
+        let _file = File::create(tmp_dir.path().join("b.txt")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('R').into(), Size::default());
+        assert_eq!(
+            app.name_prompt
+                .as_ref()
+                .map(|name_prompt| name_prompt.input.as_str()),
+            Some("a.txt")
+        );
+        for key_code in [
+            KeyCode::Backspace,
+            KeyCode::Backspace,
+            KeyCode::Backspace,
+            KeyCode::Backspace,
+            KeyCode::Backspace,
+            KeyCode::Char('z'),
+            KeyCode::Enter,
+        ] {
+            app.handle_key_event(key_code.into(), Size::default());
+        }
+
+        let renamed_path = tmp_dir.path().join("z");
+        assert!(!tmp_dir.path().join("a.txt").exists());
+        assert_eq!(std::fs::read_to_string(&renamed_path).unwrap(), "contents");
+        assert_eq!(app.selected_entry(), Some(&renamed_path));
+        assert_eq!(app.name_prompt, None);
+        assert_eq!(app.status_message.as_deref(), Some("Renamed to z"));
+    }
+
+    #[test]
+    fn rename_refuses_path_separators_and_existing_names() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let a_path = tmp_dir.path().join("a.txt");
+        std::fs::write(&a_path, "a").unwrap();
+        std::fs::write(tmp_dir.path().join("b.txt"), "b").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('R').into(), Size::default());
+        for character in "/../a.txt".chars() {
+            app.handle_key_event(KeyCode::Char(character).into(), Size::default());
+        }
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Names can't contain / or NUL")
+        );
+        assert!(a_path.exists());
+        assert!(app.name_prompt.is_some());
+
+        // The prompt stays open to fix the name, but another entry's name is refused too
+        let name_prompt = app.name_prompt.as_mut().unwrap();
+        name_prompt.input = "b.txt".to_string();
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(app.status_message.as_deref(), Some("b.txt already exists"));
+        assert_eq!(std::fs::read_to_string(&a_path).unwrap(), "a");
+        assert_eq!(
+            std::fs::read_to_string(tmp_dir.path().join("b.txt")).unwrap(),
+            "b"
+        );
+
+        app.handle_key_event(KeyCode::Esc.into(), Size::default());
+        assert_eq!(app.name_prompt, None);
+        assert!(a_path.exists());
+    }
+
+    #[test]
+    fn typing_a_child_dir_name_descends_into_it() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let _file = File::create(tmp_dir.path().join("a.txt")).unwrap();
+        let _documents_dir = create_dir(tmp_dir.path().join("documents"));
+        let _music_dir = create_dir(tmp_dir.path().join("music"));
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let depth = app.current_cursor_depth;
+
+        for key_code in [
+            KeyCode::Char(':'),
+            KeyCode::Char('m'),
+            KeyCode::Tab,
+            KeyCode::Enter,
+        ] {
+            app.handle_key_event(key_code.into(), Size::default());
+        }
+
+        assert_eq!(app.current_dir_path, tmp_dir.path().join("music"));
+        assert_eq!(app.current_cursor_depth, depth + 1);
+        assert_eq!(app.cd_prompt, None);
+    }
+
+    #[test]
+    fn typing_a_missing_child_dir_name_shows_an_error() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let _file = File::create(tmp_dir.path().join("a.txt")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+
+        for key_code in [
+            KeyCode::Char(':'),
+            KeyCode::Char('a'),
+            KeyCode::Char('.'),
+            KeyCode::Char('t'),
+            KeyCode::Char('x'),
+            KeyCode::Char('t'),
+            KeyCode::Enter,
+        ] {
+            app.handle_key_event(key_code.into(), Size::default());
+        }
+
+        assert_eq!(app.current_dir_path, tmp_dir.path());
+        assert_eq!(
+            app.status_message,
+            Some("No directory named \"a.txt\"".to_string())
+        );
+    }
+
+    #[test]
+    fn auto_preview_follows_the_cursor() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let _nested_dir = create_dir(tmp_dir.path().join("nested_dir"));
+        let _file = File::create(tmp_dir.path().join("file.txt")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        assert_eq!(app.previewed_path(), None);
+
+        app.handle_key_event(KeyCode::Char('a').into(), Size::default());
+        assert_eq!(
+            app.previewed_path(),
+            Some(&tmp_dir.path().join("nested_dir"))
+        );
+
+        // Moving on previews the next entry, without pressing `c`
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        assert_eq!(app.previewed_path(), Some(&tmp_dir.path().join("file.txt")));
+
+        app.handle_key_event(KeyCode::Char('a').into(), Size::default());
+        assert_eq!(app.previewed_path(), None);
+    }
+
+    #[test]
+    fn entering_a_vanished_dir_stays_put() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let depth = app.current_cursor_depth;
+
+        std::fs::remove_dir(&nested_dir_path).unwrap();
+        app.go_into_dir();
+
+        assert_eq!(app.current_dir_path, tmp_dir.path());
+        assert_eq!(app.current_cursor_depth, depth);
+        assert_eq!(app.current_dir_contents, vec![nested_dir_path]);
+        assert!(
+            app.status_message
+                .as_ref()
+                .is_some_and(|message| message.starts_with("Unable to open nested_dir: "))
+        );
+    }
+
+    #[test]
+    fn resizing_a_column_only_applies_to_that_column_and_directory() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let _nested_dir = create_dir(tmp_dir.path().join("nested_dir"));
+        let _file_1 = File::create(tmp_dir.path().join("file_1.txt")).unwrap();
+        let _file_2 = File::create(tmp_dir.path().join("file_2.txt")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let frame_size = Size {
+            width: 1,
+            height: 4,
+        };
+
+        // Resizing does nothing until manual width mode is on
+        app.handle_key_event(KeyCode::Char('+').into(), frame_size);
+        assert!(app.column_width_adjustments.is_empty());
+
+        app.handle_key_event(KeyCode::Char('w').into(), frame_size);
+        app.handle_key_event(KeyCode::Right.into(), frame_size);
+        app.handle_key_event(KeyCode::Char('+').into(), frame_size);
+        app.handle_key_event(KeyCode::Char('+').into(), frame_size);
+        app.handle_key_event(KeyCode::Char('-').into(), frame_size);
+
+        assert_eq!(
+            app.column_width_adjustments,
+            HashMap::from([(tmp_dir.path().to_path_buf(), HashMap::from([(1, 1)]))])
+        );
+    }
+
+    #[test]
+    fn breadcrumb_marks_the_launch_dir_once_below_it() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let root = tmp_dir.path().to_str().unwrap();
+        assert_eq!(app.breadcrumb(), Line::from(root));
+
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        let root_name = tmp_dir.path().file_name().unwrap().to_str().unwrap();
+        assert_eq!(
+            app.breadcrumb(),
+            Line::from(vec![
+                Span::raw(&root[..root.len() - root_name.len()]),
+                Span::styled(root_name, Style::new().bold().underlined()),
+                Span::raw("/nested_dir"),
+            ])
+        );
+    }
+
+    #[test]
+    fn trailing_separators_are_normalized_away() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+        let with_trailing_separator =
+            |path: &Path| PathBuf::from(format!("{}/", path.to_str().unwrap()));
+
+        let mut app = new_app(with_trailing_separator(&nested_dir_path));
+        assert_eq!(
+            app.current_dir_path.as_os_str(),
+            nested_dir_path.as_os_str()
+        );
+        assert_eq!(
+            app.breadcrumb(),
+            Line::from(nested_dir_path.to_str().unwrap())
+        );
+
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        assert_eq!(app.current_dir_path.as_os_str(), tmp_dir.path().as_os_str());
+
+        // Jumping to a remembered directory normalizes it the same way
+        app.jump_to_dir(with_trailing_separator(&nested_dir_path));
+        assert_eq!(
+            app.current_dir_path.as_os_str(),
+            nested_dir_path.as_os_str()
+        );
+        assert_eq!(
+            app.current_cursor_depth,
+            nested_dir_path.ancestors().count() - 1
+        );
+    }
+
+    #[test]
+    fn footer_shows_scan_total_once_finished() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+        std::fs::write(nested_dir_path.join("a.txt"), "aaaa").unwrap();
+        let _nested_nested_dir = create_dir(nested_dir_path.join("nested_nested_dir"));
+        std::fs::write(
+            nested_dir_path.join("nested_nested_dir").join("b.txt"),
+            "bb",
+        )
+        .unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('u').into(), Size::default());
+        assert_eq!(
+            app.scan.as_ref().map(|scan| scan.path()),
+            Some(nested_dir_path.as_path())
+        );
+
+        // Stands in for the walk, so each stage can be drawn
+        let (scan, discovered, sender) = Scan::fed_by_hand(nested_dir_path.clone());
+        app.scan = Some(scan);
+        let footer = |app: &App| {
+            rendered_text(app, 40, 3)
+                .chars()
+                .skip(80)
+                .collect::<String>()
+        };
+
+        discovered.store(2, std::sync::atomic::Ordering::Relaxed);
+        assert!(footer(&app).ends_with(" scanning… 2 ┛"));
+
+        sender
+            .send(ScanTotals {
+                entries: 3,
+                bytes: 6,
+            })
+            .unwrap();
+        app.on_tick(Instant::now(), Size::default());
+        assert!(footer(&app).ends_with(" 3 items, 6B ┛"));
+
+        // Leaving the directory stops the scan
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert!(app.scan.is_none());
+    }
+
+    #[test]
+    fn gitignored_entries_are_hidden_when_toggled() {
+        let tmp_dir = TempDir::new("repo").unwrap();
+        let _git_dir = create_dir(tmp_dir.path().join(".git"));
+        std::fs::write(tmp_dir.path().join(".gitignore"), "*.log\ntarget/\n").unwrap();
+        let _target_dir = create_dir(tmp_dir.path().join("target"));
+        let _log_file = File::create(tmp_dir.path().join("debug.log")).unwrap();
+        let _tracked_file = File::create(tmp_dir.path().join("main.rs")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        assert_eq!(app.current_dir_contents.len(), 5);
+
+        app.handle_key_event(KeyCode::Char('I').into(), Size::default());
+        assert_eq!(
+            app.current_dir_contents,
+            vec![
+                tmp_dir.path().join(".git"),
+                tmp_dir.path().join(".gitignore"),
+                tmp_dir.path().join("main.rs"),
+            ]
+        );
+
+        // Ignored directories can be dimmed instead
+        app.config.gitignored_dirs = IgnoredDirDisplay::Dim;
+        app.update_current_dir_contents();
+        assert!(
+            app.current_dir_contents
+                .contains(&tmp_dir.path().join("target"))
+        );
+        assert!(
+            !app.current_dir_contents
+                .contains(&tmp_dir.path().join("debug.log"))
+        );
+        assert_eq!(
+            app.gitignored_dirs,
+            HashSet::from([tmp_dir.path().join("target")])
+        );
+    }
+
+    #[test]
+    fn can_select_entry_by_name() {
+        let mut app = App {
+            current_dir_contents: vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")],
+            current_dir_path: PathBuf::from("./"),
+            cursor_positions: vec![0],
+            ..Default::default()
+        };
+
+        assert!(app.select_entry_by_name(OsStr::new("c")));
+        assert_eq!(app.current_cursor_position(), 2);
+    }
+
+    #[test]
+    fn selecting_missing_entry_by_name_does_not_move_cursor() {
+        let mut app = App {
+            current_dir_contents: vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")],
+            current_dir_path: PathBuf::from("./"),
+            cursor_positions: vec![1],
+            ..Default::default()
+        };
+
+        assert!(!app.select_entry_by_name(OsStr::new("d")));
+        assert_eq!(app.current_cursor_position(), 1);
+    }
+
+    #[test]
+    fn default_render_app_border() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path =
+            PathBuf::from(format!("{}/nested_dir", tmp_dir.path().to_str().unwrap()));
+        let _nested_dir = create_dir(&nested_dir_path);
+        let file_path_0 = tmp_dir.path().join("file.txt");
+        let _tmp_file_0 = File::create(&file_path_0).unwrap();
+        let file_path_1 = tmp_dir.path().join("zzz.txt");
+        let _tmp_file = File::create(&file_path_1).unwrap();
+
+        let app = new_app(tmp_dir.path().to_path_buf());
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 81, 5));
+
+        app.render(buf.area, &mut buf);
+
+        let mut expected = Buffer::with_lines(vec![
+            "┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━ TUI File Explorer ━━━━━━━━━━━━━━ sorted by name ┓",
+            &format!("┃{:width$}┃", tmp_dir.path().to_str().unwrap(), width = 79),
+            "┃                                                                               ┃",
+            "┃                                                                               ┃",
+            "┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┛",
+        ]);
+        let title_style = Style::new().bold();
+        let current_dir_style = Style::new();
+
+        let temp_dir_absolute_path_length = tmp_dir.path().to_str().unwrap().len() as u16;
+        expected.set_style(Rect::new(31, 0, 19, 1), title_style);
+        expected.set_style(
+            Rect::new(1, 1, 1 + temp_dir_absolute_path_length, 1),
+            current_dir_style,
+        );
+
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn detail_pane_follows_the_cursor() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let _nested_dir = create_dir(tmp_dir.path().join("a_dir"));
+        std::fs::write(tmp_dir.path().join("b.txt"), "hello").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('i').into(), Size::default());
+        let details = app.selected_details.clone().unwrap();
+        assert_eq!(details.path, tmp_dir.path().join("a_dir"));
+        assert_eq!(details.kind, "directory");
+        assert_eq!(details.size, "-");
+
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.on_tick(Instant::now(), Size::default());
+        let details = app.selected_details.clone().unwrap();
+        assert_eq!(details.path, tmp_dir.path().join("b.txt"));
+        assert_eq!(details.kind, "file");
+        assert_eq!(details.size, "5B");
+    }
+
+    #[test]
+    fn large_directory_renders_into_a_buffer() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        for index in 0..2000 {
+            File::create(tmp_dir.path().join(format!("file_{index:04}.txt"))).unwrap();
+        }
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        // Otherwise every column is squeezed in, too narrow to read
+        app.config.max_columns = Some(4);
+
+        let area = Rect::new(5, 3, 120, 40);
+        let buffer = app.render_to_buffer(area);
+
+        assert_eq!(buffer.area, area);
+        let first_row: String = (area.x..area.right())
+            .map(|x| buffer[(x, area.y + 2)].symbol())
+            .collect();
+        assert!(first_row.contains("> file_0000.txt"));
+    }
+
+    #[test]
+    fn tab_cycles_what_the_right_pane_shows() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+        let _nested_file = File::create(nested_dir_path.join("inner.txt")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let size = Size::new(80, 8);
+        let rendered_text = |app: &App| rendered_text(app, size.width, size.height);
+
+        app.handle_key_event(KeyCode::Char('a').into(), size);
+        app.on_tick(Instant::now(), size);
+        assert_eq!(app.right_pane_mode, RightPaneMode::Contents);
+        assert!(rendered_text(&app).contains("inner.txt"));
+
+        app.handle_key_event(KeyCode::Tab.into(), size);
+        assert_eq!(app.right_pane_mode, RightPaneMode::Details);
+        let text = rendered_text(&app);
+        assert!(text.contains("Type: directory"));
+        assert!(!text.contains("inner.txt"));
+
+        app.handle_key_event(KeyCode::Tab.into(), size);
+        assert_eq!(app.right_pane_mode, RightPaneMode::Peek);
+        let text = rendered_text(&app);
+        assert!(text.contains("inner.txt"));
+        assert!(!text.contains("Type: directory"));
+
+        // The peek listing is read once for the selection, not again on every draw
+        File::create(nested_dir_path.join("later.txt")).unwrap();
+        app.on_tick(Instant::now(), size);
+        assert!(!rendered_text(&app).contains("later.txt"));
+        app.refresh_keeping_selection();
+        app.on_tick(Instant::now(), size);
+        assert!(rendered_text(&app).contains("later.txt"));
+
+        app.handle_key_event(KeyCode::Tab.into(), size);
+        assert_eq!(app.right_pane_mode, RightPaneMode::Contents);
+    }
+
+    #[test]
+    fn view_settings_are_restored_per_directory() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('z').into(), Size::default());
+        assert!(app.summarize_extensions);
+
+        // A directory without settings of its own uses the defaults
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(app.current_dir_path, nested_dir_path);
+        assert!(!app.summarize_extensions);
+
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        assert!(app.summarize_extensions);
+        // Only directories whose settings were changed are remembered
+        assert_eq!(app.dir_view_settings.get(&nested_dir_path), None);
+    }
+
+    #[test]
+    fn sort_and_visibility_are_restored_per_directory() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        create_dir(&nested_dir_path).unwrap();
+        std::fs::write(nested_dir_path.join("a.txt"), "a").unwrap();
+        std::fs::write(nested_dir_path.join("b.txt"), "bigger").unwrap();
+        File::create(nested_dir_path.join(".hidden")).unwrap();
+        let listed_names = |app: &App| {
+            app.current_dir_contents
+                .iter()
+                .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('.').into(), Size::default());
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(listed_names(&app), ["a.txt", "b.txt"]);
+        app.handle_key_event(KeyCode::Char('s').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('.').into(), Size::default());
+        assert_eq!(listed_names(&app), ["b.txt", "a.txt", ".hidden"]);
+
+        // The parent keeps its own order and hides dotfiles
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        assert_eq!(app.sort_mode, SortMode::Path);
+        assert!(app.hide_dotfiles);
+
+        // Coming back sorts by size and shows dotfiles straight away
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(listed_names(&app), ["b.txt", "a.txt", ".hidden"]);
+        assert!(rendered_text(&app, 80, 5).contains("sorted by size"));
+
+        // Reloading keeps what's in use
+        app.refresh_keeping_selection();
+        assert_eq!(app.sort_mode, SortMode::Size);
+        assert!(!app.hide_dotfiles);
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for character in text.chars() {
+            app.handle_key_event(KeyCode::Char(character).into(), Size::default());
+        }
+    }
+
+    #[test]
+    fn batch_rename_renames_marked_entries_after_confirmation() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let _first_file = File::create(tmp_dir.path().join("IMG_1.jpg")).unwrap();
+        let _second_file = File::create(tmp_dir.path().join("IMG_2.jpg")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('V').into(), Size::default());
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+
+        app.handle_key_event(KeyCode::Char('B').into(), Size::default());
+        type_text(&mut app, "photo_{n:02}.{ext}");
+        let (renames, collisions) = app.batch_rename_preview().unwrap();
+        assert_eq!(renames[1].to, tmp_dir.path().join("photo_02.jpg"));
+        assert!(collisions.is_empty());
+
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(
+            app.pending_confirmation.as_ref().map(Confirmation::prompt),
+            Some("Rename 2 marked items? (y/n)".to_string())
+        );
+
+        app.handle_key_event(KeyCode::Char('y').into(), Size::default());
+        assert_eq!(
+            app.current_dir_contents,
+            vec![
+                tmp_dir.path().join("photo_01.jpg"),
+                tmp_dir.path().join("photo_02.jpg")
+            ]
+        );
+        assert!(app.marked.is_empty());
+    }
+
+    #[test]
+    fn batch_rename_is_blocked_by_collisions() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let _first_file = File::create(tmp_dir.path().join("a.txt")).unwrap();
+        let _second_file = File::create(tmp_dir.path().join("b.txt")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('V').into(), Size::default());
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+
+        app.handle_key_event(KeyCode::Char('B').into(), Size::default());
+        type_text(&mut app, "same.{ext}");
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+
+        assert_eq!(app.pending_confirmation, None);
+        assert_eq!(
+            app.status_message,
+            Some("Nothing renamed, these new names collide: same.txt".to_string())
+        );
+        assert_eq!(app.batch_rename_pattern, Some("same.{ext}".to_string()));
+        assert!(tmp_dir.path().join("a.txt").exists());
+    }
+
+    #[test]
+    fn navigation_is_recorded_most_recent_first() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+        let other_dir_path = tmp_dir.path().join("other_dir");
+        let _other_dir = create_dir(&other_dir_path);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        app.jump_to_dir(other_dir_path.clone());
+
+        assert_eq!(
+            app.recent_dirs.paths(),
+            [
+                other_dir_path,
+                tmp_dir.path().to_path_buf(),
+                nested_dir_path
+            ]
+        );
+    }
+
+    #[test]
+    fn picking_a_recent_dir_jumps_there() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+        let _nested_file = File::create(nested_dir_path.join("file.txt")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+
+        app.handle_key_event(KeyCode::Char('H').into(), Size::default());
+        assert_eq!(app.recent_dirs_cursor_position, Some(0));
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+
+        assert_eq!(app.recent_dirs_cursor_position, None);
+        assert_eq!(app.current_dir_path, nested_dir_path);
+        assert_eq!(
+            app.current_dir_contents,
+            vec![nested_dir_path.join("file.txt")]
+        );
+        assert_eq!(app.recent_dirs.paths()[0], nested_dir_path);
+    }
+
+    #[test]
+    fn back_then_forward_returns_to_the_same_dir() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+
+        app.handle_key_event(KeyCode::Char('[').into(), Size::default());
+        assert_eq!(app.current_dir_path, tmp_dir.path());
+
+        app.handle_key_event(
+            KeyEvent::new(KeyCode::Right, KeyModifiers::ALT),
+            Size::default(),
+        );
+        assert_eq!(app.current_dir_path, nested_dir_path);
+    }
+
+    #[test]
+    fn navigating_after_going_back_drops_the_forward_history() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let first_dir_path = tmp_dir.path().join("first_dir");
+        let _first_dir = create_dir(&first_dir_path);
+        let second_dir_path = tmp_dir.path().join("second_dir");
+        let _second_dir = create_dir(&second_dir_path);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.jump_to_dir(first_dir_path);
+        app.handle_key_event(KeyCode::Char('[').into(), Size::default());
+        app.jump_to_dir(second_dir_path.clone());
+
+        app.handle_key_event(KeyCode::Char(']').into(), Size::default());
+        assert_eq!(app.current_dir_path, second_dir_path);
+
+        app.handle_key_event(KeyCode::Char('[').into(), Size::default());
+        assert_eq!(app.current_dir_path, tmp_dir.path());
+    }
 
     #[test]
-    fn can_exit() {
+    fn active_view_modifiers_are_summarized() {
         let mut app = App::default();
-        assert!(!app.exit);
+        assert_eq!(app.view_modifiers(), "");
 
-        app.handle_key_event(KeyCode::Char('q').into(), Size::default());
-        assert!(app.exit);
+        app.hide_gitignored = true;
+        app.summarize_extensions = true;
+        app.auto_preview = true;
+        assert_eq!(app.view_modifiers(), "[f:git][summary][auto]");
+
+        app.sort_mode = SortMode::Size;
+        assert_eq!(app.view_modifiers(), "[f:git][sort:size↓][summary][auto]");
     }
 
     #[test]
-    fn can_move_cursor() {
-        let mut app = App {
-            current_dir_contents: vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")],
-            current_dir_path: PathBuf::from("./"),
-            cursor_positions: vec![0],
-            ..Default::default()
+    fn read_only_mode_refuses_to_delete() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+
+        let mut app = new_app(tmp_dir.path().to_path_buf()).read_only();
+        app.config.confirm_destructive = false;
+
+        app.handle_key_event(KeyCode::Char('d').into(), Size::default());
+        assert_eq!(app.pending_confirmation, None);
+        assert_eq!(app.status_message, Some("read-only mode".to_string()));
+        assert!(nested_dir_path.exists());
+
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(app.current_dir_path, nested_dir_path);
+    }
+
+    #[test]
+    fn read_only_mode_refuses_every_mutating_key() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        create_dir(&nested_dir_path).unwrap();
+        std::fs::write(tmp_dir.path().join("notes.txt"), "notes").unwrap();
+        let listing = || {
+            let mut listing: Vec<_> = std::fs::read_dir(tmp_dir.path())
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .collect();
+            listing.sort();
+            listing
         };
+        let before = listing();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf()).read_only();
+        app.config.confirm_destructive = false;
+        // Something yanked and something marked, so every key has something to act on
+        app.handle_key_event(KeyCode::Char('y').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('V').into(), Size::default());
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+
+        for key in ['d', 'x', 'p', 'X', 'P', 'M', 'E', 'n', 'S', 'R', 'B'] {
+            app.status_message = None;
+            app.handle_key_event(KeyCode::Char(key).into(), Size::default());
+            assert_eq!(
+                app.status_message.as_deref(),
+                Some(READ_ONLY_MESSAGE),
+                "{key} wasn't refused"
+            );
+            assert_eq!(app.pending_confirmation, None);
+            assert_eq!(app.name_prompt, None);
+            assert_eq!(app.batch_rename_pattern, None);
+            // Confirming anything left over changes nothing either
+            app.handle_key_event(KeyCode::Char('y').into(), Size::default());
+        }
+        assert_eq!(listing(), before);
+
+        // Nor is the selected file opened in an editor, from `e` or from enter
+        app.handle_key_event(KeyCode::Char('V').into(), Size::default());
+        app.select_entry_by_name(OsStr::new("notes.txt"));
+        app.config.file_enter_action = Some(FileOpenAction::Editor);
+        for key in [KeyCode::Char('e'), KeyCode::Enter] {
+            app.status_message = None;
+            app.handle_key_event(key.into(), Size::default());
+            assert_eq!(app.status_message.as_deref(), Some(READ_ONLY_MESSAGE));
+            assert!(app.pending_external_command.is_none());
+        }
+
+        // Searching doesn't change anything, so `n` still steps through the matches
+        app.handle_key_event(KeyCode::Char('/').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('t').into(), Size::default());
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        let first_match = app.selected_path().map(Path::to_path_buf);
+        app.handle_key_event(KeyCode::Char('n').into(), Size::default());
+        assert_ne!(app.selected_path().map(Path::to_path_buf), first_match);
+        assert!(app.status_message.as_deref().unwrap().starts_with("Match"));
+        assert_eq!(app.name_prompt, None);
+    }
+
+    #[test]
+    fn layout_report_compares_columns_that_fit_with_those_needed() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        for index in 0..6 {
+            let _file = File::create(tmp_dir.path().join(format!("file_{index}.txt"))).unwrap();
+        }
 
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        // Two rows per column, each 18 cells wide for a 10 character name
         let frame_size = Size {
-            width: 1,
-            height: 6,
+            width: 40,
+            height: 5,
+        };
+        app.handle_key_event(KeyCode::Char('D').into(), frame_size);
+        assert_eq!(
+            app.status_message,
+            Some("Layout: 2 of 3 columns fit in 39 cells, 2 rows per column".to_string())
+        );
+
+        // The preview takes half the width
+        app.handle_key_event(KeyCode::Char('a').into(), frame_size);
+        assert_eq!(
+            app.layout_report(frame_size),
+            "Layout: 1 of 3 columns fit in 20 cells, 2 rows per column"
+        );
+    }
+
+    #[test]
+    fn quick_look_overlays_the_selected_file() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        std::fs::write(
+            tmp_dir.path().join("notes.txt"),
+            "first line\nsecond line\n",
+        )
+        .unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let rendered_text = |app: &App| rendered_text(app, 60, 12);
+        assert!(!rendered_text(&app).contains("first line"));
+
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+        assert!(app.marked.is_empty());
+        let text = rendered_text(&app);
+        assert!(text.contains("first line"));
+        assert!(text.contains("second line"));
+
+        // Arrows scroll the overlay, stopping at the last line, instead of moving the cursor
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        assert_eq!(app.quick_look_scroll_offset, Some(1));
+        let text = rendered_text(&app);
+        assert!(!text.contains("first line"));
+        assert!(text.contains("second line"));
+
+        app.handle_key_event(KeyCode::Esc.into(), Size::default());
+        assert_eq!(app.quick_look_scroll_offset, None);
+        assert!(!rendered_text(&app).contains("second line"));
+    }
+
+    #[test]
+    fn right_pane_loads_long_files_a_chunk_at_a_time() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let contents: String = (1..=3000)
+            .map(|number| format!("line {number}\n"))
+            .collect();
+        std::fs::write(tmp_dir.path().join("app.log"), contents).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let size = Size::new(80, 20);
+        app.handle_key_event(KeyCode::Char('c').into(), size);
+        app.on_tick(Instant::now(), size);
+        let lines_loaded = |app: &App| app.selected_preview_text().unwrap().lines().len();
+        assert_eq!(lines_loaded(&app), 1000);
+        assert!(rendered_text(&app, 80, 20).contains("1000 lines loaded, m for more"));
+
+        // Scrolling stops at the last line loaded
+        app.file_scroll_offset = 998;
+        app.handle_key_event(KeyCode::Char('j').into(), size);
+        app.handle_key_event(KeyCode::Char('j').into(), size);
+        assert_eq!(app.file_scroll_offset, 999);
+        assert!(rendered_text(&app, 80, 20).contains("line 1000"));
+
+        app.handle_key_event(KeyCode::Char('m').into(), size);
+        assert_eq!(lines_loaded(&app), 2000);
+        app.handle_key_event(KeyCode::Char('j').into(), size);
+        assert!(rendered_text(&app, 80, 20).contains("line 1001"));
+        assert!(rendered_text(&app, 80, 20).contains("2000 lines loaded, m for more"));
+
+        app.handle_key_event(KeyCode::Char('m').into(), size);
+        app.on_tick(Instant::now(), size);
+        assert_eq!(lines_loaded(&app), 3000);
+        assert!(!rendered_text(&app, 80, 20).contains("m for more"));
+    }
+
+    #[test]
+    fn copied_preview_is_the_scrolled_window_shown() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let contents: String = (1..=20)
+            .map(|number| format!("line {number:02} of the notes\n"))
+            .collect();
+        std::fs::write(tmp_dir.path().join("notes.txt"), contents).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let size = Size::new(40, 8);
+        app.handle_key_event(KeyCode::Char('c').into(), size);
+        app.on_tick(Instant::now(), size);
+        app.handle_key_event(KeyCode::Char('j').into(), size);
+        app.handle_key_event(KeyCode::Char('j').into(), size);
+        let shift_right = KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT);
+        for _ in 0..3 {
+            app.handle_key_event(shift_right, size);
+        }
+        app.handle_key_event(KeyCode::Char('Y').into(), size);
+
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Copied preview to clipboard")
+        );
+        assert_eq!(
+            app.visible_preview_text(size).as_deref(),
+            Some("e 03 of the notes\ne 04 of the notes\ne 05 of the notes\ne 06 of the notes")
+        );
+        assert!(rendered_text(&app, 40, 8).contains("e 06 of the notes"));
+    }
+
+    #[test]
+    fn preview_is_drawn_from_what_the_tick_read() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        create_dir(&nested_dir_path).unwrap();
+        std::fs::write(nested_dir_path.join("inner.txt"), "inner").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let size = Size::new(80, 8);
+        app.handle_key_event(KeyCode::Char('a').into(), size);
+        assert!(!rendered_text(&app, 80, 8).contains("inner.txt"));
+
+        // Drawing doesn't list the directory again, which the next tick would
+        app.on_tick(Instant::now(), size);
+        std::fs::remove_file(nested_dir_path.join("inner.txt")).unwrap();
+        assert!(rendered_text(&app, 80, 8).contains("inner.txt"));
+    }
+
+    #[test]
+    fn media_preview_shows_the_probe_once_it_has_finished() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let song_path = tmp_dir.path().join("song.mp3");
+        std::fs::write(&song_path, [0, 1, 2]).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let size = Size::new(80, 8);
+        app.handle_key_event(KeyCode::Char('c').into(), size);
+        app.refresh_preview_fallbacks(size);
+        // Drawn without waiting on `ffprobe`
+        assert!(rendered_text(&app, 80, 8).contains("Probing…"));
+
+        app.media_probe.request(&song_path);
+        app.media_probe.deliver(
+            file_version(&song_path),
+            Some(vec!["Duration: 0:03".to_string()]),
+        );
+        app.refresh_preview_fallbacks(size);
+        let text = rendered_text(&app, 80, 8);
+        assert!(text.contains("Duration: 0:03"));
+        assert!(!text.contains("Probing…"));
+    }
+
+    #[test]
+    fn quick_look_follows_the_selection_a_refresh_moves() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        std::fs::write(tmp_dir.path().join("a.txt"), "first file").unwrap();
+        std::fs::write(tmp_dir.path().join("b.txt"), "second file").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.select_entry_by_name(OsStr::new("b.txt"));
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+        assert!(rendered_text(&app, 80, 20).contains("second file"));
+
+        // The refresh moves the cursor onto a.txt, whose text replaces b.txt's
+        std::fs::remove_file(tmp_dir.path().join("b.txt")).unwrap();
+        app.refresh_keeping_selection();
+        app.on_tick(Instant::now(), Size::new(80, 20));
+        assert_eq!(app.quick_look_text, None);
+        let text = rendered_text(&app, 80, 20);
+        assert!(text.contains("first file"));
+        assert!(!text.contains("second file"));
+
+        // With nothing left to look at, the next key closes the overlay
+        std::fs::remove_file(tmp_dir.path().join("a.txt")).unwrap();
+        app.refresh_keeping_selection();
+        for key in [KeyCode::Right, KeyCode::Down] {
+            app.quick_look_scroll_offset = Some(0);
+            app.handle_key_event(key.into(), Size::new(80, 20));
+            assert_eq!(app.quick_look_scroll_offset, None);
+        }
+    }
+
+    #[test]
+    fn quick_look_loads_long_files_a_chunk_at_a_time() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let log_path = tmp_dir.path().join("app.log");
+        let contents: String = (1..=3000)
+            .map(|number| format!("line {number}\n"))
+            .collect();
+        std::fs::write(&log_path, contents).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+        let lines_loaded = |app: &App| app.quick_look_text.as_ref().unwrap().lines().len();
+        assert_eq!(lines_loaded(&app), 1000);
+        assert!(rendered_text(&app, 80, 20).contains("1000 lines loaded, m for more"));
+
+        // Scrolling stops at the last line loaded
+        app.quick_look_scroll_offset = Some(998);
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        assert_eq!(app.quick_look_scroll_offset, Some(999));
+        assert!(rendered_text(&app, 80, 20).contains("line 1000"));
+
+        app.handle_key_event(KeyCode::Char('m').into(), Size::default());
+        assert_eq!(lines_loaded(&app), 2000);
+        let quick_look_text = app.quick_look_text.as_ref().unwrap();
+        assert_eq!(quick_look_text.lines()[1000], "line 1001");
+        assert_eq!(quick_look_text.lines()[1999], "line 2000");
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        assert!(rendered_text(&app, 80, 20).contains("line 1001"));
+
+        // Once everything is read, there's no more to offer
+        app.handle_key_event(KeyCode::Char('m').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('m').into(), Size::default());
+        assert_eq!(lines_loaded(&app), 3000);
+        assert!(!rendered_text(&app, 80, 20).contains("m for more"));
+    }
+
+    #[test]
+    fn space_marks_instead_while_multi_selecting() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("notes.txt");
+        let _file = File::create(&file_path).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('V').into(), Size::default());
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+
+        assert_eq!(app.quick_look_scroll_offset, None);
+        assert_eq!(app.marked, HashSet::from([file_path]));
+    }
+
+    #[test]
+    fn preview_matches_a_directly_rendered_file_view() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("notes.txt");
+        std::fs::write(
+            &file_path,
+            format!("short\n{}\n写真写真写真写真写真\n", "x".repeat(200)),
+        )
+        .unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        let area = Rect::new(0, 0, 41, 10);
+        app.handle_key_event(KeyCode::Char('c').into(), area.as_size());
+        app.on_tick(Instant::now(), area.as_size());
+        let app_buffer = app.render_to_buffer(area);
+        assert!(rendered_text(&app, area.width, area.height).contains("short"));
+
+        // The right half of the area inside the border, as `draw` lays it out
+        let column_height = area.height - 3;
+        let internal_area = Rect::new(1, 2, area.width - 1, column_height);
+        let [_, file_view_area] =
+            Layout::horizontal([Constraint::Percentage(50); 2]).areas(internal_area);
+        let mut file_view_buffer = Buffer::empty(file_view_area);
+        FileView::new(&file_path, column_height as usize, app.preview_limits())
+            .with_theme(&app.theme)
+            .render(file_view_area, &mut file_view_buffer);
+
+        for position in file_view_area.positions() {
+            assert_eq!(
+                app_buffer.cell(position).unwrap().symbol(),
+                file_view_buffer.cell(position).unwrap().symbol(),
+                "at {position}"
+            );
+        }
+    }
+
+    #[test]
+    fn group_headers_are_listed_and_skipped_by_the_cursor() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        create_dir(tmp_dir.path().join("photos")).unwrap();
+        for name in ["a.png", "b.png", "notes.txt"] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.group_headers = true;
+        app.sort_mode = SortMode::Type;
+        app.comparator = SortMode::Type.comparator(app.config.sort_tiebreaker);
+        app.load_current_dir_contents().unwrap();
+
+        let buffer = app.render_to_buffer(Rect::new(0, 0, 40, 12));
+        let rows: Vec<String> = buffer
+            .content()
+            .chunks(40)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect();
+        let rows: Vec<&str> = rows
+            .iter()
+            .map(|row| row[3..].trim_end_matches(['┃', ' ']))
+            .collect();
+        assert_eq!(
+            rows[2..9],
+            [
+                "— Directories —",
+                "> photos",
+                "— Images —",
+                "  a.png",
+                "  b.png",
+                "— Other files —",
+                "  notes.txt",
+            ]
+        );
+
+        app.handle_key_event(KeyCode::Down.into(), Size::new(40, 12));
+        assert_eq!(app.selected_entry(), Some(&tmp_dir.path().join("a.png")));
+
+        // Three rows to a column: the headers of the images and other files end the first two
+        let frame_size = Size::new(80, 6);
+        app.handle_key_event(KeyCode::Up.into(), frame_size);
+        app.handle_key_event(KeyCode::Right.into(), frame_size);
+        assert_eq!(app.selected_entry(), Some(&tmp_dir.path().join("b.png")));
+        app.handle_key_event(KeyCode::Right.into(), frame_size);
+        assert_eq!(
+            app.selected_entry(),
+            Some(&tmp_dir.path().join("notes.txt"))
+        );
+        app.handle_key_event(KeyCode::Up.into(), frame_size);
+        app.handle_key_event(KeyCode::Up.into(), frame_size);
+        app.handle_key_event(KeyCode::Left.into(), frame_size);
+        assert_eq!(app.selected_entry(), Some(&tmp_dir.path().join("photos")));
+    }
+
+    #[test]
+    fn expanded_dirs_list_their_contents_indented_beneath_them() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        create_dir(tmp_dir.path().join("docs")).unwrap();
+        for name in ["docs/a.txt", "docs/b.txt", "notes.txt"] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
+        let listed_rows = |app: &App| {
+            let buffer = app.render_to_buffer(Rect::new(0, 0, 40, 8));
+            buffer
+                .content()
+                .chunks(40)
+                .skip(2)
+                .take(5)
+                .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+                .map(|row| row[3..].trim_end_matches(['┃', ' ']).to_string())
+                .filter(|row| !row.is_empty())
+                .collect::<Vec<_>>()
+        };
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('o').into(), Size::default());
+        assert_eq!(
+            listed_rows(&app),
+            ["> docs", "    a.txt", "    b.txt", "  notes.txt"]
+        );
+
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        assert_eq!(
+            app.selected_entry(),
+            Some(&tmp_dir.path().join("docs").join("a.txt"))
+        );
+
+        app.handle_key_event(KeyCode::Up.into(), Size::default());
+        app.handle_key_event(KeyCode::Char('o').into(), Size::default());
+        assert_eq!(listed_rows(&app), ["> docs", "  notes.txt"]);
+
+        // What the gitignore hides stays hidden beneath an expanded directory too
+        create_dir(tmp_dir.path().join(".git")).unwrap();
+        std::fs::write(tmp_dir.path().join(".gitignore"), "*.log\n").unwrap();
+        File::create(tmp_dir.path().join("docs").join("debug.log")).unwrap();
+        app.handle_key_event(KeyCode::Char('.').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('I').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('o').into(), Size::default());
+        assert_eq!(
+            listed_rows(&app),
+            ["> docs", "    a.txt", "    b.txt", "  notes.txt"]
+        );
+    }
+
+    #[test]
+    fn entering_a_child_of_an_expanded_dir_and_backing_out_keeps_the_depth() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let sub_path = tmp_dir.path().join("docs").join("sub");
+        std::fs::create_dir_all(&sub_path).unwrap();
+        let depth_of = |path: &Path| path.ancestors().count() - 1;
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('o').into(), Size::default());
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        assert_eq!(app.selected_entry(), Some(&sub_path));
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(app.current_dir_path, sub_path);
+        assert_eq!(app.current_cursor_depth, depth_of(&sub_path));
+
+        // Backing out all the way stops at the root rather than running out of depth
+        for _ in 0..depth_of(&sub_path) + 2 {
+            app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+            assert_eq!(app.current_cursor_depth, depth_of(&app.current_dir_path));
+        }
+        assert_eq!(app.current_dir_path, Path::new("/"));
+    }
+
+    #[test]
+    fn navigation_lock_ignores_directory_changes_in_quick_succession() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let inner_dir = tmp_dir.path().join("outer").join("inner");
+        std::fs::create_dir_all(&inner_dir).unwrap();
+
+        let mut app = new_app(inner_dir);
+        app.config.navigation_lock_ms = 300;
+        let started = Instant::now();
+
+        app.event_time = Some(started);
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        app.event_time = Some(started + Duration::from_millis(100));
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        assert_eq!(app.current_dir_path, tmp_dir.path().join("outer"));
+
+        app.event_time = Some(started + Duration::from_millis(500));
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        assert_eq!(app.current_dir_path, tmp_dir.path());
+    }
+
+    #[test]
+    fn selection_listeners_are_told_the_old_and_new_paths() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        for name in ["a.txt", "b.txt"] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
+        let selection_changes = Rc::new(RefCell::new(Vec::new()));
+
+        let mut app = new_app(tmp_dir.path().to_path_buf()).with_selection_listener({
+            let selection_changes = Rc::clone(&selection_changes);
+            move |selection_change| {
+                selection_changes
+                    .borrow_mut()
+                    .push(selection_change.clone())
+            }
+        });
+        app.on_tick(Instant::now(), Size::default());
+        assert!(selection_changes.borrow().is_empty());
+
+        // Each change is heard about as it happens, without waiting for a tick
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.select_entry_by_name(OsStr::new("a.txt"));
+        app.on_tick(Instant::now(), Size::default());
+        assert_eq!(
+            *selection_changes.borrow(),
+            [
+                SelectionChange {
+                    old_path: Some(tmp_dir.path().join("a.txt")),
+                    new_path: Some(tmp_dir.path().join("b.txt")),
+                },
+                SelectionChange {
+                    old_path: Some(tmp_dir.path().join("b.txt")),
+                    new_path: Some(tmp_dir.path().join("a.txt")),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn shift_moves_mark_the_range_from_the_anchor() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        for name in ["a", "b", "c", "d"] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
+        let shift = |code| KeyEvent::new(code, KeyModifiers::SHIFT);
+        let marked = |app: &App| {
+            sorted(app.marked.iter())
+                .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        app.handle_key_event(shift(KeyCode::Down), Size::default());
+        app.handle_key_event(shift(KeyCode::Down), Size::default());
+        assert_eq!(marked(&app), ["b", "c", "d"]);
+        app.handle_key_event(shift(KeyCode::Up), Size::default());
+        assert_eq!(marked(&app), ["b", "c"]);
+        assert_eq!(app.range_anchor, Some(1));
+
+        app.handle_key_event(KeyCode::Up.into(), Size::default());
+        assert_eq!(app.range_anchor, None);
+        app.handle_key_event(shift(KeyCode::Up), Size::default());
+        assert_eq!(app.range_anchor, Some(1));
+        assert_eq!(marked(&app), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn narrowing_the_listing_drops_the_range_anchor() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        for name in ["alpha", "apex", "b", "c", "d", "e"] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
+        let shift_down = KeyEvent::new(KeyCode::Down, KeyModifiers::SHIFT);
+        let marked = |app: &App| {
+            sorted(app.marked.iter())
+                .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.select_entry_by_name(OsStr::new("d"));
+        app.handle_key_event(shift_down, Size::default());
+        assert_eq!(marked(&app), ["d", "e"]);
+
+        app.handle_key_event(KeyCode::Char('/').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('a').into(), Size::default());
+        app.handle_key_event(KeyCode::Char('p').into(), Size::default());
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(app.current_dir_contents.len(), 2);
+        assert_eq!(app.range_anchor, None);
+
+        // The range starts again from the cursor, rather than from a row that's gone
+        assert_eq!(app.selected_entry(), Some(&tmp_dir.path().join("apex")));
+        app.handle_key_event(
+            KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT),
+            Size::default(),
+        );
+        assert_eq!(marked(&app), ["alpha", "apex", "d", "e"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_dir_is_listed_as_empty_with_the_reason() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let locked_dir = tmp_dir.path().join("locked");
+        create_dir(&locked_dir).unwrap();
+        File::create(locked_dir.join("secret.txt")).unwrap();
+        std::fs::set_permissions(&locked_dir, std::fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Root can read it regardless, so this only checks the reason when it really is unreadable
+        let app = new_app(locked_dir.clone());
+        let text = rendered_text(&app, 80, 5);
+        if std::fs::read_dir(&locked_dir).is_err() {
+            assert!(app.current_dir_contents.is_empty());
+            assert!(text.contains("Permission denied"));
+        }
+        std::fs::set_permissions(&locked_dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn missing_dir_is_listed_as_empty_with_the_reason() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+
+        let mut app = new_app(tmp_dir.path().join("gone"));
+        assert!(app.current_dir_contents.is_empty());
+        assert!(rendered_text(&app, 80, 5).contains("Directory not found"));
+
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        assert_eq!(app.dir_error, None);
+    }
+
+    #[test]
+    fn s_cycles_through_name_size_and_modified_orders() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let now = SystemTime::now();
+        for (name, size, age_in_days) in [("a", 3, 3), ("b", 10, 2), ("c", 1, 1), ("d", 3, 4)] {
+            let file = File::create(tmp_dir.path().join(name)).unwrap();
+            file.set_len(size).unwrap();
+            file.set_modified(now - Duration::from_secs(86_400 * age_in_days))
+                .unwrap();
+        }
+        let listed_names = |app: &App| {
+            app.current_dir_contents
+                .iter()
+                .map(|path| path.file_name().unwrap().to_str().unwrap())
+                .collect::<String>()
+        };
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        assert_eq!(listed_names(&app), "abcd");
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+
+        // Sizes tie between a and d, which falls back to their names
+        app.handle_key_event(KeyCode::Char('s').into(), Size::default());
+        assert_eq!(listed_names(&app), "badc");
+        assert!(rendered_text(&app, 80, 5).contains("sorted by size"));
+        assert_eq!(app.selected_entry(), Some(&tmp_dir.path().join("b")));
+
+        app.handle_key_event(KeyCode::Char('s').into(), Size::default());
+        assert_eq!(listed_names(&app), "cbad");
+        assert!(rendered_text(&app, 80, 5).contains("sorted by modified"));
+
+        app.handle_key_event(KeyCode::Char('s').into(), Size::default());
+        assert_eq!(listed_names(&app), "abcd");
+        assert!(rendered_text(&app, 80, 5).contains("sorted by name"));
+    }
+
+    #[test]
+    fn directories_are_listed_before_files_until_g_is_pressed() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        File::create(tmp_dir.path().join("a.txt")).unwrap();
+        create_dir(tmp_dir.path().join("b_dir")).unwrap();
+        File::create(tmp_dir.path().join("c.txt")).unwrap();
+        create_dir(tmp_dir.path().join("d_dir")).unwrap();
+        let listed_names = |app: &App| {
+            app.current_dir_contents
+                .iter()
+                .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
         };
 
-        assert_eq!(app.current_cursor_position(), 0);
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        assert_eq!(listed_names(&app), ["b_dir", "d_dir", "a.txt", "c.txt"]);
+        let last_dir = app
+            .current_dir_contents
+            .iter()
+            .rposition(|path| path.is_dir())
+            .unwrap();
+        let first_file = app
+            .current_dir_contents
+            .iter()
+            .position(|path| !path.is_dir())
+            .unwrap();
+        assert!(last_dir < first_file);
+
+        // The cursor stays on the same entry as it moves
+        app.select_entry_by_name(OsStr::new("c.txt"));
+        app.handle_key_event(KeyCode::Char('g').into(), Size::default());
+        assert_eq!(listed_names(&app), ["a.txt", "b_dir", "c.txt", "d_dir"]);
+        assert_eq!(app.selected_entry(), Some(&tmp_dir.path().join("c.txt")));
+
+        app.handle_key_event(KeyCode::Char('g').into(), Size::default());
+        assert_eq!(listed_names(&app), ["b_dir", "d_dir", "a.txt", "c.txt"]);
+        assert_eq!(app.selected_entry(), Some(&tmp_dir.path().join("c.txt")));
+    }
+
+    #[test]
+    fn directories_first_keeps_each_group_together() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let now = SystemTime::now();
+        for (name, age_in_days) in [("a_dir", 3), ("b_dir", 0)] {
+            create_dir(tmp_dir.path().join(name)).unwrap();
+            File::open(tmp_dir.path().join(name))
+                .unwrap()
+                .set_modified(now - Duration::from_secs(86_400 * age_in_days))
+                .unwrap();
+        }
+        for (name, age_in_days) in [("c.txt", 0), ("d.txt", 3)] {
+            File::create(tmp_dir.path().join(name))
+                .unwrap()
+                .set_modified(now - Duration::from_secs(86_400 * age_in_days))
+                .unwrap();
+        }
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.group_headers = true;
+        app.sort_mode = SortMode::Modified;
+        app.comparator = SortMode::Modified.comparator(app.config.sort_tiebreaker);
+        app.load_current_dir_contents().unwrap();
+
+        let listed_names: Vec<_> = app
+            .current_dir_contents
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(listed_names, ["b_dir", "c.txt", "a_dir", "d.txt"]);
+        let labels: Vec<_> = app
+            .group_headers
+            .iter()
+            .map(|header| header.label.as_str())
+            .collect();
+        assert_eq!(labels, ["Today", "Last week"]);
+    }
+
+    #[test]
+    fn listing_text_follows_the_shown_order_and_filters() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        create_dir(tmp_dir.path().join("docs")).unwrap();
+        std::fs::write(tmp_dir.path().join("a.txt"), "a").unwrap();
+        std::fs::write(tmp_dir.path().join("b.txt"), "bbbbbbbb").unwrap();
+        std::fs::write(tmp_dir.path().join(".env"), "").unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        std::fs::write(tmp_dir.path().join("docs").join("notes.md"), "").unwrap();
+        app.handle_key_event(KeyCode::Char('.').into(), Size::default());
+        app.select_entry_by_name(OsStr::new("docs"));
+        app.handle_key_event(KeyCode::Char('o').into(), Size::default());
+
+        assert_eq!(
+            app.directory_view(10).listing_text(),
+            "docs\n  notes.md\na.txt\nb.txt"
+        );
+    }
+
+    #[test]
+    fn unanswered_confirmation_cancels_itself_after_the_timeout() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("keep.txt");
+        File::create(&file_path).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.confirm_destructive = true;
+        app.config.confirmation_timeout_secs = 5;
+        app.handle_key_event(KeyCode::Char('d').into(), Size::default());
+        assert!(app.pending_confirmation.is_some());
 
-        app.handle_key_event(KeyCode::Down.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 1);
+        let started = Instant::now();
+        app.on_tick(started, Size::default());
+        app.on_tick(started + Duration::from_secs(4), Size::default());
+        assert!(app.pending_confirmation.is_some());
 
-        app.handle_key_event(KeyCode::Up.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 0);
+        app.on_tick(started + Duration::from_secs(5), Size::default());
+        assert_eq!(app.pending_confirmation, None);
+        app.handle_key_event(KeyCode::Char('y').into(), Size::default());
+        assert!(file_path.exists());
     }
 
     #[test]
-    fn can_cursor_wraps_around_vertically() {
-        /*
-        Dir looks like this
-        a    c
-        b
-        */
-        let mut app = App {
-            current_dir_contents: vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")],
-            current_dir_path: PathBuf::from("./"),
-            cursor_positions: vec![0],
-            ..Default::default()
-        };
-
-        let frame_size = Size {
-            width: 2,
-            height: 5,
+    fn pinned_entries_are_listed_first_whatever_the_order() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        for (name, size) in [("a.txt", 100), ("README.md", 1), ("z.txt", 50)] {
+            File::create(tmp_dir.path().join(name))
+                .unwrap()
+                .set_len(size)
+                .unwrap();
+        }
+        let listed_names = |app: &App| {
+            app.current_dir_contents
+                .iter()
+                .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
         };
 
-        assert_eq!(app.current_cursor_position(), 0);
-
-        app.handle_key_event(KeyCode::Up.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 2);
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.pinned = vec!["README*".to_string()];
+        let mut app = app.with_comparator(|a, b| b.cmp(a));
+        assert_eq!(listed_names(&app), ["README.md", "z.txt", "a.txt"]);
+        assert!(rendered_text(&app, 40, 6).contains("↑ README.md"));
+        assert!(!rendered_text(&app, 80, 6).contains("sorted by"));
 
-        app.handle_key_event(KeyCode::Down.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 0);
+        app.handle_key_event(KeyCode::Char('s').into(), Size::default());
+        assert_eq!(listed_names(&app), ["README.md", "a.txt", "z.txt"]);
+        assert!(rendered_text(&app, 80, 6).contains("sorted by size"));
     }
 
     #[test]
-    fn can_cursor_wraps_around_horizontally() {
-        /*
-        Dir looks like this
-        a    c
-        b
-        */
-        let mut app = App {
-            current_dir_contents: vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")],
-            current_dir_path: PathBuf::from("./"),
-            cursor_positions: vec![0],
-            ..Default::default()
-        };
+    fn unreadable_file_preview_can_fall_back_to_metadata() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        std::fs::write(tmp_dir.path().join("garbled.txt"), [0xff, 0xfe, b'a']).unwrap();
 
-        let frame_size = Size {
-            width: 2,
-            height: 5,
+        let open_preview = |unreadable_preview| {
+            let mut app = new_app(tmp_dir.path().to_path_buf());
+            app.config.unreadable_preview = unreadable_preview;
+            app.handle_key_event(KeyCode::Char('c').into(), Size::default());
+            app.on_tick(Instant::now(), Size::new(80, 10));
+            app
         };
+        let app = open_preview(UnreadablePreview::default());
+        assert!(rendered_text(&app, 80, 10).contains("Not valid UTF-8"));
 
-        assert_eq!(app.current_cursor_position(), 0);
+        let app = open_preview(UnreadablePreview::Metadata);
+        let text = rendered_text(&app, 80, 10);
+        assert!(text.contains("Type: file"));
+        assert!(text.contains("Size: 3B"));
+        assert!(!text.contains("Not valid UTF-8"));
+    }
 
-        app.handle_key_event(KeyCode::Left.into(), frame_size);
+    #[test]
+    fn unreadable_file_preview_can_close_itself() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        std::fs::write(tmp_dir.path().join("garbled.txt"), [0xff, 0xfe, b'a']).unwrap();
+        std::fs::write(tmp_dir.path().join("notes.txt"), "hello").unwrap();
 
-        assert_eq!(app.current_cursor_position(), 2);
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.unreadable_preview = UnreadablePreview::Close;
+        let size = Size::new(80, 10);
+        app.handle_key_event(KeyCode::Down.into(), size);
+        app.handle_key_event(KeyCode::Char('c').into(), size);
+        app.on_tick(Instant::now(), size);
+        assert!(app.view_file);
 
-        app.handle_key_event(KeyCode::Right.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 0);
+        app.handle_key_event(KeyCode::Up.into(), size);
+        app.on_tick(Instant::now(), size);
+        assert!(!app.view_file);
+        assert!(
+            app.status_message
+                .as_deref()
+                .is_some_and(|message| message.contains("Not valid UTF-8"))
+        );
     }
 
     #[test]
-    fn can_enter_dir() {
+    fn readability_is_decided_once_per_selection() {
         let tmp_dir = TempDir::new("tmp_dir").unwrap();
-        let nested_dir_path =
-            PathBuf::from(format!("{}/nested_dir", tmp_dir.path().to_str().unwrap()));
-        let _nested_dir = create_dir(&nested_dir_path);
-        let file_path = tmp_dir.path().join("file.txt");
-        let _tmp_file = File::create(&file_path).unwrap();
+        std::fs::write(tmp_dir.path().join("garbled.txt"), [0xff, 0xfe, b'a']).unwrap();
+        let notes_path = tmp_dir.path().join("notes.txt");
+        std::fs::write(&notes_path, "hello").unwrap();
 
-        let mut app = App::new(tmp_dir.path().to_path_buf());
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.unreadable_preview = UnreadablePreview::Close;
+        let size = Size::new(80, 10);
+        app.handle_key_event(KeyCode::Down.into(), size);
+        app.handle_key_event(KeyCode::Char('c').into(), size);
+        app.on_tick(Instant::now(), size);
 
-        let frame_size = Size {
-            width: 1,
-            height: 5,
-        };
+        // The file isn't read again on later ticks
+        std::fs::write(&notes_path, [0xff, 0xfe, b'a']).unwrap();
+        app.on_tick(Instant::now(), size);
+        assert!(app.view_file);
 
-        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        // Previews loaded in the background are judged by what they loaded
+        app.config.background_preview_min_bytes = 1;
+        app.handle_key_event(KeyCode::Up.into(), size);
+        let started = Instant::now();
+        while app.view_file && started.elapsed() < Duration::from_secs(5) {
+            app.on_tick(Instant::now(), size);
+        }
+        assert!(!app.view_file);
+    }
+
+    #[test]
+    fn hiding_dotfiles_keeps_the_cursor_on_the_same_file() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        for name in [".a", ".b", ".c", ".d", "notes.txt", "zebra.txt"] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        assert!(app.select_entry_by_name(OsStr::new("notes.txt")));
+        assert_eq!(app.current_cursor_position(), 4);
+
+        app.handle_key_event(KeyCode::Char('.').into(), Size::default());
+        assert_eq!(app.current_dir_contents.len(), 2);
         assert_eq!(
-            app.current_dir_contents,
-            vec![file_path.clone(), nested_dir_path.clone()]
+            app.selected_path(),
+            Some(tmp_dir.path().join("notes.txt").as_path())
         );
-        assert_eq!(app.current_cursor_position(), 0);
+        assert_eq!(app.view_modifiers(), "[.h]");
 
-        // Current dir does not change when attempting to enter file
-        app.handle_key_event(KeyCode::Enter.into(), frame_size);
-        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('.').into(), Size::default());
+        assert_eq!(app.current_dir_contents.len(), 6);
         assert_eq!(
-            app.current_dir_contents,
-            vec![file_path.clone(), nested_dir_path.clone()]
+            app.selected_path(),
+            Some(tmp_dir.path().join("notes.txt").as_path())
         );
-        assert_eq!(app.current_cursor_position(), 0);
-
-        // But does change if entering dir
-        app.handle_key_event(KeyCode::Down.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 1);
-        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
-        app.handle_key_event(KeyCode::Enter.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 0);
-        assert_eq!(app.current_dir_path, nested_dir_path);
     }
 
     #[test]
-    fn can_exit_dir() {
+    fn hidden_dotfiles_stay_hidden_in_and_out_of_directories() {
         let tmp_dir = TempDir::new("tmp_dir").unwrap();
-        let file_path = tmp_dir.path().join("file.txt");
-        let _tmp_file = File::create(&file_path).unwrap();
-        let nested_dir_path =
-            PathBuf::from(format!("{}/nested_dir", tmp_dir.path().to_str().unwrap()));
-        let _nested_dir = create_dir(&nested_dir_path);
-        let nested_file_path_0 = nested_dir_path.join("file_a.txt");
-        let nested_file_path_1 = nested_dir_path.join("file_b.txt");
-        let _nested_file_0 = File::create(&nested_file_path_0).unwrap();
-        let _nested_file_1 = File::create(&nested_file_path_1).unwrap();
-
-        let mut app = App::new(nested_dir_path.clone());
+        create_dir(tmp_dir.path().join(".config")).unwrap();
+        create_dir(tmp_dir.path().join("src")).unwrap();
+        for name in [".gitignore", "Cargo.toml", "src/.hidden", "src/main.rs"] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
 
-        let frame_size = Size {
-            width: 1,
-            height: 5,
-        };
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        assert_eq!(app.current_dir_contents.len(), 4);
+        app.handle_key_event(KeyCode::Char('.').into(), Size::default());
+        assert_eq!(app.current_dir_contents.len(), 2);
 
-        assert_eq!(app.current_dir_path, nested_dir_path);
+        assert!(app.select_entry_by_name(OsStr::new("src")));
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
         assert_eq!(
             app.current_dir_contents,
-            vec![nested_file_path_0.clone(), nested_file_path_1.clone()]
+            [tmp_dir.path().join("src/main.rs")]
         );
-        assert_eq!(app.current_cursor_position(), 0);
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        assert_eq!(app.current_dir_contents.len(), 2);
 
-        // Go up a dir when left key pressed
-        app.handle_key_event(KeyCode::Down.into(), frame_size);
-        assert_eq!(app.current_dir_path, nested_dir_path);
-        assert_eq!(
-            app.current_dir_contents,
-            vec![nested_file_path_0.clone(), nested_file_path_1.clone()]
-        );
+        app.handle_key_event(KeyCode::Char('.').into(), Size::default());
+        assert_eq!(app.current_dir_contents.len(), 4);
+    }
+
+    #[test]
+    fn selection_that_gets_hidden_is_clamped() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        // `-` sorts before `.`, leaving the dotfile last
+        for name in ["-a.txt", "-b.txt", ".z"] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        assert!(app.select_entry_by_name(OsStr::new(".z")));
+        assert_eq!(app.current_cursor_position(), 2);
+
+        app.handle_key_event(KeyCode::Char('.').into(), Size::default());
         assert_eq!(app.current_cursor_position(), 1);
+    }
 
-        app.handle_key_event(KeyCode::Backspace.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 0);
+    #[test]
+    fn auto_refresh_picks_up_new_files_once_the_interval_passes() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        File::create(tmp_dir.path().join("a.log")).unwrap();
+
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.auto_refresh_secs = 5;
+        let started = Instant::now();
+        app.on_tick(started, Size::default());
+
+        File::create(tmp_dir.path().join("b.log")).unwrap();
+        app.on_tick(started + Duration::from_secs(4), Size::default());
+        assert_eq!(app.current_dir_contents.len(), 1);
+
+        app.on_tick(started + Duration::from_secs(5), Size::default());
         assert_eq!(
             app.current_dir_contents,
-            vec![file_path.clone(), nested_dir_path.clone()]
+            vec![tmp_dir.path().join("a.log"), tmp_dir.path().join("b.log")]
         );
     }
 
     #[test]
-    fn cursor_position_retained_after_entering_then_exiting_dir() {
+    fn auto_refresh_closes_quick_look_once_its_file_is_gone() {
         let tmp_dir = TempDir::new("tmp_dir").unwrap();
-        let nested_dir_path =
-            PathBuf::from(format!("{}/nested_dir", tmp_dir.path().to_str().unwrap()));
-        let _nested_dir = create_dir(&nested_dir_path);
-        let file_path = tmp_dir.path().join("file.txt");
-        let _tmp_file = File::create(&file_path).unwrap();
-
-        let mut app = App::new(tmp_dir.path().to_path_buf());
-
-        let frame_size = Size {
-            width: 1,
-            height: 5,
-        };
-
-        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
-        assert_eq!(app.current_cursor_position(), 0);
+        let notes_path = tmp_dir.path().join("notes.txt");
+        std::fs::write(&notes_path, "one\ntwo\nthree").unwrap();
 
-        // Change cursor position to 1
-        app.handle_key_event(KeyCode::Down.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 1);
-        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.config.auto_refresh_secs = 5;
+        let started = Instant::now();
+        app.on_tick(started, Size::default());
+        app.handle_key_event(KeyCode::Char(' ').into(), Size::default());
+        assert_eq!(app.quick_look_scroll_offset, Some(0));
 
-        // Entering directory sets cursor position to 0, as this is the first time entering
-        app.handle_key_event(KeyCode::Enter.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 0);
-        assert_eq!(app.current_dir_path, nested_dir_path);
+        std::fs::remove_file(&notes_path).unwrap();
+        app.on_tick(started + Duration::from_secs(5), Size::default());
+        assert!(app.current_dir_contents.is_empty());
+        assert_eq!(app.quick_look_scroll_offset, None);
+        assert_eq!(app.quick_look_text, None);
 
-        // Exiting directory sets cursor position back to 1
-        app.handle_key_event(KeyCode::Backspace.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 1);
-        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Down.into(), Size::default());
+        assert_eq!(app.quick_look_scroll_offset, None);
     }
 
     #[test]
-    fn entering_a_new_sub_directory_starts_cursor_position_at_0() {
+    fn n_and_shift_n_cycle_through_search_matches() {
         let tmp_dir = TempDir::new("tmp_dir").unwrap();
-        let nested_dir_path_0 =
-            PathBuf::from(format!("{}/nested_dir_0", tmp_dir.path().to_str().unwrap()));
-        let nested_dir_path_1 =
-            PathBuf::from(format!("{}/nested_dir_1", tmp_dir.path().to_str().unwrap()));
-        let _nested_dir_0 = create_dir(&nested_dir_path_0);
-        let _nested_dir_1 = create_dir(&nested_dir_path_1);
+        for name in ["a.txt", "log-1", "b.txt", "log-2", "c.txt", "log-3"] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
+        let log = |number: usize| tmp_dir.path().join(format!("log-{number}"));
 
-        let nested_file_path_0 = nested_dir_path_0.join("file_a.txt");
-        let nested_file_path_1 = nested_dir_path_0.join("file_b.txt");
-        let _nested_file_0 = File::create(&nested_file_path_0).unwrap();
-        let _nested_file_1 = File::create(&nested_file_path_1).unwrap();
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('/').into(), Size::default());
+        for character in "LOG".chars() {
+            app.handle_key_event(KeyCode::Char(character).into(), Size::default());
+        }
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(app.selected_path(), Some(log(1).as_path()));
+        assert_eq!(app.status_message.as_deref(), Some("Match 1 of 3"));
 
-        let mut app = App::new(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('n').into(), Size::default());
+        assert_eq!(app.selected_path(), Some(log(2).as_path()));
+        app.handle_key_event(KeyCode::Char('n').into(), Size::default());
+        assert_eq!(app.selected_path(), Some(log(3).as_path()));
+        app.handle_key_event(KeyCode::Char('n').into(), Size::default());
+        assert_eq!(app.selected_path(), Some(log(1).as_path()));
 
-        let frame_size = Size {
-            width: 1,
-            height: 5,
-        };
+        app.handle_key_event(KeyCode::Char('N').into(), Size::default());
+        assert_eq!(app.selected_path(), Some(log(3).as_path()));
+        assert_eq!(app.status_message.as_deref(), Some("Match 3 of 3"));
 
-        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
-        assert_eq!(app.current_cursor_position(), 0);
+        // Once the search is closed, `n` creates a file again
+        app.handle_key_event(KeyCode::Esc.into(), Size::default());
+        app.handle_key_event(KeyCode::Char('n').into(), Size::default());
+        assert!(app.name_prompt.is_some());
+    }
 
-        // Entering directory sets cursor position to 0, as this is the first time entering
-        app.handle_key_event(KeyCode::Enter.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 0);
-        assert_eq!(app.current_dir_path, nested_dir_path_0);
+    #[test]
+    fn search_narrows_what_was_read_without_reading_again() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        File::create(tmp_dir.path().join("notes.txt")).unwrap();
 
-        // Can change this directories cursor position
-        app.handle_key_event(KeyCode::Down.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 1);
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('/').into(), Size::default());
+        // Only a real refresh would list this
+        File::create(tmp_dir.path().join("new.txt")).unwrap();
+        app.handle_key_event(KeyCode::Char('t').into(), Size::default());
+        assert_eq!(app.current_dir_contents, [tmp_dir.path().join("notes.txt")]);
 
-        // Exiting directory sets cursor position back to 0
-        app.handle_key_event(KeyCode::Backspace.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 0);
-        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        // Keys held with ctrl or alt aren't typed into the query
+        app.handle_key_event(
+            KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL),
+            Size::default(),
+        );
+        app.handle_key_event(
+            KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT),
+            Size::default(),
+        );
+        assert_eq!(app.search.as_ref().unwrap().query, "t");
 
-        // Move cursor to other directory
-        app.handle_key_event(KeyCode::Down.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 1);
+        app.handle_key_event(KeyCode::Esc.into(), Size::default());
+        assert_eq!(app.current_dir_contents.len(), 1);
+        app.refresh_keeping_selection();
+        assert_eq!(app.current_dir_contents.len(), 2);
+    }
 
-        // Go into this new directory
-        app.handle_key_event(KeyCode::Enter.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 0);
-        assert_eq!(app.current_dir_path, nested_dir_path_1);
+    #[test]
+    fn fuzzy_search_narrows_the_listing_until_esc() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        for name in ["cargo.lock", "Cargo.toml", "README.md", "src-config.rs"] {
+            File::create(tmp_dir.path().join(name)).unwrap();
+        }
+        let listed_names = |app: &App| {
+            app.current_dir_contents
+                .iter()
+                .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
 
-        // Exiting directory again sets cursor position back to 1
-        app.handle_key_event(KeyCode::Backspace.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 1);
-        assert_eq!(app.current_dir_path, tmp_dir.path().to_path_buf());
+        let mut app = new_app(tmp_dir.path().to_path_buf());
+        app.handle_key_event(KeyCode::Char('/').into(), Size::default());
+        for character in "CT".chars() {
+            app.handle_key_event(KeyCode::Char(character).into(), Size::default());
+        }
+        assert_eq!(listed_names(&app), ["Cargo.toml"]);
 
-        // Entering first sub directory and cursor position is 0
-        app.handle_key_event(KeyCode::Up.into(), frame_size);
-        app.handle_key_event(KeyCode::Enter.into(), frame_size);
-        assert_eq!(app.current_cursor_position(), 0);
-        assert_eq!(app.current_dir_path, nested_dir_path_0);
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        assert_eq!(listed_names(&app).len(), 3);
+        for character in "fg".chars() {
+            app.handle_key_event(KeyCode::Char(character).into(), Size::default());
+        }
+        assert_eq!(listed_names(&app), ["src-config.rs"]);
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        app.handle_key_event(KeyCode::Backspace.into(), Size::default());
+        app.handle_key_event(KeyCode::Char('o').into(), Size::default());
+        assert_eq!(
+            listed_names(&app),
+            ["Cargo.toml", "cargo.lock", "src-config.rs"]
+        );
+        // Letters that run together from the start of a word beat the first entry listed
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(
+            app.selected_entry(),
+            Some(&tmp_dir.path().join("src-config.rs"))
+        );
+
+        app.handle_key_event(KeyCode::Esc.into(), Size::default());
+        assert_eq!(listed_names(&app).len(), 4);
+        assert_eq!(
+            app.selected_entry(),
+            Some(&tmp_dir.path().join("src-config.rs"))
+        );
     }
 
     #[test]
-    fn default_render_app_border() {
+    fn jumping_to_a_favorite_drops_the_search() {
         let tmp_dir = TempDir::new("tmp_dir").unwrap();
-        let nested_dir_path =
-            PathBuf::from(format!("{}/nested_dir", tmp_dir.path().to_str().unwrap()));
-        let _nested_dir = create_dir(&nested_dir_path);
-        let file_path_0 = tmp_dir.path().join("file.txt");
-        let _tmp_file_0 = File::create(&file_path_0).unwrap();
-        let file_path_1 = tmp_dir.path().join("zzz.txt");
-        let _tmp_file = File::create(&file_path_1).unwrap();
+        let search_dir_path = tmp_dir.path().join("search_dir");
+        let favorite_dir_path = tmp_dir.path().join("favorite_dir");
+        let _search_dir = create_dir(&search_dir_path);
+        let _favorite_dir = create_dir(&favorite_dir_path);
+        File::create(search_dir_path.join("notes.txt")).unwrap();
+        let favorite_paths = vec![
+            favorite_dir_path.join("data.csv"),
+            favorite_dir_path.join("notes.txt"),
+        ];
+        for path in &favorite_paths {
+            File::create(path).unwrap();
+        }
 
-        let app = App::new(tmp_dir.path().to_path_buf());
+        let mut app = new_app(search_dir_path);
+        app.favorites.add(&favorite_dir_path);
+        app.handle_key_event(KeyCode::Char('/').into(), Size::default());
+        for character in "notes".chars() {
+            app.handle_key_event(KeyCode::Char(character).into(), Size::default());
+        }
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert!(app.search.is_some());
 
-        let mut buf = Buffer::empty(Rect::new(0, 0, 81, 5));
+        app.handle_key_event(KeyCode::Char('b').into(), Size::default());
+        app.handle_key_event(KeyCode::Enter.into(), Size::default());
+        assert_eq!(app.current_dir_path, favorite_dir_path);
+        assert!(app.search.is_none());
+        assert_eq!(app.current_dir_contents, favorite_paths);
+    }
 
-        app.render(buf.area, &mut buf);
+    #[test]
+    fn help_lists_remapped_keys_and_scrolls_to_later_entries() {
+        let mut app = App::default().with_described_key_handler(
+            KeyCode::Char('d'),
+            "Duplicate the entry",
+            |_| {},
+        );
+        let frame_size = Size::new(100, 20);
+        let rendered_text = |app: &App| rendered_text(app, frame_size.width, frame_size.height);
 
-        let mut expected = Buffer::with_lines(vec![
-            "┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━ TUI File Explorer ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┓",
-            &format!("┃{:width$}┃", tmp_dir.path().to_str().unwrap(), width = 79),
-            "┃                                                                               ┃",
-            "┃                                                                               ┃",
-            "┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┛",
-        ]);
-        let title_style = Style::new().bold();
-        let current_dir_style = Style::new();
+        app.handle_key_event(KeyCode::Char('?').into(), frame_size);
+        let text = rendered_text(&app);
+        assert!(text.contains("Move up"));
+        assert!(!text.contains("Quit without asking"));
 
-        let temp_dir_absolute_path_length = tmp_dir.path().to_str().unwrap().len() as u16;
-        expected.set_style(Rect::new(31, 0, 19, 1), title_style);
-        expected.set_style(
-            Rect::new(1, 1, 1 + temp_dir_absolute_path_length, 1),
-            current_dir_style,
+        for _ in 0..100 {
+            app.handle_key_event(KeyCode::Down.into(), frame_size);
+        }
+        let text = rendered_text(&app);
+        assert!(!text.contains("Move up"));
+        assert!(text.contains("Duplicate the entry"));
+        assert!(!text.contains("Delete the entry"));
+        // Stops with the last line at the bottom of the overlay, 14 lines inside its border
+        assert!(text.contains("Quit without asking"));
+        assert_eq!(
+            app.help_scroll_offset,
+            Some(help_lines(&app.key_handlers).len() - 14)
         );
 
-        assert_eq!(buf, expected);
+        app.handle_key_event(KeyCode::Esc.into(), frame_size);
+        assert_eq!(app.help_scroll_offset, None);
+        assert!(!rendered_text(&app).contains("Duplicate the entry"));
     }
 }
@@ -0,0 +1,166 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use flate2::read::GzDecoder;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// Detects an archive by its extension, falling back to the magic bytes at the start of the file
+pub fn archive_kind(file_path: &Path) -> Option<ArchiveKind> {
+    let file_name = file_path.file_name()?.to_str()?.to_lowercase();
+
+    if file_name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if file_name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else {
+        archive_kind_from_magic_bytes(file_path)
+    }
+}
+
+fn archive_kind_from_magic_bytes(file_path: &Path) -> Option<ArchiveKind> {
+    let mut header = Vec::with_capacity(262);
+    File::open(file_path)
+        .ok()?
+        .take(262)
+        .read_to_end(&mut header)
+        .ok()?;
+
+    if header.starts_with(b"PK\x03\x04") {
+        Some(ArchiveKind::Zip)
+    } else if header.get(257..262) == Some(b"ustar") {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+/// Lists up to `max_entries` of the archive's members, one `size  name` line per member
+pub fn get_archive_listing(
+    file_path: &Path,
+    kind: ArchiveKind,
+    max_entries: usize,
+) -> io::Result<Vec<String>> {
+    let file = File::open(file_path)?;
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(file)?;
+            (0..archive.len().min(max_entries))
+                .map(|index| {
+                    let entry = archive.by_index_raw(index)?;
+                    Ok(format_archive_entry(&entry.name()?, entry.size()))
+                })
+                .collect()
+        }
+        ArchiveKind::Tar => list_tar_entries(tar::Archive::new(file), max_entries),
+        ArchiveKind::TarGz => {
+            list_tar_entries(tar::Archive::new(GzDecoder::new(file)), max_entries)
+        }
+    }
+}
+
+fn list_tar_entries<R: Read>(
+    mut archive: tar::Archive<R>,
+    max_entries: usize,
+) -> io::Result<Vec<String>> {
+    archive
+        .entries()?
+        .take(max_entries)
+        .map(|entry| {
+            let entry = entry?;
+            Ok(format_archive_entry(
+                &entry.path()?.to_string_lossy(),
+                entry.size(),
+            ))
+        })
+        .collect()
+}
+
+fn format_archive_entry(name: &str, size: u64) -> String {
+    format!("{size:>10}  {name}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tempdir::TempDir;
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+
+    #[test]
+    fn archive_kind_detected_from_extension() {
+        assert_eq!(archive_kind(Path::new("a.zip")), Some(ArchiveKind::Zip));
+        assert_eq!(archive_kind(Path::new("a.TAR")), Some(ArchiveKind::Tar));
+        assert_eq!(
+            archive_kind(Path::new("a.tar.gz")),
+            Some(ArchiveKind::TarGz)
+        );
+        assert_eq!(archive_kind(Path::new("a.tgz")), Some(ArchiveKind::TarGz));
+        assert_eq!(archive_kind(Path::new("a.txt")), None);
+    }
+
+    #[test]
+    fn zip_listing_contains_member_names_and_sizes() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        // No extension, so detection has to use the magic bytes
+        let zip_path = tmp_dir.path().join("archive");
+
+        let mut zip_writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        zip_writer
+            .start_file("hello.txt", SimpleFileOptions::default())
+            .unwrap();
+        zip_writer.write_all(b"hello").unwrap();
+        zip_writer
+            .start_file("nested/world.txt", SimpleFileOptions::default())
+            .unwrap();
+        zip_writer.write_all(b"hello world").unwrap();
+        zip_writer.finish().unwrap();
+
+        assert_eq!(archive_kind(&zip_path), Some(ArchiveKind::Zip));
+        assert_eq!(
+            get_archive_listing(&zip_path, ArchiveKind::Zip, 10).unwrap(),
+            vec![
+                "         5  hello.txt".to_string(),
+                "        11  nested/world.txt".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn tar_listing_contains_member_names_and_stops_at_max_entries() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let tar_path = tmp_dir.path().join("archive.tar");
+
+        let mut tar_builder = tar::Builder::new(File::create(&tar_path).unwrap());
+        for (name, contents) in [("a.txt", "a"), ("b.txt", "bb"), ("c.txt", "ccc")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_cksum();
+            tar_builder
+                .append_data(&mut header, name, contents.as_bytes())
+                .unwrap();
+        }
+        tar_builder.finish().unwrap();
+
+        assert_eq!(
+            get_archive_listing(&tar_path, ArchiveKind::Tar, 2).unwrap(),
+            vec![
+                "         1  a.txt".to_string(),
+                "         2  b.txt".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    symbols::border,
+    text::{Line, Text},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+use crate::batch_rename::Rename;
+
+/// Preview of the old and new names in a batch rename, flagging any new names that collide
+pub struct BatchRenameView<'a> {
+    renames: &'a [Rename],
+    collisions: &'a HashSet<PathBuf>,
+}
+
+impl<'a> BatchRenameView<'a> {
+    pub fn new(renames: &'a [Rename], collisions: &'a HashSet<PathBuf>) -> Self {
+        BatchRenameView {
+            renames,
+            collisions,
+        }
+    }
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|os_str| os_str.to_str())
+        .unwrap_or("<invalid utf-8>")
+}
+
+impl Widget for &BatchRenameView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from(" Rename ".bold()).centered())
+            .borders(Borders::LEFT)
+            .border_set(border::ROUNDED);
+
+        let lines: Vec<Line> = self
+            .renames
+            .iter()
+            .map(|rename| {
+                let line = format!("{} → {}", file_name(&rename.from), file_name(&rename.to));
+                if self.collisions.contains(&rename.to) {
+                    Line::from(format!("{line} (collides)")).red()
+                } else {
+                    Line::from(line)
+                }
+            })
+            .collect();
+
+        Paragraph::new(Text::from(lines))
+            .left_aligned()
+            .block(block)
+            .render(area, buf);
+    }
+}
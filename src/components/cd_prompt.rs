@@ -0,0 +1,70 @@
+/// Text typed after `:`, naming a child directory of the current directory to descend into
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CdPrompt {
+    pub input: String,
+}
+
+impl CdPrompt {
+    pub fn prompt(&self) -> String {
+        format!("cd {}_", self.input)
+    }
+
+    /// Extends the input to the longest prefix shared by every subdirectory it could be naming
+    pub fn complete(&mut self, subdirectory_names: &[String]) {
+        let mut candidates = subdirectory_names
+            .iter()
+            .filter(|name| name.starts_with(&self.input));
+        let Some(first) = candidates.next() else {
+            return;
+        };
+
+        let common_prefix = candidates.fold(first.as_str(), |prefix, name| {
+            let shared_length = prefix
+                .char_indices()
+                .zip(name.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map_or(0, |((index, a), _)| index + a.len_utf8());
+            &prefix[..shared_length]
+        });
+        self.input = common_prefix.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subdirectory_names() -> Vec<String> {
+        ["documents", "downloads", "music"]
+            .map(str::to_string)
+            .to_vec()
+    }
+
+    #[test]
+    fn unique_match_is_completed_in_full() {
+        let mut cd_prompt = CdPrompt {
+            input: "mu".to_string(),
+        };
+        cd_prompt.complete(&subdirectory_names());
+        assert_eq!(cd_prompt.input, "music");
+    }
+
+    #[test]
+    fn ambiguous_match_is_completed_to_the_common_prefix() {
+        let mut cd_prompt = CdPrompt {
+            input: "d".to_string(),
+        };
+        cd_prompt.complete(&subdirectory_names());
+        assert_eq!(cd_prompt.input, "do");
+    }
+
+    #[test]
+    fn no_match_leaves_the_input_alone() {
+        let mut cd_prompt = CdPrompt {
+            input: "x".to_string(),
+        };
+        cd_prompt.complete(&subdirectory_names());
+        assert_eq!(cd_prompt.input, "x");
+    }
+}
@@ -0,0 +1,47 @@
+use std::{path::PathBuf, time::Instant};
+
+use crate::batch_rename::Rename;
+
+/// An action that only runs once the user has answered `y` to its prompt
+#[derive(Debug, Clone, PartialEq)]
+pub enum PendingAction {
+    EnterDir(PathBuf),
+    Preview,
+    PasteMarked,
+    MoveMarked,
+    ExportSelectionList,
+    Delete(PathBuf),
+    Quit,
+    BatchRename(Vec<Rename>),
+}
+
+impl PendingAction {
+    /// Whether the action removes entries from where they were, so can skip its prompt when
+    /// `confirm_destructive` is off. Pasting isn't, as a copy never replaces an entry already
+    /// in its way
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, PendingAction::Delete(_) | PendingAction::MoveMarked)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Confirmation {
+    pub message: String,
+    pub action: PendingAction,
+    /// The first tick the prompt was shown on, which its timeout counts from
+    pub shown_since: Option<Instant>,
+}
+
+impl Confirmation {
+    pub fn new(message: String, action: PendingAction) -> Self {
+        Confirmation {
+            message,
+            action,
+            shown_since: None,
+        }
+    }
+
+    pub fn prompt(&self) -> String {
+        format!("{} (y/n)", self.message)
+    }
+}
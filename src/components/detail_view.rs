@@ -0,0 +1,197 @@
+use std::{
+    fs::{self, Metadata},
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    symbols::border,
+    text::{Line, Text},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+use crate::human_size::{SizeUnits, human_size};
+
+/// Metadata about a single entry, formatted for display
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryDetails {
+    pub path: PathBuf,
+    pub kind: String,
+    pub size: String,
+    pub modified: String,
+    pub permissions: String,
+}
+
+impl EntryDetails {
+    /// Reads the entry's own metadata, so symlinks are described rather than followed
+    pub fn of(path: &Path, units: SizeUnits) -> io::Result<Self> {
+        let metadata = fs::symlink_metadata(path)?;
+        let file_type = metadata.file_type();
+        let kind = if file_type.is_symlink() {
+            "symlink"
+        } else if file_type.is_dir() {
+            "directory"
+        } else if file_type.is_file() {
+            "file"
+        } else {
+            "other"
+        };
+
+        Ok(EntryDetails {
+            path: path.to_path_buf(),
+            kind: kind.to_string(),
+            size: if file_type.is_dir() {
+                "-".to_string()
+            } else {
+                human_size(metadata.len(), units)
+            },
+            modified: metadata
+                .modified()
+                .map(format_timestamp)
+                .unwrap_or_else(|_| "unknown".to_string()),
+            permissions: format_permissions(&metadata),
+        })
+    }
+
+    pub fn fields(&self) -> [(&'static str, &str); 4] {
+        [
+            ("Type", &self.kind),
+            ("Size", &self.size),
+            ("Modified", &self.modified),
+            ("Permissions", &self.permissions),
+        ]
+    }
+}
+
+/// Formats a time as `YYYY-MM-DD HH:MM` in UTC
+fn format_timestamp(time: SystemTime) -> String {
+    let Ok(since_epoch) = time.duration_since(UNIX_EPOCH) else {
+        return "before 1970".to_string();
+    };
+    let seconds = since_epoch.as_secs();
+    let (days, seconds_of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // Converts days since the epoch to a civil date, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60
+    )
+}
+
+#[cfg(unix)]
+fn format_permissions(metadata: &Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = metadata.permissions().mode();
+    ["r", "w", "x"]
+        .iter()
+        .cycle()
+        .take(9)
+        .enumerate()
+        .map(|(index, symbol)| {
+            if mode & (0o400 >> index) != 0 {
+                *symbol
+            } else {
+                "-"
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn format_permissions(metadata: &Metadata) -> String {
+    if metadata.permissions().readonly() {
+        "read-only".to_string()
+    } else {
+        "read-write".to_string()
+    }
+}
+
+/// Side panel describing the selected entry
+pub struct DetailView<'a> {
+    details: Option<&'a EntryDetails>,
+}
+
+impl<'a> DetailView<'a> {
+    pub fn new(details: Option<&'a EntryDetails>) -> Self {
+        DetailView { details }
+    }
+}
+
+impl Widget for &DetailView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from(" Details ".bold()).centered())
+            .borders(Borders::LEFT)
+            .border_set(border::ROUNDED);
+
+        let lines: Vec<Line> = match self.details {
+            Some(details) => details
+                .fields()
+                .into_iter()
+                .map(|(label, value)| Line::from(format!("{label}: {value}")))
+                .collect(),
+            None => vec![Line::from("No details")],
+        };
+
+        Paragraph::new(Text::from(lines))
+            .left_aligned()
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn timestamps_are_formatted_as_utc_dates() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01 00:00");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(1_709_210_096)),
+            "2024-02-29 12:34"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_details_are_read() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        fs::write(&file_path, "hello").unwrap();
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        let details = EntryDetails::of(&file_path, SizeUnits::Binary).unwrap();
+        assert_eq!(details.kind, "file");
+        assert_eq!(details.size, "5B");
+        assert_eq!(details.permissions, "rw-r-----");
+    }
+}
@@ -0,0 +1,81 @@
+use std::{fmt, path::Path};
+
+/// A shallow summary of a directory's contents
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DirSummary {
+    Readable { items: usize, dirs: usize },
+    Unreadable,
+}
+
+impl DirSummary {
+    /// Only reads the directory itself, not any of its subdirectories
+    pub fn of(dir_path: &Path) -> Self {
+        let Ok(entries) = std::fs::read_dir(dir_path) else {
+            return DirSummary::Unreadable;
+        };
+
+        let (items, dirs) =
+            entries
+                .filter_map(|entry| entry.ok())
+                .fold((0, 0), |(items, dirs), entry| {
+                    let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+                    (items + 1, dirs + usize::from(is_dir))
+                });
+        DirSummary::Readable { items, dirs }
+    }
+}
+
+impl fmt::Display for DirSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DirSummary::Readable { items, dirs } => write!(
+                f,
+                "{items} {}, {dirs} {}",
+                if *items == 1 { "item" } else { "items" },
+                if *dirs == 1 { "dir" } else { "dirs" }
+            ),
+            DirSummary::Unreadable => f.write_str("(unreadable)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{File, create_dir};
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn summary_counts_items_and_dirs() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        for dir_name in ["dir_a", "dir_b", "dir_c"] {
+            create_dir(tmp_dir.path().join(dir_name)).unwrap();
+        }
+        // Nested entries aren't counted
+        File::create(tmp_dir.path().join("dir_a").join("nested.txt")).unwrap();
+        for file_name in ["a.txt", "b.txt"] {
+            File::create(tmp_dir.path().join(file_name)).unwrap();
+        }
+
+        let summary = DirSummary::of(tmp_dir.path());
+        assert_eq!(summary, DirSummary::Readable { items: 5, dirs: 3 });
+        assert_eq!(summary.to_string(), "5 items, 3 dirs");
+    }
+
+    #[test]
+    fn summary_uses_singular_for_one() {
+        assert_eq!(
+            DirSummary::Readable { items: 1, dirs: 1 }.to_string(),
+            "1 item, 1 dir"
+        );
+    }
+
+    #[test]
+    fn missing_dir_is_unreadable() {
+        let summary = DirSummary::of(Path::new("/does/not/exist"));
+        assert_eq!(summary, DirSummary::Unreadable);
+        assert_eq!(summary.to_string(), "(unreadable)");
+    }
+}
@@ -1,122 +1,812 @@
-use std::path::{Path, PathBuf};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    ops::Range,
+    path::{Path, PathBuf},
+};
 
 use ratatui::{
     buffer::Buffer,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::Stylize,
-    text::{Line, Text},
-    widgets::{Paragraph, Widget},
+    layout::{Alignment, Constraint, Direction, Flex, Layout, Rect},
+    text::{Line, Span, Text},
+    widgets::{Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget},
+};
+
+use crate::{
+    components::{
+        dir_summary::DirSummary, extension_groups::ExtensionGroup, group_headers::GroupHeader,
+        search::Search, theme::Theme,
+    },
+    display_width::display_width,
+    paths::is_hidden,
 };
 
-pub struct DirectoryView {
+/// A column between the cursor marker and the names, marking the entries it applies to. Each one
+/// is reserved for every row while its feature has anything to mark, so names stay lined up
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Gutter {
+    Star,
+    /// Directories git ignores, while they are dimmed rather than hidden
+    Ignored,
+    /// Directories another filesystem is mounted at
+    MountPoint,
+    /// Entries the `pinned` setting keeps at the top
+    Pinned,
+}
+
+impl Gutter {
+    fn glyph(self) -> &'static str {
+        match self {
+            Gutter::Star => "★",
+            Gutter::Ignored => "!",
+            Gutter::MountPoint => "⛁",
+            Gutter::Pinned => "↑",
+        }
+    }
+}
+
+/// A row of the listing, either an entry or the title of the group of entries below it
+#[derive(Debug, Clone, PartialEq)]
+enum Row {
+    Entry(PathBuf),
+    Header(String),
+}
+
+impl Row {
+    fn entry(&self) -> Option<&PathBuf> {
+        match self {
+            Row::Entry(entity) => Some(entity),
+            Row::Header(_) => None,
+        }
+    }
+}
+
+/// Drawn before a nested entry's name once for each level it is below the listed directory
+const NESTING_INDENT: &str = "  ";
+
+type EntryFilter<'a> = Box<dyn Fn(&Path) -> bool + 'a>;
+type EntryOrder<'a> = Box<dyn Fn(&Path, &Path) -> Ordering + 'a>;
+
+/// A listing of directory entries laid out in columns, usable in any ratatui layout
+///
+/// The cursor position is an index into the entries left once the hidden, filter and sort options
+/// have been applied. Group headers take up a row of their own, which the cursor's row and column
+/// count like any other.
+pub struct DirectoryView<'a> {
     current_dir_contents: Vec<PathBuf>,
     cursor_column_index: usize,
     cursor_row_index: usize,
+    theme: &'a Theme,
+    extension_groups: &'a [ExtensionGroup],
+    group_headers: &'a [GroupHeader],
+    nested_under: Option<&'a Path>,
+    marked: Option<&'a HashSet<PathBuf>>,
+    starred: Option<&'a HashSet<PathBuf>>,
+    dimmed: Option<&'a HashSet<PathBuf>>,
+    cut: Option<&'a Path>,
+    mount_points: Option<&'a HashSet<PathBuf>>,
+    pinned: Option<&'a HashSet<PathBuf>>,
+    hard_links: Option<&'a HashMap<PathBuf, u64>>,
+    search: Option<&'a Search>,
+    dir_summaries: Option<&'a HashMap<PathBuf, DirSummary>>,
+    max_columns: Option<usize>,
+    show_hidden: bool,
+    filter: Option<EntryFilter<'a>>,
+    sort: Option<EntryOrder<'a>>,
+    width_budget: Option<u16>,
+    scroll_offset: Option<usize>,
+    column_width_adjustments: Option<&'a HashMap<usize, i16>>,
 }
 
-impl DirectoryView {
+impl<'a> DirectoryView<'a> {
     pub fn new(
         current_dir_contents: Vec<PathBuf>,
         cursor_column_index: usize,
         cursor_row_index: usize,
+        theme: &'a Theme,
     ) -> Self {
         DirectoryView {
             current_dir_contents,
             cursor_column_index,
             cursor_row_index,
+            theme,
+            extension_groups: &[],
+            group_headers: &[],
+            nested_under: None,
+            marked: None,
+            starred: None,
+            dimmed: None,
+            cut: None,
+            mount_points: None,
+            pinned: None,
+            hard_links: None,
+            search: None,
+            dir_summaries: None,
+            max_columns: None,
+            show_hidden: true,
+            filter: None,
+            sort: None,
+            width_budget: None,
+            scroll_offset: None,
+            column_width_adjustments: None,
+        }
+    }
+
+    /// Marked entries are drawn with the theme's marked style on top of their usual style
+    pub fn with_marked(mut self, marked: &'a HashSet<PathBuf>) -> Self {
+        self.marked = Some(marked);
+        self
+    }
+
+    /// Starred entries are drawn with a star after their name
+    pub fn with_starred(mut self, starred: &'a HashSet<PathBuf>) -> Self {
+        self.starred = Some(starred);
+        self
+    }
+
+    /// Dimmed entries are drawn with the theme's ignored style on top of their usual style
+    pub fn with_dimmed(mut self, dimmed: &'a HashSet<PathBuf>) -> Self {
+        self.dimmed = Some(dimmed);
+        self
+    }
+
+    /// The entry cut to be moved is drawn with the theme's cut style on top of its usual style
+    pub fn with_cut(mut self, cut: Option<&'a Path>) -> Self {
+        self.cut = cut;
+        self
+    }
+
+    /// Mount points are drawn with a disk before their name
+    pub fn with_mount_points(mut self, mount_points: &'a HashSet<PathBuf>) -> Self {
+        self.mount_points = Some(mount_points);
+        self
+    }
+
+    /// Pinned entries are drawn with an arrow before their name
+    pub fn with_pinned(mut self, pinned: &'a HashSet<PathBuf>) -> Self {
+        self.pinned = Some(pinned);
+        self
+    }
+
+    /// Files with more than one hard link have a link and how many they have shown after their name
+    pub fn with_hard_links(mut self, hard_links: &'a HashMap<PathBuf, u64>) -> Self {
+        self.hard_links = Some(hard_links);
+        self
+    }
+
+    /// The characters of each name matched by the search are drawn with the theme's search match
+    /// style
+    pub(crate) fn with_search(mut self, search: &'a Search) -> Self {
+        self.search = Some(search);
+        self
+    }
+
+    /// Directories with a summary have how many items they hold shown after their name
+    pub(crate) fn with_dir_summaries(
+        mut self,
+        dir_summaries: &'a HashMap<PathBuf, DirSummary>,
+    ) -> Self {
+        self.dir_summaries = Some(dir_summaries);
+        self
+    }
+
+    /// Collapsed groups are shown as a single summary row in place of their first member
+    pub(crate) fn with_extension_groups(mut self, extension_groups: &'a [ExtensionGroup]) -> Self {
+        self.extension_groups = extension_groups;
+        self
+    }
+
+    /// Each header is drawn on a row of its own, above the entry it starts the group at
+    pub(crate) fn with_group_headers(mut self, group_headers: &'a [GroupHeader]) -> Self {
+        self.group_headers = group_headers;
+        self
+    }
+
+    /// Entries inside subdirectories of `dir`, rather than directly in it, are indented beneath
+    /// them by how deeply they are nested
+    pub(crate) fn with_nested_under(mut self, dir: &'a Path) -> Self {
+        self.nested_under = Some(dir);
+        self
+    }
+
+    /// How many levels below the listed directory `entity` is
+    fn depth(&self, entity: &Path) -> usize {
+        self.nested_under
+            .and_then(|dir| entity.strip_prefix(dir).ok())
+            .map_or(0, |relative_path| {
+                relative_path.components().count().saturating_sub(1)
+            })
+    }
+
+    /// Lays out no more than `max_columns` columns, scrolling horizontally to keep the cursor in view
+    pub fn with_max_columns(mut self, max_columns: Option<usize>) -> Self {
+        self.max_columns = max_columns;
+        self
+    }
+
+    /// Whether entries whose names start with a `.` are listed, which they are by default
+    pub fn with_hidden(mut self, show_hidden: bool) -> Self {
+        self.show_hidden = show_hidden;
+        self
+    }
+
+    /// Only lists the entries `filter` returns true for
+    pub fn with_filter(mut self, filter: impl Fn(&Path) -> bool + 'a) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Lists entries in the order decided by `compare`, instead of the order they were given in
+    pub fn with_sort(mut self, compare: impl Fn(&Path, &Path) -> Ordering + 'a) -> Self {
+        self.sort = Some(Box::new(compare));
+        self
+    }
+
+    /// The most cells wide the listing may be, however much room it is given
+    pub fn with_width_budget(mut self, width_budget: u16) -> Self {
+        self.width_budget = Some(width_budget);
+        self
+    }
+
+    /// The entries to show, after the hidden, filter and sort options are applied
+    fn visible_contents(&self) -> Vec<PathBuf> {
+        let mut visible_contents: Vec<PathBuf> = self
+            .current_dir_contents
+            .iter()
+            .filter(|entity| self.show_hidden || !is_hidden(entity))
+            .filter(|entity| self.filter.as_ref().is_none_or(|filter| filter(entity)))
+            .cloned()
+            .collect();
+        if let Some(compare) = &self.sort {
+            visible_contents.sort_by(|a, b| compare(a, b));
+        }
+        visible_contents
+    }
+
+    /// The visible entries, with the header of each group in the row above its first entry
+    fn visible_rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        for entity in self.visible_contents() {
+            if let Some(group_header) = self
+                .group_headers
+                .iter()
+                .find(|group_header| group_header.first == entity)
+            {
+                rows.push(Row::Header(group_header.title()));
+            }
+            rows.push(Row::Entry(entity));
+        }
+        rows
+    }
+
+    /// The indices of the columns to lay out, ending at the cursor's column once it is scrolled past
+    fn visible_columns(&self, number_of_columns: usize) -> Range<usize> {
+        let Some(max_columns) = self.max_columns.filter(|&max_columns| max_columns > 0) else {
+            return 0..number_of_columns;
+        };
+
+        let first_column = (self.cursor_column_index + 1).saturating_sub(max_columns);
+        first_column..number_of_columns.min(first_column + max_columns)
+    }
+
+    /// Lists everything in a single column scrolled down by `offset` rows, with a scrollbar on the
+    /// right showing where the visible rows are in the full list
+    ///
+    /// In this mode the cursor row is an index into the full list of rows, and the cursor column is
+    /// ignored.
+    pub fn with_scrollbar(mut self, offset: usize) -> Self {
+        self.scroll_offset = Some(offset);
+        self
+    }
+
+    /// Widens (or narrows, when negative) columns by index from their automatic width
+    pub fn with_column_width_adjustments(
+        mut self,
+        column_width_adjustments: &'a HashMap<usize, i16>,
+    ) -> Self {
+        self.column_width_adjustments = Some(column_width_adjustments);
+        self
+    }
+
+    fn column_constraints(
+        &self,
+        dir_contents_columns: &[Vec<Row>],
+        visible_columns: Range<usize>,
+    ) -> Vec<Constraint> {
+        visible_columns
+            .zip(dir_contents_columns)
+            .map(|(column_index, column)| {
+                let gutters_width = self.gutters().len() * self.theme.gutter_width;
+                let widest_badge = column
+                    .iter()
+                    .filter_map(|row| self.badge(row.entry()?))
+                    .map(|badge| display_width(&badge))
+                    .max()
+                    .unwrap_or(0);
+                let widest_nesting = column
+                    .iter()
+                    .filter_map(|row| Some(self.depth(row.entry()?) * NESTING_INDENT.len()))
+                    .max()
+                    .unwrap_or(0);
+                let width = column_width(column, self.theme, self.extension_groups)
+                    .saturating_add((gutters_width + widest_badge + widest_nesting) as u16);
+                let adjustment = self
+                    .column_width_adjustments
+                    .and_then(|adjustments| adjustments.get(&column_index))
+                    .copied()
+                    .unwrap_or(0);
+                Constraint::Length(width.saturating_add_signed(adjustment).max(1))
+            })
+            .collect()
+    }
+
+    fn format_column(
+        &self,
+        column_rows: &[Row],
+        cursor_row_index: Option<usize>,
+    ) -> Vec<Line<'static>> {
+        let column_contents: Vec<PathBuf> =
+            column_rows.iter().filter_map(Row::entry).cloned().collect();
+        // The cursor is never on a header, so it is on the entry with as many entries above it
+        let cursor_entry_index = cursor_row_index
+            .filter(|&row_index| matches!(column_rows.get(row_index), Some(Row::Entry(_))))
+            .map(|row_index| {
+                column_rows[..row_index]
+                    .iter()
+                    .filter(|row| row.entry().is_some())
+                    .count()
+            });
+        let mut entry_lines = self
+            .format_entries(&column_contents, cursor_entry_index)
+            .into_iter();
+
+        column_rows
+            .iter()
+            .map(|row| match row {
+                Row::Entry(_) => entry_lines.next().unwrap_or_default(),
+                Row::Header(title) => Line::from(title.clone()).style(self.theme.header_style),
+            })
+            .collect()
+    }
+
+    fn format_entries(
+        &self,
+        column_contents: &[PathBuf],
+        cursor_row_index: Option<usize>,
+    ) -> Vec<Line<'static>> {
+        let gutters = self.gutters();
+        let mut lines = get_formatted_paths(
+            column_contents,
+            cursor_row_index,
+            self.theme,
+            self.extension_groups,
+            &|entity| {
+                gutters
+                    .iter()
+                    .map(|&gutter| {
+                        self.gutter_set(gutter)?
+                            .contains(entity)
+                            .then(|| gutter.glyph())
+                    })
+                    .collect()
+            },
+            &|entity| self.depth(entity),
+            &|name| {
+                self.search
+                    .map(|search| search.matched_chars(name))
+                    .unwrap_or_default()
+            },
+        );
+
+        for (line, entity) in lines.iter_mut().zip(column_contents) {
+            if self.dimmed.is_some_and(|dimmed| dimmed.contains(entity)) {
+                line.style = line.style.patch(self.theme.ignored_style);
+            }
+            if self.marked.is_some_and(|marked| marked.contains(entity)) {
+                line.style = line.style.patch(self.theme.marked_style);
+            }
+            if self.cut == Some(entity.as_path()) {
+                line.style = line.style.patch(self.theme.cut_style);
+            }
+            if let Some(badge) = self.badge(entity) {
+                if self.theme.right_to_left {
+                    line.spans
+                        .insert(0, Span::raw(format!("{} ", badge.trim_start())));
+                } else {
+                    line.push_span(badge);
+                }
+            }
+        }
+        lines
+    }
+
+    /// The item count shown after a directory's name, once it has been summarized, or the hard
+    /// link count shown after a file's
+    fn badge(&self, entity: &Path) -> Option<String> {
+        if let Some(links) = self
+            .hard_links
+            .and_then(|hard_links| hard_links.get(entity))
+        {
+            return Some(format!(" ⇄{links}"));
+        }
+        match self.dir_summaries?.get(entity)? {
+            DirSummary::Readable { items, .. } => Some(format!(" {items}")),
+            DirSummary::Unreadable => Some(" ?".to_string()),
+        }
+    }
+
+    fn gutter_set(&self, gutter: Gutter) -> Option<&'a HashSet<PathBuf>> {
+        match gutter {
+            Gutter::Star => self.starred,
+            Gutter::Ignored => self.dimmed,
+            Gutter::MountPoint => self.mount_points,
+            Gutter::Pinned => self.pinned,
+        }
+    }
+
+    /// The gutters with anything to mark, in the order they are shown
+    fn gutters(&self) -> Vec<Gutter> {
+        [
+            Gutter::Pinned,
+            Gutter::Star,
+            Gutter::Ignored,
+            Gutter::MountPoint,
+        ]
+        .into_iter()
+        .filter(|&gutter| self.gutter_set(gutter).is_some_and(|set| !set.is_empty()))
+        .collect()
+    }
+
+    fn render_scrolling(&self, area: Rect, buf: &mut Buffer, offset: usize) {
+        let visible_rows = self.visible_rows();
+        let height = area.height as usize;
+        let offset = offset.min(visible_rows.len().saturating_sub(height));
+
+        let [list_area, scrollbar_area] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Fill(1), Constraint::Length(1)])
+            .areas(area);
+
+        let shown_rows = &visible_rows[offset..visible_rows.len().min(offset + height)];
+        let cursor_row_index = self
+            .cursor_row_index
+            .checked_sub(offset)
+            .filter(|&row_index| row_index < height);
+        Paragraph::new(Text::from(self.format_column(shown_rows, cursor_row_index)))
+            .left_aligned()
+            .render(list_area, buf);
+
+        let mut scrollbar_state = ScrollbarState::new(visible_rows.len().saturating_sub(height))
+            .position(offset)
+            .viewport_content_length(height);
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .render(scrollbar_area, buf, &mut scrollbar_state);
+    }
+
+    /// How many columns the whole listing needs, and how many of them fit side by side in `area`
+    pub fn column_fit(&self, area: Rect) -> (usize, usize) {
+        let dir_contents_columns = self.get_dir_contents_as_columns(area.height.max(1));
+        let visible_columns = self.visible_columns(dir_contents_columns.len());
+        let columns_that_fit = self.columns_that_fit(
+            &dir_contents_columns[visible_columns.clone()],
+            visible_columns,
+            area.width,
+        );
+        (dir_contents_columns.len(), columns_that_fit)
+    }
+
+    /// Every row as plain text, one per line in the order they are listed, as it would be
+    /// drawn without the cursor marker and gutters
+    pub fn listing_text(&self) -> String {
+        self.visible_rows()
+            .iter()
+            .map(|row| match row {
+                Row::Entry(entity) => format!(
+                    "{}{}",
+                    NESTING_INDENT.repeat(self.depth(entity)),
+                    display_name(entity, self.extension_groups)
+                ),
+                Row::Header(title) => title.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The entries drawn in full when the listing is rendered into `area`, leaving out those in
+    /// columns squeezed past its edge
+    pub fn visible_entries(&self, area: Rect) -> Vec<PathBuf> {
+        let area = Rect {
+            width: self
+                .width_budget
+                .map_or(area.width, |budget| area.width.min(budget)),
+            ..area
+        };
+        let height = area.height.max(1) as usize;
+        if let Some(offset) = self.scroll_offset {
+            let visible_rows = self.visible_rows();
+            let offset = offset.min(visible_rows.len().saturating_sub(height));
+            return visible_rows
+                .iter()
+                .skip(offset)
+                .take(height)
+                .filter_map(Row::entry)
+                .cloned()
+                .collect();
         }
+
+        let dir_contents_columns = self.get_dir_contents_as_columns(height as u16);
+        let visible_columns = self.visible_columns(dir_contents_columns.len());
+        let dir_contents_columns = &dir_contents_columns[visible_columns.clone()];
+        let columns_that_fit =
+            self.columns_that_fit(dir_contents_columns, visible_columns, area.width);
+        dir_contents_columns
+            .iter()
+            .take(columns_that_fit.max(1))
+            .flatten()
+            .filter_map(Row::entry)
+            .cloned()
+            .collect()
     }
 
-    fn get_dir_contents_as_columns(&self, column_height: u16) -> Vec<Vec<PathBuf>> {
-        self.current_dir_contents
+    fn columns_that_fit(
+        &self,
+        dir_contents_columns: &[Vec<Row>],
+        visible_columns: Range<usize>,
+        width: u16,
+    ) -> usize {
+        let mut used_width = 0;
+        self.column_constraints(dir_contents_columns, visible_columns)
+            .iter()
+            .take_while(|constraint| {
+                let Constraint::Length(column_width) = constraint else {
+                    return false;
+                };
+                used_width += column_width;
+                used_width <= width
+            })
+            .count()
+    }
+
+    fn get_dir_contents_as_columns(&self, column_height: u16) -> Vec<Vec<Row>> {
+        self.visible_rows()
             .chunks(column_height as usize)
             .map(|chunk| chunk.to_vec())
             .collect()
     }
 }
 
-impl Widget for &DirectoryView {
+impl Widget for &DirectoryView<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let width = self
+            .width_budget
+            .map_or(area.width, |budget| area.width.min(budget));
+        // Right to left, the listing keeps to the right edge of the area instead
+        let x = if self.theme.right_to_left {
+            area.right() - width
+        } else {
+            area.x
+        };
+        let area = Rect { x, width, ..area };
+        if let Some(offset) = self.scroll_offset {
+            self.render_scrolling(area, buf, offset);
+            return;
+        }
+
         let dir_contents_columns = self.get_dir_contents_as_columns(area.height);
+        let visible_columns = self.visible_columns(dir_contents_columns.len());
+        let dir_contents_columns = &dir_contents_columns[visible_columns.clone()];
 
-        let column_widths: Vec<Constraint> = dir_contents_columns
-            .iter()
-            .map(|column| {
-                Constraint::Length(
-                    (column
-                        .iter()
-                        .map(|e| e.file_name().unwrap().to_str().unwrap().len())
-                        .max()
-                        .unwrap()
-                        + 8) as u16,
-                )
-            })
-            .collect();
+        let mut column_widths =
+            self.column_constraints(dir_contents_columns, visible_columns.clone());
 
-        let columns = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(column_widths)
-            .split(area);
+        // Right to left, columns fill from the right edge, with names aligned to their right
+        let layout = Layout::default().direction(Direction::Horizontal);
+        let (columns, alignment) = if self.theme.right_to_left {
+            column_widths.reverse();
+            let mut columns = layout
+                .flex(Flex::End)
+                .constraints(column_widths)
+                .split(area)
+                .to_vec();
+            columns.reverse();
+            (columns, Alignment::Right)
+        } else {
+            let columns = layout.constraints(column_widths).split(area).to_vec();
+            (columns, Alignment::Left)
+        };
 
         for (column_index, (column_area, column_contents)) in
-            columns.iter().zip(dir_contents_columns.iter()).enumerate()
+            visible_columns.zip(columns.iter().zip(dir_contents_columns.iter()))
         {
-            if column_index == self.cursor_column_index {
-                Paragraph::new(Text::from(get_formatted_paths(
-                    column_contents,
-                    Some(self.cursor_row_index),
-                )))
-                .left_aligned()
+            let cursor_row_index =
+                (column_index == self.cursor_column_index).then_some(self.cursor_row_index);
+            let lines = self.format_column(column_contents, cursor_row_index);
+
+            Paragraph::new(Text::from(lines))
+                .alignment(alignment)
                 .render(*column_area, buf);
-            } else {
-                Paragraph::new(Text::from(get_formatted_paths(column_contents, None)))
-                    .left_aligned()
-                    .render(*column_area, buf);
-            }
         }
     }
 }
 
+/// Formats each entry, with `markers_for` giving the marker (if any) it has in each gutter,
+/// `depth_of` how many levels it is nested below the listed directory, and `matched_in` which
+/// characters of its name to highlight
 pub fn get_formatted_paths(
     current_dir_contents: &[PathBuf],
     cursor_row_index: Option<usize>,
+    theme: &Theme,
+    extension_groups: &[ExtensionGroup],
+    markers_for: &dyn Fn(&Path) -> Vec<Option<&'static str>>,
+    depth_of: &dyn Fn(&Path) -> usize,
+    matched_in: &dyn Fn(&str) -> Vec<usize>,
 ) -> Vec<Line<'static>> {
-    if let Some(cursor_row_index) = cursor_row_index {
-        current_dir_contents
-            .iter()
-            .enumerate()
-            .map(|(row_index, entity)| {
-                format_path_with_cursor(entity, cursor_row_index == row_index)
-            })
-            .collect()
-    } else {
-        current_dir_contents
-            .iter()
-            .map(|entity| format_path(entity))
-            .collect()
+    current_dir_contents
+        .iter()
+        .enumerate()
+        .map(|(row_index, entity)| {
+            let with_cursor = cursor_row_index == Some(row_index);
+            let markers = markers_for(entity);
+            let line = match collapsed_group_for(entity, extension_groups) {
+                Some(group) => format_group_with_cursor(group, with_cursor, &markers, theme),
+                None => format_path_with_cursor(
+                    entity,
+                    depth_of(entity),
+                    with_cursor,
+                    &markers,
+                    matched_in,
+                    theme,
+                ),
+            };
+
+            match theme.zebra_style {
+                Some(zebra_style) if row_index % 2 == 1 && !with_cursor => {
+                    line.patch_style(zebra_style)
+                }
+                _ => line,
+            }
+        })
+        .collect()
+}
+
+/// The width of a column in terminal cells, measured the same way the names are rendered
+fn column_width(column: &[Row], theme: &Theme, extension_groups: &[ExtensionGroup]) -> u16 {
+    let widest_name = column
+        .iter()
+        .map(|row| match row {
+            Row::Entry(entity) => display_width(&display_name(entity, extension_groups)),
+            Row::Header(title) => display_width(title),
+        })
+        .max()
+        .unwrap_or(0);
+
+    (widest_name + theme.prefix_width() + 6) as u16
+}
+
+fn collapsed_group_for<'a>(
+    entity: &Path,
+    extension_groups: &'a [ExtensionGroup],
+) -> Option<&'a ExtensionGroup> {
+    extension_groups
+        .iter()
+        .find(|group| !group.expanded && group.representative() == entity)
+}
+
+/// The text shown for an entry, not including the cursor prefix
+fn display_name(entity: &Path, extension_groups: &[ExtensionGroup]) -> String {
+    match collapsed_group_for(entity, extension_groups) {
+        Some(group) => group.summary(),
+        None => entity
+            .file_name()
+            .and_then(|os_str| os_str.to_str())
+            .unwrap_or("<invalid utf-8>")
+            .to_string(),
     }
 }
 
-fn format_path(entity: &Path) -> Line<'static> {
-    format_path_with_cursor(entity, false)
+/// Assembles `[cursor][gutters…]name`, or `name[…gutters][cursor]` right to left, padding the
+/// cursor marker and every gutter to a fixed width so names line up whichever markers each row has
+fn compose_row(with_cursor: bool, markers: &[Option<&str>], name: &str, theme: &Theme) -> String {
+    let prefix = if with_cursor {
+        &theme.cursor_marker
+    } else {
+        &theme.indent
+    };
+    // Padded by cells rather than characters, as the marker may be wide
+    let prefix_padding = " ".repeat(theme.prefix_width().saturating_sub(display_width(prefix)));
+    let gutter_width = theme.gutter_width;
+
+    if theme.right_to_left {
+        let mut row = name.to_string();
+        for marker in markers.iter().rev() {
+            row.push_str(&format!("{:>gutter_width$}", marker.unwrap_or("")));
+        }
+        row.push_str(&prefix_padding);
+        row.push_str(prefix);
+        return row;
+    }
+
+    let mut row = format!("{prefix}{prefix_padding}");
+    for marker in markers {
+        row.push_str(&format!("{:<gutter_width$}", marker.unwrap_or("")));
+    }
+    row.push_str(name);
+    row
 }
 
-fn format_path_with_cursor(entity: &Path, with_cursor: bool) -> Line<'static> {
-    let prefix = if with_cursor { "> " } else { "  " };
+fn format_group_with_cursor(
+    group: &ExtensionGroup,
+    with_cursor: bool,
+    markers: &[Option<&str>],
+    theme: &Theme,
+) -> Line<'static> {
+    Line::from(compose_row(with_cursor, markers, &group.summary(), theme)).style(theme.file_style)
+}
 
+fn format_path_with_cursor(
+    entity: &Path,
+    depth: usize,
+    with_cursor: bool,
+    markers: &[Option<&str>],
+    matched_in: &dyn Fn(&str) -> Vec<usize>,
+    theme: &Theme,
+) -> Line<'static> {
     let name = entity
         .file_name()
         .and_then(|os_str| os_str.to_str())
         .unwrap_or("<invalid utf-8>");
+    let nesting = NESTING_INDENT.repeat(depth);
+    let padded_name = if theme.right_to_left {
+        format!("{name}{nesting}")
+    } else {
+        format!("{nesting}{name}")
+    };
+    let row = compose_row(with_cursor, markers, &padded_name, theme);
+    let style = theme.style_for(entity);
 
-    let text = format!("{prefix}{name}");
+    let matched = matched_in(name);
+    if matched.is_empty() {
+        return Line::from(row).style(style);
+    }
 
-    if entity.is_dir() {
-        Line::from(text).blue()
-    } else if entity.is_file() {
-        Line::from(text).yellow()
+    // The name is at the start of the row right to left, and at the end otherwise
+    let name_start = if theme.right_to_left {
+        0
     } else {
-        Line::from(text)
+        row.chars().count() - name.chars().count()
+    };
+    let mut spans: Vec<Span> = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (position, character) in row.chars().enumerate() {
+        let is_matched = position
+            .checked_sub(name_start)
+            .is_some_and(|name_position| matched.contains(&name_position));
+        if is_matched != run_matched && !run.is_empty() {
+            spans.push(highlighted_span(
+                std::mem::take(&mut run),
+                run_matched,
+                theme,
+            ));
+        }
+        run_matched = is_matched;
+        run.push(character);
+    }
+    spans.push(highlighted_span(run, run_matched, theme));
+
+    Line::from(spans).style(style)
+}
+
+fn highlighted_span(text: String, matched: bool, theme: &Theme) -> Span<'static> {
+    if matched {
+        Span::styled(text, theme.search_match_style)
+    } else {
+        Span::raw(text)
     }
 }
 
@@ -125,7 +815,7 @@ mod tests {
     use std::fs::{File, create_dir};
 
     use itertools::sorted;
-    use ratatui::style::Style;
+    use ratatui::style::{Style, Stylize};
     use tempdir::TempDir;
 
     use super::*;
@@ -133,7 +823,14 @@ mod tests {
     #[test]
     fn only_file_name_is_shown_not_full_path() {
         assert_eq!(
-            format_path_with_cursor(Path::new("/some/nested/file.txt"), false),
+            format_path_with_cursor(
+                Path::new("/some/nested/file.txt"),
+                0,
+                false,
+                &[],
+                &|_| Vec::new(),
+                &Theme::default()
+            ),
             Line::from("  file.txt")
         )
     }
@@ -141,22 +838,140 @@ mod tests {
     #[test]
     fn path_without_cursor_has_no_cursor_prefix() {
         assert_eq!(
-            format_path_with_cursor(Path::new("file.txt"), false),
+            format_path_with_cursor(
+                Path::new("file.txt"),
+                0,
+                false,
+                &[],
+                &|_| Vec::new(),
+                &Theme::default()
+            ),
             Line::from("  file.txt")
         )
     }
 
+    #[test]
+    fn search_matches_are_highlighted_in_the_name() {
+        let theme = Theme::default();
+        let line = format_path_with_cursor(
+            Path::new("file.txt"),
+            1,
+            true,
+            &[],
+            &|name| {
+                assert_eq!(name, "file.txt");
+                vec![0, 1, 5]
+            },
+            &theme,
+        );
+
+        assert_eq!(
+            line,
+            Line::from(vec![
+                Span::raw(">   "),
+                Span::styled("fi", theme.search_match_style),
+                Span::raw("le."),
+                Span::styled("t", theme.search_match_style),
+                Span::raw("xt"),
+            ])
+            .style(theme.style_for(Path::new("file.txt")))
+        );
+    }
+
     #[test]
     fn path_with_cursor_has_cursor_prefix() {
         assert_eq!(
-            format_path_with_cursor(Path::new("file.txt"), true),
+            format_path_with_cursor(
+                Path::new("file.txt"),
+                0,
+                true,
+                &[],
+                &|_| Vec::new(),
+                &Theme::default()
+            ),
             Line::from("> file.txt")
         )
     }
 
     #[test]
-    fn format_path_passes_with_cursor_as_false() {
-        assert_eq!(format_path(Path::new("file.txt")), Line::from("  file.txt"))
+    fn custom_cursor_marker_and_indent_are_used() {
+        let theme = Theme {
+            cursor_marker: "▶ ".to_string(),
+            indent: "· ".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            get_formatted_paths(
+                &[PathBuf::from("file_1.txt"), PathBuf::from("file_2.txt")],
+                Some(1),
+                &theme,
+                &[],
+                &|_| Vec::new(),
+                &|_| 0,
+                &|_| Vec::new(),
+            ),
+            [Line::from("· file_1.txt"), Line::from("▶ file_2.txt")]
+        )
+    }
+
+    #[test]
+    fn shorter_prefix_is_padded_to_keep_names_aligned() {
+        let theme = Theme {
+            cursor_marker: "->".to_string(),
+            indent: String::new(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            get_formatted_paths(
+                &[PathBuf::from("file_1.txt"), PathBuf::from("file_2.txt")],
+                Some(0),
+                &theme,
+                &[],
+                &|_| Vec::new(),
+                &|_| 0,
+                &|_| Vec::new(),
+            ),
+            [Line::from("->file_1.txt"), Line::from("  file_2.txt")]
+        )
+    }
+
+    #[test]
+    fn wide_cursor_marker_is_padded_by_its_width() {
+        let theme = Theme {
+            cursor_marker: "👉".to_string(),
+            indent: String::new(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            get_formatted_paths(
+                &[PathBuf::from("file_1.txt"), PathBuf::from("file_2.txt")],
+                Some(0),
+                &theme,
+                &[],
+                &|_| Vec::new(),
+                &|_| 0,
+                &|_| Vec::new(),
+            ),
+            [Line::from("👉file_1.txt"), Line::from("  file_2.txt")]
+        )
+    }
+
+    #[test]
+    fn format_path_without_cursor_is_indented() {
+        assert_eq!(
+            format_path_with_cursor(
+                Path::new("file.txt"),
+                0,
+                false,
+                &[],
+                &|_| Vec::new(),
+                &Theme::default()
+            ),
+            Line::from("  file.txt")
+        )
     }
 
     #[test]
@@ -164,7 +979,12 @@ mod tests {
         assert_eq!(
             get_formatted_paths(
                 &[PathBuf::from("file_1.txt"), PathBuf::from("file_2.txt")],
-                None
+                None,
+                &Theme::default(),
+                &[],
+                &|_| Vec::new(),
+                &|_| 0,
+                &|_| Vec::new(),
             ),
             [Line::from("  file_1.txt"), Line::from("  file_2.txt")]
         )
@@ -175,12 +995,299 @@ mod tests {
         assert_eq!(
             get_formatted_paths(
                 &[PathBuf::from("file_1.txt"), PathBuf::from("file_2.txt")],
-                Some(1)
+                Some(1),
+                &Theme::default(),
+                &[],
+                &|_| Vec::new(),
+                &|_| 0,
+                &|_| Vec::new(),
             ),
             [Line::from("  file_1.txt"), Line::from("> file_2.txt")]
         )
     }
 
+    #[test]
+    fn collapsed_group_is_formatted_as_summary() {
+        let extension_groups = [ExtensionGroup {
+            extension: "jpg".to_string(),
+            members: vec![PathBuf::from("a.jpg"), PathBuf::from("b.jpg")],
+            expanded: false,
+        }];
+
+        assert_eq!(
+            get_formatted_paths(
+                &[PathBuf::from("a.jpg"), PathBuf::from("c.txt")],
+                Some(0),
+                &Theme::default(),
+                &extension_groups,
+                &|_| Vec::new(),
+                &|_| 0,
+                &|_| Vec::new(),
+            ),
+            [
+                Line::from("> 📄 2 *.jpg files").yellow(),
+                Line::from("  c.txt")
+            ]
+        )
+    }
+
+    #[test]
+    fn column_width_counts_cells_not_bytes() {
+        let theme = Theme::default();
+        let ascii_width = column_width(&[Row::Entry(PathBuf::from("abcd.txt"))], &theme, &[]);
+        // Both names are 8 cells wide, but the second is 12 bytes long
+        let multibyte_width = column_width(&[Row::Entry(PathBuf::from("写真.txt"))], &theme, &[]);
+
+        assert_eq!(ascii_width, 16);
+        assert_eq!(multibyte_width, ascii_width);
+    }
+
+    #[test]
+    fn no_color_dir_line_has_no_style() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        let _nested_dir = create_dir(&nested_dir_path);
+
+        let lines = get_formatted_paths(
+            &[nested_dir_path],
+            Some(0),
+            &Theme::no_color(),
+            &[],
+            &|_| Vec::new(),
+            &|_| 0,
+            &|_| Vec::new(),
+        );
+
+        assert_eq!(lines, [Line::from("> nested_dir")]);
+        assert_eq!(lines[0].style, Style::new());
+    }
+
+    #[test]
+    fn right_to_left_fills_columns_from_the_right_edge() {
+        let directory_contents: Vec<PathBuf> = ["a", "b", "c", "d"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        let theme = Theme::default().with_right_to_left();
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 2));
+        DirectoryView::new(directory_contents, 0, 0, &theme).render(buf.area, &mut buf);
+
+        assert_eq!(
+            buf,
+            Buffer::with_lines(vec!["        c        a <", "        d        b  "])
+        );
+    }
+
+    #[test]
+    fn max_columns_caps_layout_and_scrolls_to_cursor() {
+        let directory_contents: Vec<PathBuf> = ["a", "b", "c", "d", "e", "f"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        let theme = Theme::default();
+
+        let directory_view =
+            DirectoryView::new(directory_contents.clone(), 0, 0, &theme).with_max_columns(Some(3));
+        assert_eq!(directory_view.visible_columns(6), 0..3);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 81, 1));
+        directory_view.render(buf.area, &mut buf);
+        assert_eq!(
+            buf,
+            Buffer::with_lines(vec![format!("{:<81}", "> a        b        c")])
+        );
+
+        // The remaining columns scroll into view as the cursor reaches them
+        let directory_view =
+            DirectoryView::new(directory_contents, 4, 0, &theme).with_max_columns(Some(3));
+        assert_eq!(directory_view.visible_columns(6), 2..5);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 81, 1));
+        directory_view.render(buf.area, &mut buf);
+        assert_eq!(
+            buf,
+            Buffer::with_lines(vec![format!("{:<81}", "  c        d      > e")])
+        );
+    }
+
+    #[test]
+    fn zebra_rows_are_striped_except_under_the_cursor() {
+        let theme = Theme::default().with_zebra_rows();
+        let zebra_style = theme.zebra_style.unwrap();
+
+        let lines = get_formatted_paths(
+            &[
+                PathBuf::from("a"),
+                PathBuf::from("b"),
+                PathBuf::from("c"),
+                PathBuf::from("d"),
+            ],
+            Some(3),
+            &theme,
+            &[],
+            &|_| Vec::new(),
+            &|_| 0,
+            &|_| Vec::new(),
+        );
+
+        assert_eq!(
+            lines,
+            [
+                Line::from("  a"),
+                Line::from("  b").style(zebra_style),
+                Line::from("  c"),
+                Line::from("> d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_theme_and_options_are_applied() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let directory_contents: Vec<PathBuf> = [".hidden", "b.txt", "a.txt", "c.md"]
+            .into_iter()
+            .map(|name| {
+                let file_path = tmp_dir.path().join(name);
+                File::create(&file_path).unwrap();
+                file_path
+            })
+            .collect();
+
+        let theme = Theme {
+            file_style: Style::new().green().italic(),
+            cursor_marker: "→ ".to_string(),
+            ..Default::default()
+        };
+        let directory_view = DirectoryView::new(directory_contents, 0, 1, &theme)
+            .with_hidden(false)
+            .with_filter(|entity| {
+                entity
+                    .extension()
+                    .is_some_and(|extension| extension == "txt")
+            })
+            .with_sort(|a, b| a.cmp(b))
+            .with_width_budget(8);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 2));
+        directory_view.render(buf.area, &mut buf);
+
+        let mut expected = Buffer::with_lines(vec!["  a.txt             ", "→ b.txt             "]);
+        expected.set_style(Rect::new(0, 0, 7, 2), Style::new().green().italic());
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn names_line_up_with_every_gutter_in_use() {
+        let directory_contents: Vec<PathBuf> = ["a.txt", "b.txt", "c", "d.txt"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let starred = HashSet::from([PathBuf::from("a.txt")]);
+        let dimmed = HashSet::from([PathBuf::from("c")]);
+        let marked = HashSet::from([PathBuf::from("d.txt")]);
+        let theme = Theme::no_color();
+        let directory_view = DirectoryView::new(directory_contents, 0, 3, &theme)
+            .with_starred(&starred)
+            .with_dimmed(&dimmed)
+            .with_marked(&marked);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 4));
+        directory_view.render(buf.area, &mut buf);
+
+        let rows: Vec<String> = (0..4)
+            .map(|y| {
+                (0..16)
+                    .map(|x| buf.cell((x, y)).unwrap().symbol())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                "  ★   a.txt     ",
+                "      b.txt     ",
+                "    ! c         ",
+                ">     d.txt     ",
+            ]
+        );
+
+        // Without anything to mark, the gutters make way for the names
+        let no_stars = HashSet::new();
+        let directory_view =
+            DirectoryView::new(vec![PathBuf::from("a.txt")], 0, 0, &theme).with_starred(&no_stars);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 1));
+        directory_view.render(buf.area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(vec!["> a.txt "]));
+    }
+
+    #[test]
+    fn mount_points_are_marked_with_a_disk() {
+        let mount_points = HashSet::from([PathBuf::from("usb")]);
+        let theme = Theme::no_color();
+        let directory_view = DirectoryView::new(
+            vec![PathBuf::from("home"), PathBuf::from("usb")],
+            0,
+            0,
+            &theme,
+        )
+        .with_mount_points(&mount_points);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+        directory_view.render(buf.area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(vec![">   home", "  ⛁ usb "]));
+    }
+
+    #[test]
+    fn scrolling_mode_shows_thumb_at_offset() {
+        let directory_contents: Vec<PathBuf> =
+            (0..20).map(|i| PathBuf::from(format!("{i:02}"))).collect();
+        let theme = Theme::default();
+        let directory_view =
+            DirectoryView::new(directory_contents, 0, 11, &theme).with_scrollbar(10);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 5));
+        directory_view.render(buf.area, &mut buf);
+
+        let rows: Vec<String> = (0..5)
+            .map(|y| (0..8).map(|x| buf.cell((x, y)).unwrap().symbol()).collect())
+            .collect();
+        // 10 rows scrolled out of a possible 15 puts the thumb two thirds of the way down
+        assert_eq!(
+            rows,
+            vec!["  10   ║", "> 11   ║", "  12   ║", "  13   █", "  14   ║"]
+        );
+    }
+
+    #[test]
+    fn width_adjustment_only_changes_its_column() {
+        let directory_contents: Vec<PathBuf> =
+            ["a", "b", "c"].into_iter().map(PathBuf::from).collect();
+        let dir_contents_columns: Vec<Vec<Row>> = directory_contents
+            .iter()
+            .map(|entity| vec![Row::Entry(entity.clone())])
+            .collect();
+        let theme = Theme::default();
+        let column_width_adjustments = HashMap::from([(1, 4)]);
+
+        let directory_view = DirectoryView::new(directory_contents.clone(), 1, 0, &theme);
+        assert_eq!(
+            directory_view.column_constraints(&dir_contents_columns, 0..3),
+            [Constraint::Length(9); 3]
+        );
+
+        let directory_view = DirectoryView::new(directory_contents, 1, 0, &theme)
+            .with_column_width_adjustments(&column_width_adjustments);
+        assert_eq!(
+            directory_view.column_constraints(&dir_contents_columns, 0..3),
+            [
+                Constraint::Length(9),
+                Constraint::Length(13),
+                Constraint::Length(9)
+            ]
+        );
+    }
+
     #[test]
     fn default_render_single_column() {
         // TODO: Make this test nicer
@@ -199,7 +1306,8 @@ mod tests {
         ))
         .collect();
 
-        let directory_view = DirectoryView::new(directory_contents, 0, 0);
+        let theme = Theme::default();
+        let directory_view = DirectoryView::new(directory_contents, 0, 0, &theme);
 
         let mut buf = Buffer::empty(Rect::new(0, 0, 81, 3));
 
@@ -237,7 +1345,8 @@ mod tests {
         ))
         .collect();
 
-        let directory_view = DirectoryView::new(directory_contents, 0, 0);
+        let theme = Theme::default();
+        let directory_view = DirectoryView::new(directory_contents, 0, 0, &theme);
 
         let mut buf = Buffer::empty(Rect::new(0, 0, 81, 2));
 
@@ -0,0 +1,124 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use crate::human_size::format_with_thousands_separator;
+
+/// A run of consecutive files sharing an extension, shown as a single summary row while collapsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionGroup {
+    pub extension: String,
+    pub members: Vec<PathBuf>,
+    pub expanded: bool,
+}
+
+impl ExtensionGroup {
+    /// The collapsed group is shown in place of its first member
+    pub fn representative(&self) -> &PathBuf {
+        &self.members[0]
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "📄 {} *.{} files",
+            format_with_thousands_separator(self.members.len()),
+            self.extension
+        )
+    }
+}
+
+fn extension_of(entity: &Path) -> Option<&str> {
+    if entity.is_dir() {
+        None
+    } else {
+        entity.extension()?.to_str()
+    }
+}
+
+/// Finds every run of at least `min_group_size` consecutive files with the same extension
+pub fn group_consecutive_extensions(
+    current_dir_contents: &[PathBuf],
+    min_group_size: usize,
+) -> Vec<ExtensionGroup> {
+    current_dir_contents
+        .chunk_by(|a, b| extension_of(a).is_some() && extension_of(a) == extension_of(b))
+        .filter(|run| run.len() >= min_group_size.max(2))
+        .filter_map(|run| {
+            Some(ExtensionGroup {
+                extension: extension_of(&run[0])?.to_string(),
+                members: run.to_vec(),
+                expanded: false,
+            })
+        })
+        .collect()
+}
+
+/// The rows to display, with every member but the first of each collapsed group removed
+pub fn collapse_groups(
+    current_dir_contents: &[PathBuf],
+    extension_groups: &[ExtensionGroup],
+) -> Vec<PathBuf> {
+    let hidden_members: HashSet<&PathBuf> = extension_groups
+        .iter()
+        .filter(|group| !group.expanded)
+        .flat_map(|group| &group.members[1..])
+        .collect();
+
+    current_dir_contents
+        .iter()
+        .filter(|entity| !hidden_members.contains(entity))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jpgs_between_other_files() -> Vec<PathBuf> {
+        let mut contents = vec![PathBuf::from("a.txt")];
+        contents.extend((0..12).map(|i| PathBuf::from(format!("img_{i:02}.jpg"))));
+        contents.push(PathBuf::from("notes.md"));
+        contents.push(PathBuf::from("z.txt"));
+        contents
+    }
+
+    #[test]
+    fn runs_of_same_extension_are_grouped() {
+        let contents = jpgs_between_other_files();
+
+        let groups = group_consecutive_extensions(&contents, 5);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].extension, "jpg");
+        assert_eq!(groups[0].members, contents[1..13]);
+        assert_eq!(groups[0].summary(), "📄 12 *.jpg files");
+
+        assert_eq!(
+            collapse_groups(&contents, &groups),
+            vec![
+                PathBuf::from("a.txt"),
+                PathBuf::from("img_00.jpg"),
+                PathBuf::from("notes.md"),
+                PathBuf::from("z.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn expanding_a_group_reveals_its_members() {
+        let contents = jpgs_between_other_files();
+
+        let mut groups = group_consecutive_extensions(&contents, 5);
+        groups[0].expanded = true;
+
+        assert_eq!(collapse_groups(&contents, &groups), contents);
+    }
+
+    #[test]
+    fn short_runs_are_not_grouped() {
+        let contents = jpgs_between_other_files();
+
+        assert_eq!(group_consecutive_extensions(&contents, 13), vec![]);
+    }
+}
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    symbols::border,
+    text::{Line, Text},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+/// Sidebar listing the favorite directories
+pub struct FavoritesView<'a> {
+    favorites: &'a [PathBuf],
+    cursor_index: Option<usize>,
+}
+
+impl<'a> FavoritesView<'a> {
+    /// The cursor is only shown while the sidebar has focus
+    pub fn new(favorites: &'a [PathBuf], cursor_index: Option<usize>) -> Self {
+        FavoritesView {
+            favorites,
+            cursor_index,
+        }
+    }
+}
+
+pub fn favorite_label(favorite: &Path) -> String {
+    favorite
+        .file_name()
+        .and_then(|os_str| os_str.to_str())
+        .unwrap_or_else(|| favorite.to_str().unwrap_or("<invalid utf-8>"))
+        .to_string()
+}
+
+impl Widget for &FavoritesView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from(" Favorites ".bold()).centered())
+            .borders(Borders::RIGHT)
+            .border_set(border::ROUNDED);
+
+        let lines: Vec<Line> = self
+            .favorites
+            .iter()
+            .enumerate()
+            .map(|(index, favorite)| {
+                let prefix = if self.cursor_index == Some(index) {
+                    "> "
+                } else {
+                    "  "
+                };
+                Line::from(format!("{prefix}{}", favorite_label(favorite))).blue()
+            })
+            .collect();
+
+        Paragraph::new(Text::from(lines))
+            .left_aligned()
+            .block(block)
+            .render(area, buf);
+    }
+}
@@ -1,76 +1,820 @@
 use std::{
     fs::File,
-    io::{BufRead, BufReader},
-    path::PathBuf,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
 };
 
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::Stylize,
-    symbols::border,
+    style::{Style, Stylize},
     text::{Line, Text},
-    widgets::{Block, Borders, Paragraph, Widget},
+    widgets::{Block, BorderType, Borders, Paragraph, Widget},
+};
+
+use itertools::sorted;
+
+use crate::{
+    components::{
+        archive::{archive_kind, get_archive_listing},
+        git_diff::diff_line_style,
+        media::{MediaKind, get_media_metadata, media_kind, needs_probe},
+        theme::Theme,
+    },
+    display_width::crop_to_window,
 };
 
 #[derive(Debug)]
 pub struct FileView {
     file_name: String,
     file_contents: Vec<String>,
+    borders: Borders,
+    border_type: BorderType,
+    border_style: Style,
+    floating: bool,
+    /// How many lines have been read, when there are more that could be
+    more_after: Option<usize>,
+    /// The styles of the lines a diff adds and removes, when the contents are one
+    diff_styles: Option<(Style, Style)>,
+    /// How many characters of each line are scrolled out of view to the left
+    horizontal_offset: usize,
 }
 
 impl FileView {
-    pub fn new(file_path: &PathBuf, column_height: usize) -> Self {
-        let file_contents = get_formatted_file_contents(file_path, column_height);
+    pub fn new(file_path: &Path, column_height: usize, limits: PreviewLimits) -> Self {
+        let file_contents = get_formatted_file_contents(file_path, 0, column_height, limits);
+        Self::with_contents(file_path, file_contents)
+    }
+
+    /// A view of `file_path` showing `file_contents` rather than reading the file itself
+    pub fn with_contents(file_path: &Path, file_contents: Vec<String>) -> Self {
         let file_name = file_path.file_name().unwrap().to_str().unwrap().to_string();
 
         FileView {
             file_name,
             file_contents,
+            borders: Borders::LEFT,
+            border_type: BorderType::Rounded,
+            border_style: Style::new(),
+            floating: false,
+            more_after: None,
+            diff_styles: None,
+            horizontal_offset: 0,
         }
     }
+
+    /// Colors the contents as a diff, with the theme's added and removed styles
+    pub fn with_diff_styling(mut self, theme: &Theme) -> Self {
+        self.diff_styles = Some((theme.diff_added_style, theme.diff_removed_style));
+        self
+    }
+
+    /// Says how many of the file's lines have been read so far, and how to read more
+    pub fn with_more_after(mut self, lines_loaded: usize) -> Self {
+        self.more_after = Some(lines_loaded);
+        self
+    }
+
+    /// Takes the border from the theme's preview border
+    pub fn with_theme(mut self, theme: &Theme) -> Self {
+        self.borders = theme.preview_borders;
+        self.border_type = theme.preview_border_type;
+        self.border_style = theme.preview_border_style;
+        self
+    }
+
+    /// Scrolls each line left, leaving out its first `horizontal_offset` characters
+    pub fn with_horizontal_offset(mut self, horizontal_offset: usize) -> Self {
+        self.horizontal_offset = horizontal_offset;
+        self
+    }
+
+    /// Draws a border all the way round, for when the view floats over the listing
+    pub fn bordered(mut self) -> Self {
+        self.floating = true;
+        self
+    }
+
+    /// The part of `area` inside the view's borders and titles, where its contents go
+    pub fn inner_area(&self, area: Rect) -> Rect {
+        self.block().inner(area)
+    }
+
+    /// The text seen when the view is drawn in `area`: the lines that fit, each cropped to the
+    /// window the horizontal offset leaves
+    pub fn visible_text(&self, area: Rect) -> String {
+        let inner_area = self.inner_area(area);
+        self.file_contents
+            .iter()
+            .take(inner_area.height as usize)
+            .map(|line| crop_to_window(line, self.horizontal_offset, inner_area.width as usize))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn block(&self) -> Block<'_> {
+        let file_name = Line::from(format!(" {} ", self.file_name).bold());
+        let file_block = Block::bordered()
+            .title(file_name.centered())
+            .borders(if self.floating {
+                Borders::ALL
+            } else {
+                self.borders
+            })
+            .border_type(self.border_type)
+            .border_style(self.border_style);
+        match self.more_after {
+            Some(lines_loaded) => file_block.title_bottom(
+                Line::from(format!(" {lines_loaded} lines loaded, m for more ")).right_aligned(),
+            ),
+            None => file_block,
+        }
+    }
+}
+
+/// How much of a file is checked for NUL bytes when deciding whether it is binary
+const BINARY_SNIFF_BYTES: u64 = 8192;
+const HEX_DUMP_BYTES_PER_LINE: usize = 16;
+/// Shown in place of a media file's metadata while `ffprobe` is still finding it
+const PROBING_MESSAGE: &str = "Probing…";
+/// Appended to lines cut short by `PreviewLimits::max_line_bytes`
+const TRUNCATED_LINE_MARKER: &str = "… (truncated)";
+
+/// Bounds on how much of a file is read for its preview, so a pathological file (such as one
+/// enormous line) can't exhaust memory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreviewLimits {
+    /// Longer lines are cut short, with a marker
+    pub max_line_bytes: usize,
+    /// Reading stops here even if there are fewer lines than asked for
+    pub max_total_bytes: u64,
 }
 
-pub fn get_formatted_file_contents(file_path: &PathBuf, column_height: usize) -> Vec<String> {
-    if let Ok(file) = File::open(file_path) {
-        let reader = BufReader::new(file);
-        let lines = reader.lines().take(column_height).collect();
-        if let Ok(lines) = lines {
-            lines
+impl Default for PreviewLimits {
+    fn default() -> Self {
+        PreviewLimits {
+            max_line_bytes: 4096,
+            max_total_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// What the preview of an entry shows, before it is rendered
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreviewContent {
+    Text(Vec<String>),
+    /// A hex dump of the start of the file
+    Binary(Vec<String>),
+    /// The names of a directory's entries, with a trailing `/` on subdirectories
+    Directory(Vec<String>),
+    /// A listing of an archive's members
+    Archive(Vec<String>),
+    /// The dimensions, duration and codecs of an image, audio or video file
+    Media(Vec<String>),
+    /// Why the entry couldn't be previewed
+    Unreadable(String),
+}
+
+impl PreviewContent {
+    pub fn into_lines(self) -> Vec<String> {
+        match self {
+            PreviewContent::Text(lines)
+            | PreviewContent::Binary(lines)
+            | PreviewContent::Directory(lines)
+            | PreviewContent::Archive(lines)
+            | PreviewContent::Media(lines) => lines,
+            PreviewContent::Unreadable(reason) => vec![reason],
+        }
+    }
+}
+
+/// The first `max_lines` lines of the preview of `path`, without needing a terminal to render into
+pub fn preview_lines(path: &Path, max_lines: usize) -> PreviewContent {
+    preview_lines_with_limits(path, max_lines, PreviewLimits::default())
+}
+
+/// Like `preview_lines`, reading no more of a text file than `limits` allows
+pub fn preview_lines_with_limits(
+    path: &Path,
+    max_lines: usize,
+    limits: PreviewLimits,
+) -> PreviewContent {
+    preview_lines_with_media(path, max_lines, limits, |path, kind| {
+        Some(get_media_metadata(path, kind))
+    })
+}
+
+/// Like `preview_lines_with_limits`, taking a media file's metadata from `media_metadata`, which
+/// gives `None` while it is still being found and `Some(None)` when it couldn't be
+pub fn preview_lines_with_media(
+    path: &Path,
+    max_lines: usize,
+    limits: PreviewLimits,
+    media_metadata: impl FnOnce(&Path, MediaKind) -> Option<Option<Vec<String>>>,
+) -> PreviewContent {
+    if path.is_dir() {
+        return match get_directory_listing(path, max_lines) {
+            Ok(listing) => PreviewContent::Directory(listing),
+            Err(_) => PreviewContent::Unreadable("Unable to read directory".to_string()),
+        };
+    }
+
+    if let Some(kind) = archive_kind(path) {
+        return match get_archive_listing(path, kind, max_lines) {
+            Ok(listing) => PreviewContent::Archive(listing),
+            Err(_) => PreviewContent::Unreadable("Unable to read archive".to_string()),
+        };
+    }
+
+    // Falls through to the hex dump when the file can't be probed
+    if let Some(kind) = media_kind(path) {
+        match media_metadata(path, kind) {
+            Some(Some(metadata)) => {
+                return PreviewContent::Media(metadata.into_iter().take(max_lines).collect());
+            }
+            None => return PreviewContent::Media(vec![PROBING_MESSAGE.to_string()]),
+            Some(None) => {}
+        }
+    }
+
+    match preview_file(path, max_lines, limits) {
+        Ok(preview_content) => preview_content,
+        Err(err) => PreviewContent::Unreadable(unreadable_reason(&err)),
+    }
+}
+
+fn preview_file(
+    path: &Path,
+    max_lines: usize,
+    limits: PreviewLimits,
+) -> io::Result<PreviewContent> {
+    let mut file = File::open(path)?;
+    let mut start = Vec::new();
+    file.by_ref()
+        .take(BINARY_SNIFF_BYTES)
+        .read_to_end(&mut start)?;
+
+    if start.contains(&0) {
+        return Ok(PreviewContent::Binary(hex_dump(&start, max_lines)));
+    }
+
+    let reader = BufReader::new(
+        io::Cursor::new(start)
+            .chain(file)
+            .take(limits.max_total_bytes),
+    );
+    Ok(PreviewContent::Text(read_bounded_lines(
+        reader,
+        max_lines,
+        limits.max_line_bytes,
+    )?))
+}
+
+/// Reads lines like `BufRead::lines`, but keeps only the first `max_line_bytes` of each line
+fn read_bounded_lines(
+    reader: impl BufRead,
+    max_lines: usize,
+    max_line_bytes: usize,
+) -> io::Result<Vec<String>> {
+    Ok(read_bounded_lines_counted(reader, max_lines, max_line_bytes, u64::MAX)?.0)
+}
+
+/// Like `read_bounded_lines`, also counting the bytes the lines took up, so reading can carry on
+/// from after them. No more lines are started once `max_total_bytes` have been read, so the last
+/// line is still read whole
+fn read_bounded_lines_counted(
+    mut reader: impl BufRead,
+    max_lines: usize,
+    max_line_bytes: usize,
+    max_total_bytes: u64,
+) -> io::Result<(Vec<String>, u64)> {
+    let mut lines = Vec::new();
+    let mut bytes_consumed = 0;
+    while lines.len() < max_lines && bytes_consumed < max_total_bytes {
+        let mut line = Vec::new();
+        // One byte over the cap shows whether the line carries on past it
+        let bytes_read = reader
+            .by_ref()
+            .take(max_line_bytes as u64 + 1)
+            .read_until(b'\n', &mut line)?;
+        if bytes_read == 0 {
+            break;
+        }
+        bytes_consumed += bytes_read as u64;
+        // Either the line cap or the end of the reader, which may be the total cap
+        let cut_short = line.last() != Some(&b'\n');
+
+        let truncated = line.last() != Some(&b'\n') && line.len() > max_line_bytes;
+        if truncated {
+            line.truncate(max_line_bytes);
+            bytes_consumed += reader.skip_until(b'\n')? as u64;
+        } else if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        }
+
+        let line = match String::from_utf8(line) {
+            Ok(line) => line,
+            // Either cap can fall part way through a character
+            Err(err) if cut_short && err.utf8_error().error_len().is_none() => {
+                let valid_up_to = err.utf8_error().valid_up_to();
+                let mut line = err.into_bytes();
+                line.truncate(valid_up_to);
+                String::from_utf8(line).map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?
+            }
+            Err(_) => return Err(io::Error::from(io::ErrorKind::InvalidData)),
+        };
+        lines.push(if truncated {
+            format!("{line}{TRUNCATED_LINE_MARKER}")
         } else {
-            vec!["Unable to read contents".to_string()]
+            line
+        });
+    }
+    Ok((lines, bytes_consumed))
+}
+
+/// The lines of a text file read so far, which are added to a chunk at a time rather than reading
+/// the whole file at once
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedText {
+    path: PathBuf,
+    lines: Vec<String>,
+    /// Where in the file the next chunk starts
+    offset: u64,
+    finished: bool,
+}
+
+impl LoadedText {
+    /// The first `chunk_lines` lines of `path`, or `None` unless it is previewed as text
+    pub fn load(path: &Path, chunk_lines: usize, limits: PreviewLimits) -> Option<Self> {
+        // Audio and video are left to the media preview, without waiting on `ffprobe` here
+        let media_metadata =
+            |path: &Path, kind| (!needs_probe(kind)).then(|| get_media_metadata(path, kind));
+        if !matches!(
+            preview_lines_with_media(path, 0, limits, media_metadata),
+            PreviewContent::Text(_)
+        ) {
+            return None;
         }
-    } else {
-        vec!["Unable to read file".to_string()]
+
+        let mut loaded_text = LoadedText {
+            path: path.to_path_buf(),
+            lines: Vec::new(),
+            offset: 0,
+            finished: false,
+        };
+        loaded_text.load_more(chunk_lines, limits).ok()?;
+        Some(loaded_text)
+    }
+
+    /// Appends the next `chunk_lines` lines, stopping at the end of the line that reaches
+    /// `limits.max_total_bytes`
+    pub fn load_more(&mut self, chunk_lines: usize, limits: PreviewLimits) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+
+        let mut file = File::open(&self.path)?;
+        let file_len = file.metadata()?.len();
+        file.seek(SeekFrom::Start(self.offset))?;
+        // Room to finish the last line started, short of skipping the rest of a huge one
+        let reader_limit = limits
+            .max_total_bytes
+            .saturating_add(limits.max_line_bytes as u64 + 1);
+        let reader = BufReader::new(file.take(reader_limit));
+        let (lines, bytes_consumed) = read_bounded_lines_counted(
+            reader,
+            chunk_lines,
+            limits.max_line_bytes,
+            limits.max_total_bytes,
+        )?;
+
+        self.lines.extend(lines);
+        self.offset += bytes_consumed;
+        self.finished = bytes_consumed == 0 || self.offset >= file_len;
+        Ok(())
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// The `height` lines read so far after the first `scroll_offset`
+    pub fn visible_lines(&self, scroll_offset: usize, height: usize) -> Vec<String> {
+        self.lines
+            .iter()
+            .skip(scroll_offset)
+            .take(height)
+            .cloned()
+            .collect()
+    }
+
+    /// Whether the whole file has been read
+    pub fn is_finished(&self) -> bool {
+        self.finished
     }
 }
 
+/// Explains why a file couldn't be previewed, so it's clear what to do about it
+fn unreadable_reason(err: &io::Error) -> String {
+    match err.kind() {
+        io::ErrorKind::NotFound => "File not found".to_string(),
+        io::ErrorKind::PermissionDenied => "Permission denied".to_string(),
+        io::ErrorKind::IsADirectory => "Is a directory".to_string(),
+        io::ErrorKind::BrokenPipe => "Broken pipe while reading".to_string(),
+        io::ErrorKind::InvalidData => "Not valid UTF-8 text".to_string(),
+        _ => format!("Unable to read file: {err}"),
+    }
+}
+
+/// The `column_height` lines of the preview of `file_path` after the first `scroll_offset`
+pub fn get_formatted_file_contents(
+    file_path: &Path,
+    scroll_offset: usize,
+    column_height: usize,
+    limits: PreviewLimits,
+) -> Vec<String> {
+    preview_lines_with_limits(file_path, scroll_offset + column_height, limits)
+        .into_lines()
+        .into_iter()
+        .skip(scroll_offset)
+        .collect()
+}
+
+/// Formats bytes like `xxd`, with offsets, hex values and the printable characters
+fn hex_dump(bytes: &[u8], max_lines: usize) -> Vec<String> {
+    bytes
+        .chunks(HEX_DUMP_BYTES_PER_LINE)
+        .take(max_lines)
+        .enumerate()
+        .map(|(line_index, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+            let printable: String = chunk
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!(
+                "{:08x}  {:<47}  {printable}",
+                line_index * HEX_DUMP_BYTES_PER_LINE,
+                hex.join(" ")
+            )
+        })
+        .collect()
+}
+
+/// Lists the names of a directory's entries, with a trailing `/` on subdirectories
+fn get_directory_listing(dir_path: &Path, column_height: usize) -> io::Result<Vec<String>> {
+    let entries = sorted(
+        std::fs::read_dir(dir_path)?
+            .filter_map(|maybe_dir_entry| Some(maybe_dir_entry.ok()?.path())),
+    );
+
+    Ok(entries
+        .take(column_height)
+        .map(|entry| {
+            let name = entry.file_name().unwrap_or_default().to_string_lossy();
+            if entry.is_dir() {
+                format!("{name}/")
+            } else {
+                name.to_string()
+            }
+        })
+        .collect())
+}
+
 impl Widget for &FileView {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let file_name = Line::from(format!(" {} ", self.file_name).bold());
-        let file_block = Block::bordered()
-            .title(file_name.centered())
-            .borders(Borders::LEFT)
-            .border_set(border::ROUNDED);
-
         let formatted_file_contents: Vec<Line> = self
             .file_contents
             .iter()
+            // Wide characters take up two cells, so cropping by character count could overflow
             .map(|line| {
-                // Some characters can be multiple bytes in length
-                // This will get the nth character, which is not neccesarily the nth bytes
-                let (max_showable_character_index, _) = line
-                    .char_indices()
-                    .nth(area.width as usize)
-                    .unwrap_or((line.len(), 'a'));
-                let cropped_line = &line[..max_showable_character_index];
-                Line::from(cropped_line)
+                let formatted_line = Line::from(crop_to_window(
+                    line,
+                    self.horizontal_offset,
+                    area.width as usize,
+                ));
+                match self.diff_styles {
+                    Some((added_style, removed_style)) => {
+                        formatted_line.style(diff_line_style(line, added_style, removed_style))
+                    }
+                    None => formatted_line,
+                }
             })
             .collect();
 
         Paragraph::new(Text::from(formatted_file_contents))
             .left_aligned()
-            .block(file_block)
+            .block(self.block())
             .render(area, buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs::create_dir;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn loaded_text_carries_on_from_where_it_stopped() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("notes.txt");
+        std::fs::write(
+            &file_path,
+            format!("one\r\n{}\nthree\nfour", "x".repeat(20)),
+        )
+        .unwrap();
+        let limits = PreviewLimits {
+            max_line_bytes: 8,
+            ..Default::default()
+        };
+
+        let mut loaded_text = LoadedText::load(&file_path, 2, limits).unwrap();
+        assert_eq!(
+            loaded_text.lines(),
+            [
+                "one".to_string(),
+                format!("xxxxxxxx{TRUNCATED_LINE_MARKER}")
+            ]
+        );
+        assert!(!loaded_text.is_finished());
+
+        loaded_text.load_more(2, limits).unwrap();
+        assert_eq!(&loaded_text.lines()[2..], ["three", "four"]);
+        assert!(loaded_text.is_finished());
+
+        // The byte cap ends a chunk after the line it falls in, rather than part way through
+        std::fs::write(&file_path, "one\ntwo\nthree\n").unwrap();
+        let limits = PreviewLimits {
+            max_total_bytes: 5,
+            ..Default::default()
+        };
+        let mut loaded_text = LoadedText::load(&file_path, 10, limits).unwrap();
+        assert_eq!(loaded_text.lines(), ["one", "two"]);
+        loaded_text.load_more(10, limits).unwrap();
+        assert_eq!(loaded_text.lines(), ["one", "two", "three"]);
+        assert!(loaded_text.is_finished());
+
+        // Only text is loaded this way
+        std::fs::write(&file_path, [0, 1, 2]).unwrap();
+        assert_eq!(LoadedText::load(&file_path, 2, limits), None);
+    }
+
+    #[test]
+    fn directory_is_previewed_as_listing() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        create_dir(tmp_dir.path().join("nested_dir")).unwrap();
+        File::create(tmp_dir.path().join("b.txt")).unwrap();
+        File::create(tmp_dir.path().join("a.txt")).unwrap();
+
+        assert_eq!(
+            get_formatted_file_contents(tmp_dir.path(), 0, 10, PreviewLimits::default()),
+            vec!["a.txt", "b.txt", "nested_dir/"]
+        );
+        assert_eq!(
+            get_formatted_file_contents(tmp_dir.path(), 0, 2, PreviewLimits::default()),
+            vec!["a.txt", "b.txt"]
+        );
+    }
+
+    #[test]
+    fn text_is_previewed_as_lines() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        std::fs::write(&file_path, "line 1\nline 2\nline 3\n").unwrap();
+
+        assert_eq!(
+            preview_lines(&file_path, 2),
+            PreviewContent::Text(vec!["line 1".to_string(), "line 2".to_string()])
+        );
+    }
+
+    #[test]
+    fn scrolled_preview_starts_after_the_offset() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        std::fs::write(&file_path, "line 1\nline 2\nline 3\nline 4\n").unwrap();
+        let limits = PreviewLimits::default();
+
+        assert_eq!(
+            get_formatted_file_contents(&file_path, 1, 2, limits),
+            vec!["line 2", "line 3"]
+        );
+        // Near the end the window holds only what is left
+        assert_eq!(
+            get_formatted_file_contents(&file_path, 3, 2, limits),
+            vec!["line 4"]
+        );
+        assert!(get_formatted_file_contents(&file_path, 4, 2, limits).is_empty());
+    }
+
+    #[test]
+    fn long_lines_are_truncated_with_a_marker() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        std::fs::write(&file_path, format!("{}\nshort\r\n", "a".repeat(10_000))).unwrap();
+
+        let limits = PreviewLimits {
+            max_line_bytes: 8,
+            ..PreviewLimits::default()
+        };
+        assert_eq!(
+            get_formatted_file_contents(&file_path, 0, 10, limits),
+            vec!["aaaaaaaa… (truncated)", "short"]
+        );
+    }
+
+    #[test]
+    fn reading_stops_at_the_total_cap() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        std::fs::write(&file_path, "line 1\nline 2\nline 3\n").unwrap();
+
+        let limits = PreviewLimits {
+            max_total_bytes: 10,
+            ..PreviewLimits::default()
+        };
+        assert_eq!(
+            get_formatted_file_contents(&file_path, 0, 10, limits),
+            vec!["line 1", "lin"]
+        );
+    }
+
+    #[test]
+    fn total_cap_inside_a_character_keeps_the_whole_characters() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        std::fs::write(&file_path, "line 1\ncafé\n").unwrap();
+
+        // Stops after the first of é's two bytes
+        let limits = PreviewLimits {
+            max_total_bytes: 11,
+            ..PreviewLimits::default()
+        };
+        assert_eq!(
+            get_formatted_file_contents(&file_path, 0, 10, limits),
+            vec!["line 1", "caf"]
+        );
+    }
+
+    #[test]
+    fn line_exactly_at_the_cap_is_kept_whole() {
+        let reader = io::Cursor::new("12345678\nnext");
+
+        assert_eq!(
+            read_bounded_lines(reader, 10, 8).unwrap(),
+            vec!["12345678", "next"]
+        );
+    }
+
+    #[test]
+    fn binary_is_previewed_as_hex_dump() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.bin");
+        let mut contents = b"\x7fELF\0\x01".to_vec();
+        contents.extend(b"abcdefghijklmnopqrstuvwxyz");
+        std::fs::write(&file_path, contents).unwrap();
+
+        assert_eq!(
+            preview_lines(&file_path, 1),
+            PreviewContent::Binary(vec![
+                "00000000  7f 45 4c 46 00 01 61 62 63 64 65 66 67 68 69 6a  .ELF..abcdefghij"
+                    .to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn image_is_previewed_as_its_dimensions() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("pixel.png");
+        let mut contents = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        contents.extend(3u32.to_be_bytes());
+        contents.extend(2u32.to_be_bytes());
+        contents.extend(b"\x08\x06\0\0\0");
+        std::fs::write(&file_path, contents).unwrap();
+
+        assert_eq!(
+            preview_lines(&file_path, 10),
+            PreviewContent::Media(vec![
+                "Format: PNG".to_string(),
+                "Dimensions: 3 × 2".to_string()
+            ])
+        );
+
+        // Anything that doesn't parse is shown as bytes instead
+        let broken_path = tmp_dir.path().join("broken.png");
+        std::fs::write(&broken_path, b"\0\x01").unwrap();
+        assert!(matches!(
+            preview_lines(&broken_path, 10),
+            PreviewContent::Binary(_)
+        ));
+    }
+
+    #[test]
+    fn directory_and_missing_paths_are_dispatched() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        create_dir(tmp_dir.path().join("nested_dir")).unwrap();
+
+        assert_eq!(
+            preview_lines(tmp_dir.path(), 10),
+            PreviewContent::Directory(vec!["nested_dir/".to_string()])
+        );
+        assert_eq!(
+            preview_lines(&tmp_dir.path().join("missing.txt"), 10),
+            PreviewContent::Unreadable("File not found".to_string())
+        );
+    }
+
+    #[test]
+    fn directory_opened_as_a_file_is_explained() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+
+        assert_eq!(
+            preview_file(tmp_dir.path(), 10, PreviewLimits::default())
+                .map_err(|err| unreadable_reason(&err))
+                .unwrap_err(),
+            "Is a directory"
+        );
+    }
+
+    #[test]
+    fn error_kinds_have_specific_reasons() {
+        assert_eq!(
+            unreadable_reason(&io::Error::from(io::ErrorKind::PermissionDenied)),
+            "Permission denied"
+        );
+        assert_eq!(
+            unreadable_reason(&io::Error::from(io::ErrorKind::BrokenPipe)),
+            "Broken pipe while reading"
+        );
+        assert_eq!(
+            unreadable_reason(&io::Error::other("disk on fire")),
+            "Unable to read file: disk on fire"
+        );
+    }
+
+    #[test]
+    fn visible_text_is_what_fits_inside_the_border() {
+        let file_contents: Vec<String> = (0..10).map(|i| format!("line {i}")).collect();
+        let file_view = FileView::with_contents(Path::new("notes.txt"), file_contents);
+
+        // The title takes the top row and the border the left column
+        assert_eq!(
+            file_view.visible_text(Rect::new(0, 0, 20, 3)),
+            "line 0\nline 1"
+        );
+        let file_view = file_view.with_horizontal_offset(2);
+        assert_eq!(file_view.visible_text(Rect::new(0, 0, 4, 2)), "ne ");
+    }
+
+    #[test]
+    fn visible_text_stops_at_end_of_contents() {
+        let file_contents: Vec<String> = (0..3).map(|i| format!("line {i}")).collect();
+        let file_view = FileView::with_contents(Path::new("notes.txt"), file_contents);
+
+        assert_eq!(
+            file_view.visible_text(Rect::new(0, 0, 20, 10)),
+            "line 0\nline 1\nline 2"
+        );
+    }
+
+    #[test]
+    fn preview_border_comes_from_the_theme() {
+        let theme = Theme::default().with_preview_border(
+            Borders::ALL,
+            BorderType::Double,
+            Style::new().red(),
+        );
+        let file_view =
+            FileView::with_contents(Path::new("a.txt"), vec!["hi".to_string()]).with_theme(&theme);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 9, 3));
+        file_view.render(buf.area, &mut buf);
+
+        let corner = buf.cell((0, 0)).unwrap();
+        assert_eq!(corner.symbol(), "╔");
+        assert_eq!(corner.fg, ratatui::style::Color::Red);
+        assert_eq!(buf.cell((0, 1)).unwrap().symbol(), "║");
+        assert_eq!(buf.cell((1, 1)).unwrap().symbol(), "h");
+
+        // Without any border the contents start in the first column
+        let theme = theme.with_preview_border(Borders::NONE, BorderType::Plain, Style::new());
+        let file_view =
+            FileView::with_contents(Path::new("a.txt"), vec!["hi".to_string()]).with_theme(&theme);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 9, 3));
+        file_view.render(buf.area, &mut buf);
+        assert_eq!(buf.cell((0, 1)).unwrap().symbol(), "h");
+    }
+}
@@ -0,0 +1,69 @@
+use std::{path::Path, process::Command};
+
+use ratatui::style::Style;
+
+/// The changes to `file_path` not yet staged, as `git diff` shows them, or `None` for files with
+/// none, untracked files, files outside a repository, or when git isn't installed
+pub fn unstaged_diff(file_path: &Path) -> Option<Vec<String>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(file_path.parent()?)
+        .args(["diff", "--no-color", "--no-ext-diff", "--"])
+        .arg(file_path.file_name()?)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let diff: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    (!diff.is_empty()).then_some(diff)
+}
+
+/// The style of a line of a diff: added and removed lines in their own styles, but not the
+/// `+++` and `---` lines naming the files
+pub fn diff_line_style(line: &str, added_style: Style, removed_style: Style) -> Style {
+    if line.starts_with('+') && !line.starts_with("+++") {
+        added_style
+    } else if line.starts_with('-') && !line.starts_with("---") {
+        removed_style
+    } else {
+        Style::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Stylize;
+
+    use super::*;
+
+    #[test]
+    fn only_changed_lines_are_colored() {
+        let (added, removed) = (Style::new().green(), Style::new().red());
+
+        assert_eq!(diff_line_style("+added", added, removed), added);
+        assert_eq!(diff_line_style("-removed", added, removed), removed);
+        assert_eq!(
+            diff_line_style("+++ b/notes.txt", added, removed),
+            Style::new()
+        );
+        assert_eq!(
+            diff_line_style("--- a/notes.txt", added, removed),
+            Style::new()
+        );
+        assert_eq!(diff_line_style(" context", added, removed), Style::new());
+    }
+
+    #[test]
+    fn files_outside_a_repository_have_no_diff() {
+        let tmp_dir = tempdir::TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("notes.txt");
+        std::fs::write(&file_path, "notes").unwrap();
+
+        assert_eq!(unstaged_diff(&file_path), None);
+    }
+}
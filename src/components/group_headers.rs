@@ -0,0 +1,64 @@
+use std::path::{Path, PathBuf};
+
+/// A label drawn on a row of its own above the first entry of a group, which the cursor skips
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupHeader {
+    pub first: PathBuf,
+    pub label: String,
+}
+
+impl GroupHeader {
+    pub fn title(&self) -> String {
+        format!("— {} —", self.label)
+    }
+}
+
+/// A header above every run of consecutive entries that `label_of` puts in the same group
+pub fn group_headers(
+    current_dir_contents: &[PathBuf],
+    label_of: impl Fn(&Path) -> Option<&'static str>,
+) -> Vec<GroupHeader> {
+    let mut group_headers = Vec::new();
+    let mut previous_label = None;
+    for entity in current_dir_contents {
+        let label = label_of(entity);
+        if let Some(label) = label
+            && previous_label != Some(label)
+        {
+            group_headers.push(GroupHeader {
+                first: entity.clone(),
+                label: label.to_string(),
+            });
+        }
+        previous_label = label;
+    }
+    group_headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_header_starts_each_run_of_labels() {
+        let contents = ["a.png", "b.png", "c.mp3", "d.txt", "e.txt"].map(PathBuf::from);
+
+        let headers = group_headers(&contents, |entity| match entity.extension()?.to_str()? {
+            "png" => Some("Images"),
+            "mp3" => Some("Audio"),
+            _ => Some("Other files"),
+        });
+
+        assert_eq!(
+            headers
+                .iter()
+                .map(|header| (header.first.to_str().unwrap(), header.title()))
+                .collect::<Vec<_>>(),
+            [
+                ("a.png", "— Images —".to_string()),
+                ("c.mp3", "— Audio —".to_string()),
+                ("d.txt", "— Other files —".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    symbols::border,
+    text::{Line, Text},
+    widgets::{Block, Paragraph, Widget},
+};
+
+/// Overlay listing every key binding, scrolled so `scroll_offset` lines are hidden above it
+pub struct HelpView<'a> {
+    lines: &'a [String],
+    scroll_offset: usize,
+}
+
+impl<'a> HelpView<'a> {
+    pub fn new(lines: &'a [String], scroll_offset: usize) -> Self {
+        HelpView {
+            lines,
+            scroll_offset,
+        }
+    }
+}
+
+impl Widget for &HelpView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from(" Help ".bold()).centered())
+            .border_set(border::ROUNDED);
+
+        let lines: Vec<Line> = self
+            .lines
+            .iter()
+            .skip(self.scroll_offset)
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+
+        Paragraph::new(Text::from(lines))
+            .left_aligned()
+            .block(block)
+            .render(area, buf);
+    }
+}
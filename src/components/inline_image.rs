@@ -0,0 +1,207 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use ratatui::layout::Rect;
+
+use crate::components::media::{MediaKind, image_dimensions, media_kind};
+
+/// Images bigger than this are previewed as their dimensions, rather than sent to the terminal
+pub const MAX_INLINE_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// How much of the encoded image goes in each escape sequence, the most Kitty accepts at once
+const KITTY_CHUNK_BYTES: usize = 4096;
+
+/// The escape sequences some terminals understand for drawing an image file at full quality
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageProtocol {
+    Kitty,
+    Iterm2,
+}
+
+impl ImageProtocol {
+    /// The protocol of the terminal the app is running in, if it has one
+    pub fn from_env() -> Option<Self> {
+        Self::detect(|name| env::var(name).ok())
+    }
+
+    /// The protocol the terminal described by the environment variables `var` looks up supports.
+    /// None inside tmux or screen, which don't pass the sequences through
+    pub fn detect(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let term = var("TERM").unwrap_or_default();
+        let term_program = var("TERM_PROGRAM").unwrap_or_default();
+        if var("TMUX").is_some() || term.starts_with("screen") || term.starts_with("tmux") {
+            None
+        } else if var("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || term_program == "ghostty"
+        {
+            Some(ImageProtocol::Kitty)
+        } else if term_program == "iTerm.app"
+            || term_program == "WezTerm"
+            || var("LC_TERMINAL").as_deref() == Some("iTerm2")
+        {
+            Some(ImageProtocol::Iterm2)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the terminal can draw `file_path` itself: Kitty only takes PNGs as they are
+    pub fn supports(self, file_path: &Path) -> bool {
+        match self {
+            ImageProtocol::Kitty => file_path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("png")),
+            ImageProtocol::Iterm2 => media_kind(file_path) == Some(MediaKind::Image),
+        }
+    }
+
+    /// Draws the image file `image` as large as fits in `area`, keeping its shape, starting at
+    /// the cursor
+    pub fn escape_sequence(self, image: &[u8], area: Rect) -> String {
+        let (columns, rows) = fitted_size(image_dimensions(image), area);
+        let encoded = STANDARD.encode(image);
+        match self {
+            ImageProtocol::Kitty => {
+                let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK_BYTES).collect();
+                chunks
+                    .iter()
+                    .enumerate()
+                    .map(|(index, chunk)| {
+                        let more = u8::from(index + 1 < chunks.len());
+                        // Quiet, as replies would arrive as key presses
+                        let control = if index == 0 {
+                            format!("a=T,f=100,q=2,c={columns},r={rows},m={more}")
+                        } else {
+                            format!("m={more}")
+                        };
+                        format!("\x1b_G{control};{}\x1b\\", String::from_utf8_lossy(chunk))
+                    })
+                    .collect()
+            }
+            ImageProtocol::Iterm2 => format!(
+                "\x1b]1337;File=inline=1;size={};width={columns};height={rows};preserveAspectRatio=1:{encoded}\x07",
+                image.len()
+            ),
+        }
+    }
+
+    /// Removes the images drawn before, which Kitty keeps on a layer of their own rather than in
+    /// the cells ratatui redraws
+    pub fn clear_sequence(self) -> &'static str {
+        match self {
+            ImageProtocol::Kitty => "\x1b_Ga=d,q=2\x1b\\",
+            ImageProtocol::Iterm2 => "",
+        }
+    }
+}
+
+/// An image file drawn by the terminal over the preview
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImagePlacement {
+    pub path: PathBuf,
+    pub area: Rect,
+}
+
+/// The columns and rows an image of the given dimensions fills within `area`, taking cells to be
+/// twice as tall as they are wide. The whole area when the dimensions aren't known
+fn fitted_size(dimensions: Option<(&'static str, u32, u32)>, area: Rect) -> (u16, u16) {
+    let Some((_, width, height)) = dimensions.filter(|&(_, width, height)| width > 0 && height > 0)
+    else {
+        return (area.width, area.height);
+    };
+    let (width, height) = (u64::from(width), u64::from(height));
+
+    let columns_at_full_height = u64::from(area.height) * 2 * width / height;
+    if columns_at_full_height <= u64::from(area.width) {
+        (columns_at_full_height.max(1) as u16, area.height)
+    } else {
+        let rows = u64::from(area.width) * height / (2 * width);
+        (area.width, rows.max(1) as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn detect_in(vars: &[(&str, &str)]) -> Option<ImageProtocol> {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        ImageProtocol::detect(|name| vars.get(name).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn protocol_is_detected_from_the_terminal_environment() {
+        assert_eq!(
+            detect_in(&[("TERM", "xterm-kitty")]),
+            Some(ImageProtocol::Kitty)
+        );
+        assert_eq!(
+            detect_in(&[("TERM", "xterm-256color"), ("KITTY_WINDOW_ID", "1")]),
+            Some(ImageProtocol::Kitty)
+        );
+        assert_eq!(
+            detect_in(&[("TERM", "xterm-256color"), ("TERM_PROGRAM", "iTerm.app")]),
+            Some(ImageProtocol::Iterm2)
+        );
+        assert_eq!(
+            detect_in(&[("TERM", "xterm-256color"), ("LC_TERMINAL", "iTerm2")]),
+            Some(ImageProtocol::Iterm2)
+        );
+        assert_eq!(
+            detect_in(&[("TERM", "xterm-256color"), ("TERM_PROGRAM", "WezTerm")]),
+            Some(ImageProtocol::Iterm2)
+        );
+
+        // Plain terminals and multiplexers get the usual preview
+        assert_eq!(detect_in(&[("TERM", "xterm-256color")]), None);
+        assert_eq!(detect_in(&[("TERM", "linux")]), None);
+        assert_eq!(detect_in(&[]), None);
+        assert_eq!(
+            detect_in(&[("TERM", "tmux-256color"), ("KITTY_WINDOW_ID", "1")]),
+            None
+        );
+        assert_eq!(
+            detect_in(&[
+                ("TERM", "xterm-kitty"),
+                ("TMUX", "/tmp/tmux-1000/default,1,0")
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn kitty_only_draws_pngs() {
+        assert!(ImageProtocol::Kitty.supports(Path::new("photo.PNG")));
+        assert!(!ImageProtocol::Kitty.supports(Path::new("photo.jpg")));
+        assert!(ImageProtocol::Iterm2.supports(Path::new("photo.jpg")));
+        assert!(!ImageProtocol::Iterm2.supports(Path::new("notes.txt")));
+    }
+
+    #[test]
+    fn kitty_image_is_sent_in_chunks() {
+        let image = vec![0; KITTY_CHUNK_BYTES];
+        let sequence = ImageProtocol::Kitty.escape_sequence(&image, Rect::new(0, 0, 40, 20));
+
+        let chunks: Vec<&str> = sequence.split_terminator("\x1b\\").collect();
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].starts_with("\x1b_Ga=T,f=100,q=2,c=40,r=20,m=1;"));
+        assert!(chunks[1].starts_with("\x1b_Gm=0;"));
+    }
+
+    #[test]
+    fn image_keeps_its_shape_within_the_area() {
+        let area = Rect::new(0, 0, 40, 20);
+
+        // Wide images fill the width, tall ones the height
+        assert_eq!(fitted_size(Some(("PNG", 800, 200)), area), (40, 5));
+        assert_eq!(fitted_size(Some(("PNG", 100, 400)), area), (10, 20));
+        assert_eq!(fitted_size(None, area), (40, 20));
+    }
+}
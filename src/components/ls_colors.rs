@@ -0,0 +1,171 @@
+use std::{collections::HashMap, path::Path};
+
+use ratatui::style::{Color, Modifier, Style};
+
+/// Entry styles parsed from an `LS_COLORS` string, as used by `ls` and `dircolors`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LsColors {
+    type_styles: HashMap<String, Style>,
+    extension_styles: Vec<(String, Style)>,
+}
+
+impl LsColors {
+    pub fn from_env() -> Option<Self> {
+        let ls_colors = std::env::var("LS_COLORS").ok()?;
+        if ls_colors.is_empty() {
+            None
+        } else {
+            Some(Self::parse(&ls_colors))
+        }
+    }
+
+    pub fn parse(ls_colors: &str) -> Self {
+        let mut parsed = LsColors::default();
+
+        for (key, codes) in ls_colors
+            .split(':')
+            .filter_map(|assignment| assignment.split_once('='))
+        {
+            let style = parse_sgr_codes(codes);
+            if let Some(extension) = key.strip_prefix('*') {
+                parsed
+                    .extension_styles
+                    .push((extension.to_lowercase(), style));
+            } else {
+                parsed.type_styles.insert(key.to_string(), style);
+            }
+        }
+
+        parsed
+    }
+
+    /// The style `ls` would use for this entry, if `LS_COLORS` configures one
+    pub fn style_for(&self, entity: &Path) -> Option<Style> {
+        if entity.is_symlink()
+            && let Some(style) = self.type_styles.get("ln")
+        {
+            return Some(*style);
+        }
+
+        if entity.is_dir() {
+            return self.type_styles.get("di").copied();
+        }
+
+        let name = entity.file_name()?.to_str()?.to_lowercase();
+        self.extension_styles
+            .iter()
+            // Later entries take precedence, as they do in `ls`
+            .rev()
+            .find(|(extension, _)| name.ends_with(extension))
+            .map(|(_, style)| *style)
+            .or_else(|| {
+                if entity.is_file() {
+                    self.type_styles.get("fi").copied()
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+/// Converts a `;` separated list of SGR codes (e.g. `01;31`) into a style
+fn parse_sgr_codes(codes: &str) -> Style {
+    let mut style = Style::new();
+    let mut codes = codes.split(';').filter_map(|code| code.parse::<u8>().ok());
+
+    while let Some(code) = codes.next() {
+        style = match code {
+            0 => Style::new(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            30..=37 => style.fg(Color::Indexed(code - 30)),
+            90..=97 => style.fg(Color::Indexed(code - 90 + 8)),
+            40..=47 => style.bg(Color::Indexed(code - 40)),
+            100..=107 => style.bg(Color::Indexed(code - 100 + 8)),
+            38 | 48 => {
+                let color = match codes.next() {
+                    Some(5) => codes.next().map(Color::Indexed),
+                    Some(2) => match (codes.next(), codes.next(), codes.next()) {
+                        (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match (code, color) {
+                    (38, Some(color)) => style.fg(color),
+                    (48, Some(color)) => style.bg(color),
+                    _ => style,
+                }
+            }
+            _ => style,
+        };
+    }
+
+    style
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{File, create_dir};
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn sgr_codes_are_converted_to_styles() {
+        assert_eq!(
+            parse_sgr_codes("01;31"),
+            Style::new()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Indexed(1))
+        );
+        assert_eq!(
+            parse_sgr_codes("38;5;208;48;2;1;2;3"),
+            Style::new().fg(Color::Indexed(208)).bg(Color::Rgb(1, 2, 3))
+        );
+    }
+
+    #[test]
+    fn tar_file_gets_configured_extension_style() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let tar_path = tmp_dir.path().join("archive.tar");
+        let _tar_file = File::create(&tar_path).unwrap();
+        let txt_path = tmp_dir.path().join("notes.txt");
+        let _txt_file = File::create(&txt_path).unwrap();
+
+        let ls_colors = LsColors::parse("di=01;34:fi=00:*.tar=01;31");
+
+        assert_eq!(
+            ls_colors.style_for(&tar_path),
+            Some(
+                Style::new()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(Color::Indexed(1))
+            )
+        );
+        assert_eq!(ls_colors.style_for(&txt_path), Some(Style::new()));
+    }
+
+    #[test]
+    fn dir_gets_configured_type_style() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir.tar");
+        let _nested_dir = create_dir(&nested_dir_path);
+
+        let ls_colors = LsColors::parse("di=01;34:*.tar=01;31");
+
+        assert_eq!(
+            ls_colors.style_for(&nested_dir_path),
+            Some(
+                Style::new()
+                    .add_modifier(Modifier::BOLD)
+                    .fg(Color::Indexed(4))
+            )
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::SystemTime,
+};
+
+/// How much of an image is searched for its dimensions, which JPEGs can put after other metadata
+const IMAGE_HEADER_BYTES: u64 = 64 * 1024;
+
+/// Which version of a file some metadata was found for
+pub type FileVersion = (PathBuf, u64, Option<SystemTime>);
+
+type ProbeResult = (FileVersion, Option<Vec<String>>);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaKind {
+    Image,
+    Audio,
+    Video,
+}
+
+/// Detects media by its extension, as the formats are too varied to sniff reliably
+pub fn media_kind(file_path: &Path) -> Option<MediaKind> {
+    let extension = file_path.extension()?.to_str()?.to_lowercase();
+
+    match extension.as_str() {
+        "png" | "gif" | "jpg" | "jpeg" | "bmp" => Some(MediaKind::Image),
+        "mp3" | "flac" | "wav" | "ogg" | "m4a" | "aac" | "opus" => Some(MediaKind::Audio),
+        "mp4" | "mkv" | "webm" | "mov" | "avi" => Some(MediaKind::Video),
+        _ => None,
+    }
+}
+
+/// `Label: value` lines describing the file, or `None` when it couldn't be probed
+///
+/// Audio and video are probed with `ffprobe`, which can take a while, so the app leaves those to
+/// a `MediaProbe` rather than calling this while drawing
+pub fn get_media_metadata(file_path: &Path, kind: MediaKind) -> Option<Vec<String>> {
+    match kind {
+        MediaKind::Image => image_metadata(file_path).ok().flatten(),
+        MediaKind::Audio | MediaKind::Video => ffprobe_metadata(file_path),
+    }
+}
+
+/// Whether finding the metadata of a `kind` file means running `ffprobe`
+pub fn needs_probe(kind: MediaKind) -> bool {
+    matches!(kind, MediaKind::Audio | MediaKind::Video)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProbeState {
+    Pending,
+    Ready(Option<Vec<String>>),
+}
+
+/// Runs `ffprobe` on a background thread so that the UI doesn't wait on it, keeping what it said
+/// about the most recently requested file until that file changes
+#[derive(Debug)]
+pub struct MediaProbe {
+    entry: Option<(FileVersion, ProbeState)>,
+    sender: Sender<ProbeResult>,
+    receiver: Receiver<ProbeResult>,
+}
+
+impl Default for MediaProbe {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        MediaProbe {
+            entry: None,
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl MediaProbe {
+    /// Starts probing `file_path`, unless this version of it is already probed or being probed
+    pub fn request(&mut self, file_path: &Path) {
+        let version = file_version(file_path);
+        if self
+            .entry
+            .as_ref()
+            .is_some_and(|(probed, _)| *probed == version)
+        {
+            return;
+        }
+
+        self.entry = Some((version.clone(), ProbeState::Pending));
+
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let metadata = ffprobe_metadata(&version.0);
+            // The receiver only goes away when the app is closing
+            let _ = sender.send((version, metadata));
+        });
+    }
+
+    /// Stores what a probe found, ignoring it if it is for a file that is no longer wanted
+    pub fn deliver(&mut self, version: FileVersion, metadata: Option<Vec<String>>) {
+        if let Some((probed, state)) = &mut self.entry
+            && *probed == version
+        {
+            *state = ProbeState::Ready(metadata);
+        }
+    }
+
+    /// Collects any probes that have finished
+    pub fn poll(&mut self) {
+        while let Ok((version, metadata)) = self.receiver.try_recv() {
+            self.deliver(version, metadata);
+        }
+    }
+
+    pub fn state_for(&self, file_path: &Path) -> Option<&ProbeState> {
+        match &self.entry {
+            Some(((path, _, _), state)) if path == file_path => Some(state),
+            _ => None,
+        }
+    }
+}
+
+/// The version of `file_path` on disk now, told apart by its size and modification time
+pub fn file_version(file_path: &Path) -> FileVersion {
+    let metadata = fs::metadata(file_path).ok();
+    (
+        file_path.to_path_buf(),
+        metadata.as_ref().map_or(0, |metadata| metadata.len()),
+        metadata.and_then(|metadata| metadata.modified().ok()),
+    )
+}
+
+fn image_metadata(file_path: &Path) -> io::Result<Option<Vec<String>>> {
+    let mut header = Vec::new();
+    File::open(file_path)?
+        .take(IMAGE_HEADER_BYTES)
+        .read_to_end(&mut header)?;
+
+    Ok(image_dimensions(&header).map(|(format, width, height)| {
+        vec![
+            format!("Format: {format}"),
+            format!("Dimensions: {width} × {height}"),
+        ]
+    }))
+}
+
+/// The format, width and height read from the start of a PNG, GIF, BMP or JPEG
+pub fn image_dimensions(header: &[u8]) -> Option<(&'static str, u32, u32)> {
+    let u16_le = |at: usize| Some(u16::from_le_bytes(header.get(at..at + 2)?.try_into().ok()?));
+    let u32_be = |at: usize| Some(u32::from_be_bytes(header.get(at..at + 4)?.try_into().ok()?));
+    let i32_le = |at: usize| Some(i32::from_le_bytes(header.get(at..at + 4)?.try_into().ok()?));
+
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") && header.get(12..16) == Some(b"IHDR") {
+        Some(("PNG", u32_be(16)?, u32_be(20)?))
+    } else if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        Some(("GIF", u16_le(6)?.into(), u16_le(8)?.into()))
+    } else if header.starts_with(b"BM") {
+        // Negative heights mark images stored top to bottom
+        Some((
+            "BMP",
+            i32_le(18)?.unsigned_abs(),
+            i32_le(22)?.unsigned_abs(),
+        ))
+    } else if header.starts_with(b"\xff\xd8") {
+        jpeg_dimensions(header).map(|(width, height)| ("JPEG", width, height))
+    } else {
+        None
+    }
+}
+
+/// Walks the JPEG's segments to the start of frame, which holds the dimensions
+fn jpeg_dimensions(header: &[u8]) -> Option<(u32, u32)> {
+    let u16_be = |at: usize| Some(u16::from_be_bytes(header.get(at..at + 2)?.try_into().ok()?));
+
+    let mut position = 2;
+    loop {
+        if *header.get(position)? != 0xff {
+            return None;
+        }
+        let marker = *header.get(position + 1)?;
+        let segment_length = u16_be(position + 2)? as usize;
+        // SOF0 to SOF15, apart from the markers that share the range
+        if (0xc0..=0xcf).contains(&marker) && ![0xc4, 0xc8, 0xcc].contains(&marker) {
+            let height = u16_be(position + 5)?;
+            let width = u16_be(position + 7)?;
+            return Some((width.into(), height.into()));
+        }
+        position += 2 + segment_length;
+    }
+}
+
+/// Asks `ffprobe` about the file's streams, returning `None` if it isn't installed or fails
+fn ffprobe_metadata(file_path: &Path) -> Option<Vec<String>> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries"])
+        .arg("format=duration,bit_rate:stream=codec_type,codec_name,width,height")
+        .args(["-of", "default=noprint_wrappers=1"])
+        .arg(file_path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let metadata = parse_ffprobe_output(&String::from_utf8_lossy(&output.stdout));
+    (!metadata.is_empty()).then_some(metadata)
+}
+
+/// Turns ffprobe's `key=value` lines into `Label: value` lines, skipping values it didn't know
+fn parse_ffprobe_output(output: &str) -> Vec<String> {
+    let mut metadata = Vec::new();
+    let mut codec_name = None;
+    let mut width = None;
+
+    for (key, value) in output.lines().filter_map(|line| line.split_once('=')) {
+        if value == "N/A" {
+            continue;
+        }
+        match key {
+            "codec_name" => codec_name = Some(value),
+            "codec_type" => {
+                if let Some(codec_name) = codec_name.take()
+                    && !value.is_empty()
+                {
+                    let mut codec_type = value.to_string();
+                    codec_type[..1].make_ascii_uppercase();
+                    metadata.push(format!("{codec_type} codec: {codec_name}"));
+                }
+            }
+            "width" => width = Some(value),
+            "height" => {
+                if let Some(width) = width.take() {
+                    metadata.push(format!("Dimensions: {width} × {value}"));
+                }
+            }
+            "duration" => {
+                if let Ok(seconds) = value.parse::<f64>() {
+                    metadata.push(format!("Duration: {}", format_duration(seconds)));
+                }
+            }
+            "bit_rate" => {
+                if let Ok(bits_per_second) = value.parse::<u64>() {
+                    metadata.push(format!("Bitrate: {} kb/s", bits_per_second / 1000));
+                }
+            }
+            _ => {}
+        }
+    }
+    metadata
+}
+
+/// Formats like `1:02:03`, or `2:03` for anything under an hour
+fn format_duration(seconds: f64) -> String {
+    let total_seconds = seconds.round() as u64;
+    let (hours, minutes, seconds) = (
+        total_seconds / 3600,
+        total_seconds / 60 % 60,
+        total_seconds % 60,
+    );
+
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn dimensions_are_read_from_image_headers() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(640u32.to_be_bytes());
+        png.extend(480u32.to_be_bytes());
+        assert_eq!(image_dimensions(&png), Some(("PNG", 640, 480)));
+
+        let gif = b"GIF89a\x03\x00\x02\x00";
+        assert_eq!(image_dimensions(gif), Some(("GIF", 3, 2)));
+
+        // An APP0 segment comes before the start of frame
+        let jpeg = b"\xff\xd8\xff\xe0\x00\x04\x00\x00\xff\xc0\x00\x11\x08\x00\x20\x00\x40";
+        assert_eq!(image_dimensions(jpeg), Some(("JPEG", 64, 32)));
+
+        assert_eq!(image_dimensions(b"not an image"), None);
+    }
+
+    #[test]
+    fn same_file_is_probed_once_until_it_changes() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let song_path = tmp_dir.path().join("song.mp3");
+        std::fs::write(&song_path, "tune").unwrap();
+        let metadata = Some(vec!["Duration: 0:03".to_string()]);
+
+        let mut media_probe = MediaProbe::default();
+        media_probe.request(&song_path);
+        assert_eq!(
+            media_probe.state_for(&song_path),
+            Some(&ProbeState::Pending)
+        );
+
+        media_probe.deliver(file_version(&song_path), metadata.clone());
+        media_probe.request(&song_path);
+        assert_eq!(
+            media_probe.state_for(&song_path),
+            Some(&ProbeState::Ready(metadata.clone()))
+        );
+
+        // A changed file is probed again, and what was found before no longer counts for it
+        let old_version = file_version(&song_path);
+        std::fs::write(&song_path, "longer tune").unwrap();
+        media_probe.request(&song_path);
+        media_probe.deliver(old_version, metadata);
+        assert_eq!(
+            media_probe.state_for(&song_path),
+            Some(&ProbeState::Pending)
+        );
+    }
+
+    #[test]
+    fn ffprobe_output_is_labelled() {
+        let output = "codec_name=h264\ncodec_type=video\nwidth=1920\nheight=1080\n\
+                      codec_name=aac\ncodec_type=audio\nwidth=N/A\n\
+                      duration=3723.4\nbit_rate=128000\n";
+
+        assert_eq!(
+            parse_ffprobe_output(output),
+            vec![
+                "Video codec: h264",
+                "Dimensions: 1920 × 1080",
+                "Audio codec: aac",
+                "Duration: 1:02:03",
+                "Bitrate: 128 kb/s",
+            ]
+        );
+    }
+}
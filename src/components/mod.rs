@@ -1,5 +1,30 @@
 mod app;
+mod archive;
+mod batch_rename_view;
+mod cd_prompt;
+mod confirmation;
+mod detail_view;
+mod dir_summary;
 mod directory_view;
+mod extension_groups;
+mod favorites_view;
 mod file_view;
+mod git_diff;
+mod group_headers;
+mod help_view;
+mod inline_image;
+mod ls_colors;
+mod media;
+mod name_prompt;
+mod preview_cache;
+mod recent_dirs_view;
+mod right_pane;
+mod search;
+mod selection_list_view;
+mod theme;
 
 pub use app::App;
+pub use directory_view::DirectoryView;
+pub use file_view::{PreviewContent, preview_lines};
+pub(crate) use media::{MediaKind, media_kind};
+pub use theme::Theme;
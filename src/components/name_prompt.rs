@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+/// What the name typed into a `NamePrompt` is used for
+#[derive(Debug, Clone, PartialEq)]
+pub enum NamePurpose {
+    NewFile,
+    /// A new name for the entry at this path
+    Rename(PathBuf),
+}
+
+/// A name being typed for an entry in the current directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamePrompt {
+    pub input: String,
+    pub purpose: NamePurpose,
+}
+
+impl NamePrompt {
+    pub fn new(purpose: NamePurpose) -> Self {
+        NamePrompt {
+            input: String::new(),
+            purpose,
+        }
+    }
+
+    /// A prompt to rename the entry at `path`, starting from its current name
+    pub fn rename(path: PathBuf) -> Self {
+        NamePrompt {
+            input: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            purpose: NamePurpose::Rename(path),
+        }
+    }
+
+    pub fn prompt(&self) -> String {
+        let label = match self.purpose {
+            NamePurpose::NewFile => "new file",
+            NamePurpose::Rename(_) => "rename to",
+        };
+        format!("{label} {}_", self.input)
+    }
+}
+
+/// Why `name` can't name an entry, for the mistakes that are caught before asking the OS
+pub fn invalid_name_reason(name: &str) -> Option<&'static str> {
+    if name.is_empty() {
+        Some("The name is empty")
+    } else if name == "." || name == ".." {
+        Some("That name is reserved")
+    } else if name.contains('/') || name.contains('\0') {
+        Some("Names can't contain / or NUL")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_shows_what_the_name_is_for() {
+        let mut name_prompt = NamePrompt::new(NamePurpose::NewFile);
+        name_prompt.input.push_str("notes.txt");
+
+        assert_eq!(name_prompt.prompt(), "new file notes.txt_");
+
+        let name_prompt = NamePrompt::rename(PathBuf::from("/tmp/draft.txt"));
+        assert_eq!(name_prompt.prompt(), "rename to draft.txt_");
+    }
+
+    #[test]
+    fn unusable_names_are_explained() {
+        assert_eq!(invalid_name_reason(""), Some("The name is empty"));
+        assert_eq!(invalid_name_reason(".."), Some("That name is reserved"));
+        assert_eq!(
+            invalid_name_reason("sub/notes.txt"),
+            Some("Names can't contain / or NUL")
+        );
+        assert_eq!(invalid_name_reason(".notes.txt"), None);
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::components::file_view::{PreviewContent, PreviewLimits, preview_lines_with_limits};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreviewState {
+    Pending,
+    Ready(PreviewContent),
+}
+
+type LoadedPreview = (PathBuf, usize, PreviewContent);
+
+/// Loads previews on a background thread so that large files don't block the UI,
+/// keeping the preview for the most recently requested path and height
+#[derive(Debug)]
+pub struct PreviewCache {
+    entry: Option<(PathBuf, usize, PreviewState)>,
+    sender: Sender<LoadedPreview>,
+    receiver: Receiver<LoadedPreview>,
+}
+
+impl Default for PreviewCache {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        PreviewCache {
+            entry: None,
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl PreviewCache {
+    /// Starts loading the preview of `file_path`, unless it is already loaded or loading
+    pub fn request(&mut self, file_path: &Path, column_height: usize, limits: PreviewLimits) {
+        if let Some((path, height, _)) = &self.entry
+            && path == file_path
+            && *height == column_height
+        {
+            return;
+        }
+
+        self.entry = Some((
+            file_path.to_path_buf(),
+            column_height,
+            PreviewState::Pending,
+        ));
+
+        let sender = self.sender.clone();
+        let file_path = file_path.to_path_buf();
+        thread::spawn(move || {
+            let contents = preview_lines_with_limits(&file_path, column_height, limits);
+            // The receiver only goes away when the app is closing
+            let _ = sender.send((file_path, column_height, contents));
+        });
+    }
+
+    /// Stores loaded contents, ignoring any that are for a preview which is no longer wanted
+    pub fn deliver(&mut self, file_path: &Path, column_height: usize, contents: PreviewContent) {
+        if let Some((path, height, state)) = &mut self.entry
+            && path == file_path
+            && *height == column_height
+        {
+            *state = PreviewState::Ready(contents);
+        }
+    }
+
+    /// Collects any previews that have finished loading
+    pub fn poll(&mut self) {
+        while let Ok((file_path, column_height, contents)) = self.receiver.try_recv() {
+            self.deliver(&file_path, column_height, contents);
+        }
+    }
+
+    pub fn state_for(&self, file_path: &Path) -> Option<&PreviewState> {
+        match &self.entry {
+            Some((path, _, state)) if path == file_path => Some(state),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switching_selection_is_pending_until_delivered() {
+        let mut preview_cache = PreviewCache::default();
+        let path_a = Path::new("/does/not/exist/a.txt");
+        let path_b = Path::new("/does/not/exist/b.txt");
+
+        preview_cache.request(path_a, 10, PreviewLimits::default());
+        assert_eq!(
+            preview_cache.state_for(path_a),
+            Some(&PreviewState::Pending)
+        );
+
+        preview_cache.deliver(path_a, 10, PreviewContent::Text(vec!["a".to_string()]));
+        assert_eq!(
+            preview_cache.state_for(path_a),
+            Some(&PreviewState::Ready(PreviewContent::Text(vec![
+                "a".to_string()
+            ])))
+        );
+
+        // Moving to another file starts a new pending preview
+        preview_cache.request(path_b, 10, PreviewLimits::default());
+        assert_eq!(preview_cache.state_for(path_a), None);
+        assert_eq!(
+            preview_cache.state_for(path_b),
+            Some(&PreviewState::Pending)
+        );
+
+        // A late delivery for the old file doesn't replace it
+        preview_cache.deliver(path_a, 10, PreviewContent::Text(vec!["a".to_string()]));
+        assert_eq!(
+            preview_cache.state_for(path_b),
+            Some(&PreviewState::Pending)
+        );
+
+        preview_cache.deliver(path_b, 10, PreviewContent::Text(vec!["b".to_string()]));
+        assert_eq!(
+            preview_cache.state_for(path_b),
+            Some(&PreviewState::Ready(PreviewContent::Text(vec![
+                "b".to_string()
+            ])))
+        );
+    }
+
+    #[test]
+    fn requesting_the_same_preview_keeps_loaded_contents() {
+        let mut preview_cache = PreviewCache::default();
+        let path = Path::new("/does/not/exist/a.txt");
+
+        preview_cache.request(path, 10, PreviewLimits::default());
+        preview_cache.deliver(path, 10, PreviewContent::Text(vec!["a".to_string()]));
+        preview_cache.request(path, 10, PreviewLimits::default());
+
+        assert_eq!(
+            preview_cache.state_for(path),
+            Some(&PreviewState::Ready(PreviewContent::Text(vec![
+                "a".to_string()
+            ])))
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    symbols::border,
+    text::{Line, Text},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+/// Sidebar listing recently visited directories by their full path, most recent first
+pub struct RecentDirsView<'a> {
+    paths: &'a [PathBuf],
+    cursor_index: usize,
+}
+
+impl<'a> RecentDirsView<'a> {
+    pub fn new(paths: &'a [PathBuf], cursor_index: usize) -> Self {
+        RecentDirsView {
+            paths,
+            cursor_index,
+        }
+    }
+}
+
+impl Widget for &RecentDirsView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from(" Recent ".bold()).centered())
+            .borders(Borders::RIGHT)
+            .border_set(border::ROUNDED);
+
+        let lines: Vec<Line> = self
+            .paths
+            .iter()
+            .enumerate()
+            .map(|(index, path)| {
+                let prefix = if index == self.cursor_index {
+                    "> "
+                } else {
+                    "  "
+                };
+                Line::from(format!("{prefix}{}", path.to_string_lossy())).blue()
+            })
+            .collect();
+
+        Paragraph::new(Text::from(lines))
+            .left_aligned()
+            .block(block)
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,37 @@
+/// What the pane to the right of the listing shows about the selected entry
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RightPaneMode {
+    /// The file's contents, or a listing for directories and archives
+    #[default]
+    Contents,
+    /// The entry's type, size, modification time and permissions
+    Details,
+    /// What is inside a directory, before entering it
+    Peek,
+    /// The file's unstaged changes, when it is tracked by git and has any
+    Diff,
+}
+
+impl RightPaneMode {
+    /// The mode after this one, wrapping back round to the first
+    pub fn next(self) -> Self {
+        match self {
+            RightPaneMode::Contents => RightPaneMode::Details,
+            RightPaneMode::Details => RightPaneMode::Peek,
+            RightPaneMode::Peek | RightPaneMode::Diff => RightPaneMode::Contents,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes_cycle_back_to_the_start() {
+        let mode = RightPaneMode::default();
+        assert_eq!(mode.next(), RightPaneMode::Details);
+        assert_eq!(mode.next().next(), RightPaneMode::Peek);
+        assert_eq!(mode.next().next().next(), RightPaneMode::Contents);
+    }
+}
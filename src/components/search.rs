@@ -0,0 +1,222 @@
+use std::{
+    cmp::Reverse,
+    path::{Path, PathBuf},
+};
+
+/// A case-insensitive fuzzy search of the names in the listing, which only lists the entries it
+/// matches while it is open, and is kept once it is confirmed so `n` and `N` can step through them
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Search {
+    pub query: String,
+    /// Whether the query is still being typed
+    pub editing: bool,
+}
+
+impl Search {
+    pub fn new() -> Self {
+        Search {
+            query: String::new(),
+            editing: true,
+        }
+    }
+
+    pub fn prompt(&self) -> String {
+        if self.editing {
+            format!("/{}_", self.query)
+        } else {
+            format!("/{} (n/N)", self.query)
+        }
+    }
+
+    /// How well `entry`'s name matches the query, or `None` if it doesn't
+    pub fn score(&self, entry: &Path) -> Option<u32> {
+        Some(fuzzy_match(&self.query, &entry.file_name()?.to_string_lossy())?.score)
+    }
+
+    /// The positions of the characters in `name` matched by the query, to highlight
+    pub fn matched_chars(&self, name: &str) -> Vec<usize> {
+        fuzzy_match(&self.query, name)
+            .map(|fuzzy_match| fuzzy_match.positions)
+            .unwrap_or_default()
+    }
+
+    /// The positions of the entries whose names match the query
+    pub fn matches(&self, entries: &[PathBuf]) -> Vec<usize> {
+        entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| self.score(entry).is_some())
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// The position of the entry matching the query best, the first of them on a tie
+    pub fn best_match(&self, entries: &[PathBuf]) -> Option<usize> {
+        entries
+            .iter()
+            .enumerate()
+            .filter_map(|(position, entry)| Some((position, self.score(entry)?)))
+            .min_by_key(|&(position, score)| (Reverse(score), position))
+            .map(|(position, _)| position)
+    }
+
+    /// The first match after `position`, or before it going `backwards`, wrapping round the
+    /// listing. `position` itself only counts once every other match has been passed
+    pub fn step(&self, entries: &[PathBuf], position: usize, backwards: bool) -> Option<usize> {
+        let matches = self.matches(entries);
+        if backwards {
+            matches
+                .iter()
+                .rev()
+                .find(|&&matched| matched < position)
+                .or(matches.last())
+                .copied()
+        } else {
+            matches
+                .iter()
+                .find(|&&matched| matched > position)
+                .or(matches.first())
+                .copied()
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct FuzzyMatch {
+    score: u32,
+    /// The character positions in the name of each character of the query
+    positions: Vec<usize>,
+}
+
+/// Finds the query's characters in `name` in order, ignoring case, scoring the match higher the
+/// more of them run on from each other or start a word
+fn fuzzy_match(query: &str, name: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let name: Vec<char> = name.chars().collect();
+    let matches_at = |position: usize, query_char: char| {
+        name[position].to_lowercase().eq(query_char.to_lowercase())
+    };
+    let Some((&first, rest)) = query.split_first() else {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    };
+
+    // Every place the first character matches is tried, taking the earliest match of the rest
+    // after it, as a later start can run on where an earlier one is broken up
+    (0..name.len())
+        .filter(|&start| matches_at(start, first))
+        .filter_map(|start| {
+            let mut positions = vec![start];
+            for &query_char in rest {
+                let after = positions.last().unwrap() + 1;
+                positions
+                    .push((after..name.len()).find(|&position| matches_at(position, query_char))?);
+            }
+            Some(positions)
+        })
+        .map(|positions| {
+            let score = positions
+                .iter()
+                .enumerate()
+                .map(|(index, &position)| {
+                    let runs_on = index > 0 && positions[index - 1] + 1 == position;
+                    let starts_word = position == 0 || !name[position - 1].is_alphanumeric();
+                    1 + if runs_on { 5 } else { 0 } + if starts_word { 3 } else { 0 }
+                })
+                .sum();
+            FuzzyMatch { score, positions }
+        })
+        .min_by_key(|fuzzy_match| Reverse(fuzzy_match.score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<PathBuf> {
+        [
+            "Report.txt",
+            "notes.md",
+            "report-old.txt",
+            "todo.txt",
+            "REPORTS",
+        ]
+        .map(PathBuf::from)
+        .to_vec()
+    }
+
+    #[test]
+    fn matches_ignore_case() {
+        let search = Search {
+            query: "report".to_string(),
+            editing: false,
+        };
+        assert_eq!(search.matches(&entries()), vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn query_characters_match_in_order_with_gaps() {
+        let search = Search {
+            query: "rpt".to_string(),
+            editing: true,
+        };
+        assert_eq!(search.matches(&entries()), vec![0, 2, 4]);
+        assert_eq!(search.matched_chars("Report.txt"), vec![0, 2, 5]);
+
+        let search = Search {
+            query: "tpr".to_string(),
+            editing: true,
+        };
+        assert!(search.matches(&entries()).is_empty());
+    }
+
+    #[test]
+    fn runs_and_word_starts_score_higher() {
+        let score = |query: &str, name: &str| fuzzy_match(query, name).unwrap().score;
+
+        assert!(score("old", "report-old.txt") > score("old", "o_l_d"));
+        assert!(score("rep", "report") > score("rep", "xrep"));
+        assert!(score("rep", "xrep") > score("rep", "r_e_p"));
+        // A later start that runs on beats the earliest scattered one
+        assert_eq!(
+            fuzzy_match("log", "l_o_g-log").unwrap().positions,
+            vec![6, 7, 8]
+        );
+    }
+
+    #[test]
+    fn best_match_is_the_highest_scoring_first_on_a_tie() {
+        let search = Search {
+            query: "todo".to_string(),
+            editing: false,
+        };
+        let entries = ["t_o_d_o", "todo.txt", "my-todo", "todo.md"].map(PathBuf::from);
+        assert_eq!(search.best_match(&entries), Some(1));
+
+        let search = Search {
+            query: "zzz".to_string(),
+            editing: false,
+        };
+        assert_eq!(search.best_match(&entries), None);
+    }
+
+    #[test]
+    fn steps_wrap_round_in_both_directions() {
+        let search = Search {
+            query: "report".to_string(),
+            editing: false,
+        };
+        assert_eq!(search.step(&entries(), 0, false), Some(2));
+        assert_eq!(search.step(&entries(), 4, false), Some(0));
+        assert_eq!(search.step(&entries(), 0, true), Some(4));
+        assert_eq!(search.step(&entries(), 3, true), Some(2));
+
+        let search = Search {
+            query: "missing".to_string(),
+            editing: false,
+        };
+        assert_eq!(search.step(&entries(), 0, false), None);
+    }
+}
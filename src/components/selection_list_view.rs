@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    symbols::border,
+    text::{Line, Text},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+/// Side panel listing the collected files, numbered in the order they were added
+pub struct SelectionListView<'a> {
+    paths: &'a [PathBuf],
+}
+
+impl<'a> SelectionListView<'a> {
+    pub fn new(paths: &'a [PathBuf]) -> Self {
+        SelectionListView { paths }
+    }
+}
+
+impl Widget for &SelectionListView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from(" Collected ".bold()).centered())
+            .borders(Borders::LEFT)
+            .border_set(border::ROUNDED);
+
+        let lines: Vec<Line> = self
+            .paths
+            .iter()
+            .enumerate()
+            .map(|(index, path)| {
+                let name = path
+                    .file_name()
+                    .and_then(|os_str| os_str.to_str())
+                    .unwrap_or("<invalid utf-8>");
+                Line::from(format!("{}. {name}", index + 1)).yellow()
+            })
+            .collect();
+
+        Paragraph::new(Text::from(lines))
+            .left_aligned()
+            .block(block)
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,173 @@
+use std::{env, path::Path};
+
+use ratatui::{
+    style::{Color, Style, Stylize},
+    widgets::{BorderType, Borders},
+};
+
+use crate::{components::ls_colors::LsColors, display_width::display_width};
+
+/// Styles used when rendering directory entries
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub dir_style: Style,
+    pub file_style: Style,
+    pub other_style: Style,
+    /// Applied on top of an entry's style while it is marked
+    pub marked_style: Style,
+    /// Applied on top of the style of directories ignored by git, when they are dimmed rather than hidden
+    pub ignored_style: Style,
+    /// Marks the directory the app was launched in within the current path, once below it
+    pub root_style: Style,
+    /// Group headers, listed above each group when the sort order groups entries
+    pub header_style: Style,
+    /// The characters of names matched by the search
+    pub search_match_style: Style,
+    /// Applied on top of the style of the entry cut to be moved, until it is pasted
+    pub cut_style: Style,
+    /// Lines a diff adds
+    pub diff_added_style: Style,
+    /// Lines a diff removes
+    pub diff_removed_style: Style,
+    /// Applied on top of every other row's style to stripe long lists, except on the cursor's row
+    pub zebra_style: Option<Style>,
+    /// Shown before the entry under the cursor
+    pub cursor_marker: String,
+    /// Shown before every other entry
+    pub indent: String,
+    /// The width of each marker gutter between the cursor marker and the names, such as the
+    /// column stars are shown in
+    pub gutter_width: usize,
+    /// The sides of the preview pane with a border, which is drawn all the way round while it floats
+    /// over the listing
+    pub preview_borders: Borders,
+    pub preview_border_type: BorderType,
+    pub preview_border_style: Style,
+    /// Lays the listing out from the right edge, with the cursor marker after each name
+    pub right_to_left: bool,
+    /// Takes precedence over the built-in styles when it has a style for an entry
+    pub ls_colors: Option<LsColors>,
+    /// When false, nothing is colored or emphasised and only the cursor marker shows the selection
+    pub colors_enabled: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            dir_style: Style::new().blue(),
+            file_style: Style::new().yellow(),
+            other_style: Style::new(),
+            marked_style: Style::new().bold().underlined(),
+            ignored_style: Style::new().dark_gray(),
+            root_style: Style::new().bold().underlined(),
+            header_style: Style::new().bold(),
+            search_match_style: Style::new().magenta().bold(),
+            cut_style: Style::new().dim(),
+            diff_added_style: Style::new().green(),
+            diff_removed_style: Style::new().red(),
+            zebra_style: None,
+            cursor_marker: "> ".to_string(),
+            indent: "  ".to_string(),
+            gutter_width: 2,
+            preview_borders: Borders::LEFT,
+            preview_border_type: BorderType::Rounded,
+            preview_border_style: Style::new(),
+            right_to_left: false,
+            ls_colors: None,
+            colors_enabled: true,
+        }
+    }
+}
+
+impl Theme {
+    /// The built-in theme, using `LS_COLORS` when it is set, or no colors at all when `NO_COLOR` is
+    pub fn from_env() -> Self {
+        if env::var_os("NO_COLOR").is_some_and(|no_color| !no_color.is_empty()) {
+            return Self::no_color();
+        }
+
+        Theme {
+            ls_colors: LsColors::from_env(),
+            ..Default::default()
+        }
+    }
+
+    /// Plain text only, for terminals and recordings where color is unwanted
+    pub fn no_color() -> Self {
+        Theme {
+            dir_style: Style::new(),
+            file_style: Style::new(),
+            other_style: Style::new(),
+            marked_style: Style::new(),
+            ignored_style: Style::new(),
+            root_style: Style::new(),
+            header_style: Style::new(),
+            search_match_style: Style::new(),
+            cut_style: Style::new(),
+            diff_added_style: Style::new(),
+            diff_removed_style: Style::new(),
+            preview_border_style: Style::new(),
+            ls_colors: None,
+            colors_enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// A subtly darker background for every other row
+    pub fn with_zebra_rows(self) -> Self {
+        Theme {
+            zebra_style: Some(Style::new().bg(Color::Indexed(236))),
+            ..self
+        }
+    }
+
+    /// Columns fill from the right edge leftwards, with the cursor marker mirrored when it is the
+    /// default one
+    pub fn with_right_to_left(self) -> Self {
+        let cursor_marker = if self.cursor_marker == "> " {
+            " <".to_string()
+        } else {
+            self.cursor_marker
+        };
+        Theme {
+            right_to_left: true,
+            cursor_marker,
+            ..self
+        }
+    }
+
+    pub fn with_preview_border(
+        self,
+        borders: Borders,
+        border_type: BorderType,
+        border_style: Style,
+    ) -> Self {
+        Theme {
+            preview_borders: borders,
+            preview_border_type: border_type,
+            preview_border_style: border_style,
+            ..self
+        }
+    }
+
+    /// The width reserved before each name, so names line up whether or not they have the cursor
+    pub fn prefix_width(&self) -> usize {
+        display_width(&self.cursor_marker).max(display_width(&self.indent))
+    }
+
+    pub fn style_for(&self, entity: &Path) -> Style {
+        if let Some(style) = self
+            .ls_colors
+            .as_ref()
+            .and_then(|ls_colors| ls_colors.style_for(entity))
+        {
+            style
+        } else if entity.is_dir() {
+            self.dir_style
+        } else if entity.is_file() {
+            self.file_style
+        } else {
+            self.other_style
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use std::{env, fs, path::PathBuf, str::FromStr};
+
+use ratatui::{
+    style::Color,
+    widgets::{BorderType, Borders},
+};
+
+use crate::{
+    human_size::SizeUnits,
+    sorting::{SortMode, SortTiebreaker},
+};
+
+/// What pressing enter over a file does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileOpenAction {
+    Preview,
+    Pager,
+    Editor,
+    /// The platform's default application for the file
+    SystemOpener,
+}
+
+impl FileOpenAction {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "preview" => Some(FileOpenAction::Preview),
+            "pager" => Some(FileOpenAction::Pager),
+            "editor" => Some(FileOpenAction::Editor),
+            "open" => Some(FileOpenAction::SystemOpener),
+            _ => None,
+        }
+    }
+}
+
+/// What pressing enter over a directory does
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DirEnterBehavior {
+    #[default]
+    Navigate,
+    /// Previews the directory's contents first, only navigating into it on a second enter
+    Peek,
+}
+
+impl DirEnterBehavior {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "navigate" => Some(DirEnterBehavior::Navigate),
+            "peek" => Some(DirEnterBehavior::Peek),
+            _ => None,
+        }
+    }
+}
+
+/// How directories ignored by git are shown while gitignored entries are hidden
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum IgnoredDirDisplay {
+    #[default]
+    Hide,
+    /// Still listed, but drawn with the theme's ignored style
+    Dim,
+}
+
+impl IgnoredDirDisplay {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "hide" => Some(IgnoredDirDisplay::Hide),
+            "dim" => Some(IgnoredDirDisplay::Dim),
+            _ => None,
+        }
+    }
+}
+
+/// What the preview shows for a file it can't read
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum UnreadablePreview {
+    /// A line saying why it couldn't be read
+    #[default]
+    Reason,
+    /// The file's type, size, modification time and permissions
+    Metadata,
+    /// Closes the preview, saying why in the footer
+    Close,
+}
+
+impl UnreadablePreview {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "reason" => Some(UnreadablePreview::Reason),
+            "metadata" => Some(UnreadablePreview::Metadata),
+            "close" => Some(UnreadablePreview::Close),
+            _ => None,
+        }
+    }
+}
+
+/// How an editor is told which line to open a file at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditorLineFormat {
+    /// `+LINE file`, as understood by vi, nano, emacs and most terminal editors
+    Plus,
+    /// `--line LINE file`
+    LineFlag,
+    /// `--goto file:LINE`, as understood by VS Code
+    Goto,
+    /// `file:LINE`, as understood by Sublime Text and Helix
+    Suffix,
+}
+
+impl EditorLineFormat {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "plus" => Some(EditorLineFormat::Plus),
+            "line" => Some(EditorLineFormat::LineFlag),
+            "goto" => Some(EditorLineFormat::Goto),
+            "suffix" => Some(EditorLineFormat::Suffix),
+            _ => None,
+        }
+    }
+}
+
+/// User configurable behaviour of the explorer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Entering a directory with more entries than this asks for confirmation first
+    pub large_dir_threshold: usize,
+    /// Previewing a file larger than this many bytes asks for confirmation first
+    pub large_file_threshold: u64,
+    /// The shortest run of same-extension files that is collapsed in the summarized view
+    pub min_extension_group_size: usize,
+    /// Enter over a file does nothing when unset
+    pub file_enter_action: Option<FileOpenAction>,
+    pub dir_enter_behavior: DirEnterBehavior,
+    /// Run with the selected path whenever the selection changes, its output replacing the preview
+    pub selection_hook: Option<String>,
+    pub selection_hook_debounce_ms: u64,
+    /// Files at least this large are previewed from a background thread
+    pub background_preview_min_bytes: u64,
+    /// The most columns the listing lays out at once, scrolling horizontally past them when set
+    pub max_columns: Option<usize>,
+    /// Stripes every other row of the listing
+    pub zebra_rows: bool,
+    /// Lists directories above files in directories `g` hasn't been pressed in, unless the sort mode
+    /// mixes them in
+    pub dirs_first: bool,
+    /// Lists a header above each group, when sorting by type or modification time
+    pub group_headers: bool,
+    /// Lays the listing out from the right edge, for right-to-left languages
+    pub right_to_left: bool,
+    /// Marks files with more than one hard link with how many they have
+    pub show_hard_links: bool,
+    /// When false, destructive actions such as deleting run without asking first
+    pub confirm_destructive: bool,
+    /// How many levels below the current directory are included when copying it as a tree
+    pub tree_copy_depth: usize,
+    /// When true, `q` asks before quitting
+    pub confirm_quit: bool,
+    /// Longer lines in a preview are cut short
+    pub preview_max_line_bytes: usize,
+    /// The most of a file read for its preview
+    pub preview_max_bytes: u64,
+    /// How many lines of a text file Quick Look reads at first, and each time `m` loads more
+    pub preview_chunk_lines: usize,
+    /// When true, moving left from the first column or right from the last wraps to the other end
+    pub wrap_columns: bool,
+    /// The order entries are listed in, unless the app is given its own comparator
+    pub sort_mode: SortMode,
+    /// How entries the sort order finds equal are ordered
+    pub sort_tiebreaker: SortTiebreaker,
+    pub size_units: SizeUnits,
+    /// Re-reads the current directory this often, or never when 0
+    pub auto_refresh_secs: u64,
+    /// Ignores a directory change this soon after the last, or none when 0
+    pub navigation_lock_ms: u64,
+    /// Cancels a y/n prompt left unanswered this long, or never when 0
+    pub confirmation_timeout_secs: u64,
+    /// Paths and name globs listed at the top of their directory whatever the sort order
+    pub pinned: Vec<String>,
+    pub unreadable_preview: UnreadablePreview,
+    /// The sides of the preview pane with a border
+    pub preview_borders: Borders,
+    pub preview_border_type: BorderType,
+    /// Drawn in the terminal's default color when unset
+    pub preview_border_color: Option<Color>,
+    /// How the editor is opened at a line, guessed from the editor's name when unset
+    pub editor_line_format: Option<EditorLineFormat>,
+    /// Whether entries ignored by the surrounding git repository start off hidden
+    pub hide_gitignored: bool,
+    /// Whether directories git ignores are hidden with the rest, or listed dimmed
+    pub gitignored_dirs: IgnoredDirDisplay,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            large_dir_threshold: 100_000,
+            large_file_threshold: 100_000_000,
+            min_extension_group_size: 10,
+            file_enter_action: None,
+            dir_enter_behavior: DirEnterBehavior::Navigate,
+            selection_hook: None,
+            selection_hook_debounce_ms: 200,
+            background_preview_min_bytes: 1024 * 1024,
+            max_columns: None,
+            zebra_rows: false,
+            dirs_first: true,
+            group_headers: false,
+            right_to_left: false,
+            show_hard_links: false,
+            confirm_destructive: true,
+            tree_copy_depth: 2,
+            confirm_quit: false,
+            editor_line_format: None,
+            preview_max_line_bytes: 4096,
+            preview_max_bytes: 1024 * 1024,
+            preview_chunk_lines: 1000,
+            wrap_columns: true,
+            sort_mode: SortMode::Path,
+            sort_tiebreaker: SortTiebreaker::Name,
+            size_units: SizeUnits::Binary,
+            auto_refresh_secs: 0,
+            navigation_lock_ms: 0,
+            confirmation_timeout_secs: 0,
+            pinned: Vec::new(),
+            unreadable_preview: UnreadablePreview::Reason,
+            preview_borders: Borders::LEFT,
+            preview_border_type: BorderType::Rounded,
+            preview_border_color: None,
+            hide_gitignored: false,
+            gitignored_dirs: IgnoredDirDisplay::Hide,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file from the config directory, using the defaults if there isn't one
+    pub fn load_default() -> Self {
+        config_dir()
+            .and_then(|config_dir| fs::read_to_string(config_dir.join("config")).ok())
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Parses `key = value` lines, skipping blank lines, `#` comments and anything unrecognised
+    pub fn parse(contents: &str) -> Self {
+        let mut config = Config::default();
+
+        for (key, value) in contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+        {
+            let value = value.trim();
+            match key.trim() {
+                "large_dir_threshold" => set_parsed(&mut config.large_dir_threshold, value),
+                "large_file_threshold" => set_parsed(&mut config.large_file_threshold, value),
+                "min_extension_group_size" => {
+                    set_parsed(&mut config.min_extension_group_size, value)
+                }
+                "file_enter_action" => config.file_enter_action = FileOpenAction::parse(value),
+                "selection_hook" => {
+                    config.selection_hook = Some(value.to_string()).filter(|hook| !hook.is_empty())
+                }
+                "selection_hook_debounce_ms" => {
+                    set_parsed(&mut config.selection_hook_debounce_ms, value)
+                }
+                "background_preview_min_bytes" => {
+                    set_parsed(&mut config.background_preview_min_bytes, value)
+                }
+                "max_columns" => {
+                    config.max_columns = value.parse().ok().filter(|&max_columns| max_columns > 0)
+                }
+                "zebra_rows" => set_parsed(&mut config.zebra_rows, value),
+                "dirs_first" => set_parsed(&mut config.dirs_first, value),
+                "group_headers" => set_parsed(&mut config.group_headers, value),
+                "right_to_left" => set_parsed(&mut config.right_to_left, value),
+                "show_hard_links" => set_parsed(&mut config.show_hard_links, value),
+                "confirm_destructive" => set_parsed(&mut config.confirm_destructive, value),
+                "tree_copy_depth" => set_parsed(&mut config.tree_copy_depth, value),
+                "confirm_quit" => set_parsed(&mut config.confirm_quit, value),
+                "hide_gitignored" => set_parsed(&mut config.hide_gitignored, value),
+                "gitignored_dirs" => {
+                    if let Some(gitignored_dirs) = IgnoredDirDisplay::parse(value) {
+                        config.gitignored_dirs = gitignored_dirs;
+                    }
+                }
+                "preview_max_line_bytes" => set_parsed(&mut config.preview_max_line_bytes, value),
+                "preview_max_bytes" => set_parsed(&mut config.preview_max_bytes, value),
+                "preview_chunk_lines" => set_parsed(&mut config.preview_chunk_lines, value),
+                "wrap_columns" => set_parsed(&mut config.wrap_columns, value),
+                "preview_borders" => {
+                    if let Some(preview_borders) = parse_borders(value) {
+                        config.preview_borders = preview_borders;
+                    }
+                }
+                "preview_border_type" => {
+                    if let Some(preview_border_type) = parse_border_type(value) {
+                        config.preview_border_type = preview_border_type;
+                    }
+                }
+                "preview_border_color" => config.preview_border_color = value.parse().ok(),
+                "unreadable_preview" => {
+                    if let Some(unreadable_preview) = UnreadablePreview::parse(value) {
+                        config.unreadable_preview = unreadable_preview;
+                    }
+                }
+                "auto_refresh_secs" => set_parsed(&mut config.auto_refresh_secs, value),
+                "navigation_lock_ms" => set_parsed(&mut config.navigation_lock_ms, value),
+                "pinned" => {
+                    config.pinned = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|pattern| !pattern.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                }
+                "confirmation_timeout_secs" => {
+                    set_parsed(&mut config.confirmation_timeout_secs, value)
+                }
+                "size_units" => {
+                    if let Some(size_units) = SizeUnits::parse(value) {
+                        config.size_units = size_units;
+                    }
+                }
+                "sort_mode" => {
+                    if let Some(sort_mode) = SortMode::parse(value) {
+                        config.sort_mode = sort_mode;
+                    }
+                }
+                "sort_tiebreaker" => {
+                    if let Some(sort_tiebreaker) = SortTiebreaker::parse(value) {
+                        config.sort_tiebreaker = sort_tiebreaker;
+                    }
+                }
+                "editor_line_format" => config.editor_line_format = EditorLineFormat::parse(value),
+                "dir_enter_behavior" => {
+                    if let Some(dir_enter_behavior) = DirEnterBehavior::parse(value) {
+                        config.dir_enter_behavior = dir_enter_behavior;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        config
+    }
+}
+
+fn parse_borders(value: &str) -> Option<Borders> {
+    match value {
+        "left" => Some(Borders::LEFT),
+        "all" => Some(Borders::ALL),
+        "none" => Some(Borders::NONE),
+        _ => None,
+    }
+}
+
+fn parse_border_type(value: &str) -> Option<BorderType> {
+    match value {
+        "rounded" => Some(BorderType::Rounded),
+        "plain" => Some(BorderType::Plain),
+        "thick" => Some(BorderType::Thick),
+        "double" => Some(BorderType::Double),
+        _ => None,
+    }
+}
+
+/// Only overwrites `field` if `value` is valid, so a typo keeps the default
+fn set_parsed<T: FromStr>(field: &mut T, value: &str) {
+    if let Ok(parsed) = value.parse() {
+        *field = parsed;
+    }
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// Where persistent state such as favorites is stored
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".config")))
+        .map(|config_home| config_home.join("tui-file-explorer"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_is_parsed_from_key_value_lines() {
+        let config = Config::parse(
+            "# A comment\n\
+             large_dir_threshold = 20\n\
+             large_file_threshold = 1000\n\
+             \n\
+             file_enter_action=editor\n\
+             dir_enter_behavior = peek\n\
+             max_columns = 3\n\
+             confirm_quit = true\n\
+             editor_line_format = goto\n\
+             preview_max_line_bytes = 200\n\
+             wrap_columns = false\n\
+             sort_mode = alpha_natural_mixed\n\
+             preview_borders = none\n\
+             preview_border_color = red\n\
+             size_units = si\n\
+             auto_refresh_secs = 5\n\
+             navigation_lock_ms = 300\n\
+             confirmation_timeout_secs = 10\n\
+             pinned = README*, .git/\n\
+             unreadable_preview = metadata\n\
+             group_headers = true\n\
+             right_to_left = true\n\
+             show_hard_links = true\n\
+             preview_chunk_lines = 50\n\
+             sort_tiebreaker = modified\n\
+             dirs_first = false\n\
+             unknown_key = 1\n",
+        );
+
+        assert_eq!(
+            config,
+            Config {
+                large_dir_threshold: 20,
+                large_file_threshold: 1000,
+                file_enter_action: Some(FileOpenAction::Editor),
+                dir_enter_behavior: DirEnterBehavior::Peek,
+                max_columns: Some(3),
+                confirm_quit: true,
+                editor_line_format: Some(EditorLineFormat::Goto),
+                preview_max_line_bytes: 200,
+                wrap_columns: false,
+                sort_mode: SortMode::AlphaNaturalMixed,
+                preview_borders: Borders::NONE,
+                preview_border_color: Some(Color::Red),
+                size_units: SizeUnits::Si,
+                auto_refresh_secs: 5,
+                navigation_lock_ms: 300,
+                confirmation_timeout_secs: 10,
+                pinned: vec!["README*".to_string(), ".git/".to_string()],
+                unreadable_preview: UnreadablePreview::Metadata,
+                dirs_first: false,
+                group_headers: true,
+                right_to_left: true,
+                show_hard_links: true,
+                preview_chunk_lines: 50,
+                sort_tiebreaker: SortTiebreaker::Modified,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_values_keep_the_default() {
+        let config = Config::parse("large_dir_threshold = lots\nfile_enter_action = none");
+
+        assert_eq!(config, Config::default());
+    }
+}
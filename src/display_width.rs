@@ -0,0 +1,85 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// The number of terminal cells `text` takes up, counting wide characters such as CJK and emoji
+/// as two cells and combining characters as none
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// The longest prefix of `text` that fits in `max_width` cells, never splitting a grapheme
+pub fn truncate_to_width(text: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        width += grapheme.width();
+        if width > max_width {
+            return &text[..index];
+        }
+    }
+    text
+}
+
+/// The part of `text` that fits in `max_width` cells once its first `offset` characters are
+/// skipped, counting a character with its combining marks as one, as `truncate_to_width` does
+pub fn crop_to_window(text: &str, offset: usize, max_width: usize) -> &str {
+    let start = text
+        .grapheme_indices(true)
+        .nth(offset)
+        .map_or(text.len(), |(index, _)| index);
+    truncate_to_width(&text[start..], max_width)
+}
+
+/// How many characters `text` has, counting a character with its combining marks as one
+pub fn character_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_characters_take_two_cells() {
+        assert_eq!(display_width("file.txt"), 8);
+        assert_eq!(display_width("写真.jpg"), 8);
+        assert_eq!(display_width("🎵.mp3"), 6);
+    }
+
+    #[test]
+    fn combining_characters_take_no_cells() {
+        // "e" followed by a combining acute accent
+        assert_eq!(display_width("cafe\u{301}"), 4);
+    }
+
+    #[test]
+    fn truncation_counts_cells_not_bytes() {
+        assert_eq!(truncate_to_width("file.txt", 4), "file");
+        assert_eq!(truncate_to_width("写真.jpg", 4), "写真");
+        assert_eq!(truncate_to_width("🎵🎶.mp3", 2), "🎵");
+        assert_eq!(truncate_to_width("short", 10), "short");
+    }
+
+    #[test]
+    fn truncation_does_not_split_wide_or_combined_characters() {
+        // Half of a wide character can't be shown
+        assert_eq!(truncate_to_width("写真.jpg", 3), "写");
+        assert_eq!(truncate_to_width("cafe\u{301}!", 4), "cafe\u{301}");
+    }
+
+    #[test]
+    fn window_starts_after_the_offset() {
+        assert_eq!(crop_to_window("0123456789", 0, 4), "0123");
+        assert_eq!(crop_to_window("0123456789", 3, 4), "3456");
+        assert_eq!(crop_to_window("0123456789", 8, 4), "89");
+        assert_eq!(crop_to_window("0123456789", 12, 4), "");
+    }
+
+    #[test]
+    fn window_counts_multi_byte_characters_whole() {
+        assert_eq!(crop_to_window("naïve café", 2, 5), "ïve c");
+        assert_eq!(crop_to_window("写真.jpg", 1, 3), "真.");
+        assert_eq!(crop_to_window("写真.jpg", 1, 1), "");
+        assert_eq!(crop_to_window("cafe\u{301}s", 3, 2), "e\u{301}s");
+        assert_eq!(character_count("cafe\u{301}s"), 5);
+    }
+}
@@ -0,0 +1,296 @@
+use std::{ffi::OsString, path::Path, process::Command};
+
+use crate::config::EditorLineFormat;
+
+const DEFAULT_PAGER: &str = "less";
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Builds the command to page through `file_path`, honouring `$PAGER` (which may include arguments)
+pub fn pager_command(pager: Option<OsString>, file_path: &Path) -> Command {
+    command_from_env_value(pager, DEFAULT_PAGER, file_path)
+}
+
+/// Builds the command to edit `file_path`, honouring `$VISUAL` or `$EDITOR` (which may include arguments)
+pub fn editor_command(editor: Option<OsString>, file_path: &Path) -> Command {
+    command_from_env_value(editor, DEFAULT_EDITOR, file_path)
+}
+
+/// Builds the command to edit `file_path` with the cursor on `line`, in the way `format` describes
+/// or, if that isn't given, the way the editor is known to accept
+pub fn editor_command_at_line(
+    editor: Option<OsString>,
+    file_path: &Path,
+    line: usize,
+    format: Option<EditorLineFormat>,
+) -> Command {
+    let parts = command_parts(editor, DEFAULT_EDITOR);
+    let format = format.unwrap_or_else(|| guess_line_format(&parts[0]));
+
+    let mut command = Command::new(&parts[0]);
+    command.args(&parts[1..]);
+    match format {
+        EditorLineFormat::Plus => command.arg(format!("+{line}")).arg(file_path),
+        EditorLineFormat::LineFlag => command.args(["--line", &line.to_string()]).arg(file_path),
+        EditorLineFormat::Goto => command
+            .arg("--goto")
+            .arg(format!("{}:{line}", file_path.display())),
+        EditorLineFormat::Suffix => command.arg(format!("{}:{line}", file_path.display())),
+    };
+    command
+}
+
+/// How the editor called `program` accepts a line to open at, assuming `+LINE` for unknown editors
+fn guess_line_format(program: &str) -> EditorLineFormat {
+    let name = Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program);
+    match name {
+        "code" | "code-insiders" | "codium" => EditorLineFormat::Goto,
+        "subl" | "hx" | "zed" => EditorLineFormat::Suffix,
+        "kate" | "idea" | "pycharm" => EditorLineFormat::LineFlag,
+        _ => EditorLineFormat::Plus,
+    }
+}
+
+/// Builds the command that opens `file_path` with the platform's default application
+pub fn system_opener_command(file_path: &Path) -> Command {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(file_path);
+    command
+}
+
+/// The desktops a file manager can be opened on, which each reveal entries differently
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Platform {
+    MacOs,
+    Windows,
+    /// Linux and the BSDs, through Nautilus when it's installed or else the desktop's default
+    /// handler for directories
+    Other,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else if cfg!(windows) {
+            Platform::Windows
+        } else {
+            Platform::Other
+        }
+    }
+}
+
+/// Builds the command that opens the directory containing `path` in the graphical file manager,
+/// with `path` selected where the platform supports it
+pub fn reveal_command(path: &Path, platform: Platform) -> Command {
+    match platform {
+        Platform::MacOs => {
+            let mut command = Command::new("open");
+            command.arg("-R").arg(path);
+            command
+        }
+        Platform::Windows => {
+            let mut command = Command::new("explorer");
+            // Explorer doesn't understand the quotes std puts around an argument with a space in
+            // it, so the path is quoted by hand after `/select,`
+            let select = format!("/select,\"{}\"", path.display());
+            #[cfg(windows)]
+            std::os::windows::process::CommandExt::raw_arg(&mut command, select);
+            #[cfg(not(windows))]
+            command.arg(select);
+            command
+        }
+        // Only some file managers can select an entry, so without Nautilus just the directory is
+        // opened
+        Platform::Other => {
+            let mut command = Command::new("sh");
+            command
+                .arg("-c")
+                .arg(r#"if command -v nautilus >/dev/null; then exec nautilus --select "$1"; else exec xdg-open "$2"; fi"#)
+                .arg("sh")
+                .arg(path)
+                .arg(path.parent().unwrap_or(path));
+            command
+        }
+    }
+}
+
+fn command_from_env_value(value: Option<OsString>, default: &str, file_path: &Path) -> Command {
+    let parts = command_parts(value, default);
+    let mut command = Command::new(&parts[0]);
+    command.args(&parts[1..]).arg(file_path);
+    command
+}
+
+/// The program and arguments in an environment variable's value, or `default` if it is unset or blank
+fn command_parts(value: Option<OsString>, default: &str) -> Vec<String> {
+    let value = value
+        .and_then(|value| value.into_string().ok())
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| default.to_string());
+
+    value.split_whitespace().map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    fn program_and_args(command: &Command) -> (&OsStr, Vec<&OsStr>) {
+        (command.get_program(), command.get_args().collect())
+    }
+
+    #[test]
+    fn pager_defaults_to_less() {
+        let command = pager_command(None, Path::new("/tmp/file.txt"));
+        assert_eq!(
+            program_and_args(&command),
+            (OsStr::new("less"), vec![OsStr::new("/tmp/file.txt")])
+        );
+
+        let command = pager_command(Some(OsString::from("  ")), Path::new("/tmp/file.txt"));
+        assert_eq!(command.get_program(), OsStr::new("less"));
+    }
+
+    #[test]
+    fn pager_honours_pager_env_with_arguments() {
+        let command = pager_command(
+            Some(OsString::from("bat --paging=always")),
+            Path::new("/tmp/file.txt"),
+        );
+        assert_eq!(
+            program_and_args(&command),
+            (
+                OsStr::new("bat"),
+                vec![OsStr::new("--paging=always"), OsStr::new("/tmp/file.txt")]
+            )
+        );
+    }
+
+    #[test]
+    fn editor_defaults_to_vi() {
+        let command = editor_command(None, Path::new("/tmp/file.txt"));
+        assert_eq!(
+            program_and_args(&command),
+            (OsStr::new("vi"), vec![OsStr::new("/tmp/file.txt")])
+        );
+    }
+
+    #[test]
+    fn editor_line_argument_suits_the_editor() {
+        let command = editor_command_at_line(
+            Some(OsString::from("nvim -R")),
+            Path::new("/tmp/file.txt"),
+            42,
+            None,
+        );
+        assert_eq!(
+            program_and_args(&command),
+            (
+                OsStr::new("nvim"),
+                vec![
+                    OsStr::new("-R"),
+                    OsStr::new("+42"),
+                    OsStr::new("/tmp/file.txt")
+                ]
+            )
+        );
+
+        let command = editor_command_at_line(
+            Some(OsString::from("/usr/bin/code --wait")),
+            Path::new("/tmp/file.txt"),
+            42,
+            None,
+        );
+        assert_eq!(
+            program_and_args(&command),
+            (
+                OsStr::new("/usr/bin/code"),
+                vec![
+                    OsStr::new("--wait"),
+                    OsStr::new("--goto"),
+                    OsStr::new("/tmp/file.txt:42")
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn configured_line_format_overrides_the_guess() {
+        let command = editor_command_at_line(
+            Some(OsString::from("my-editor")),
+            Path::new("/tmp/file.txt"),
+            7,
+            Some(EditorLineFormat::LineFlag),
+        );
+        assert_eq!(
+            program_and_args(&command),
+            (
+                OsStr::new("my-editor"),
+                vec![
+                    OsStr::new("--line"),
+                    OsStr::new("7"),
+                    OsStr::new("/tmp/file.txt")
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn macos_reveals_the_entry_in_finder() {
+        let command = reveal_command(Path::new("/tmp/dir/file.txt"), Platform::MacOs);
+        assert_eq!(
+            program_and_args(&command),
+            (
+                OsStr::new("open"),
+                vec![OsStr::new("-R"), OsStr::new("/tmp/dir/file.txt")]
+            )
+        );
+    }
+
+    #[test]
+    fn windows_selects_the_entry_in_explorer() {
+        let command = reveal_command(Path::new("/tmp/dir/file.txt"), Platform::Windows);
+        assert_eq!(
+            program_and_args(&command),
+            (
+                OsStr::new("explorer"),
+                vec![OsStr::new("/select,\"/tmp/dir/file.txt\"")]
+            )
+        );
+    }
+
+    #[test]
+    fn other_platforms_select_the_entry_or_open_the_containing_directory() {
+        let command = reveal_command(Path::new("/tmp/dir/file.txt"), Platform::Other);
+        let (program, args) = program_and_args(&command);
+        assert_eq!(program, OsStr::new("sh"));
+        assert!(
+            args[1]
+                .to_str()
+                .unwrap()
+                .contains(r#"nautilus --select "$1""#)
+        );
+        assert!(args[1].to_str().unwrap().contains(r#"xdg-open "$2""#));
+        assert_eq!(
+            args[2..],
+            [
+                OsStr::new("sh"),
+                OsStr::new("/tmp/dir/file.txt"),
+                OsStr::new("/tmp/dir")
+            ]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::config::{config_dir, home_dir};
+
+/// Favorite directories, persisted one path per line
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Favorites {
+    paths: Vec<PathBuf>,
+    storage_path: Option<PathBuf>,
+}
+
+impl Favorites {
+    pub fn load_default() -> Self {
+        match config_dir() {
+            Some(config_dir) => Self::load(config_dir.join("favorites")),
+            None => Self::default(),
+        }
+    }
+
+    /// Loads the favorites stored at `storage_path`, starting with the home and downloads
+    /// directories if nothing has been stored yet
+    pub fn load(storage_path: PathBuf) -> Self {
+        let paths = match fs::read_to_string(&storage_path) {
+            Ok(contents) => contents
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+            Err(_) => home_dir()
+                .map(|home| vec![home.join("Downloads"), home])
+                .unwrap_or_default()
+                .into_iter()
+                .rev()
+                .filter(|path| path.is_dir())
+                .collect(),
+        };
+
+        Favorites {
+            paths,
+            storage_path: Some(storage_path),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(storage_path) = &self.storage_path else {
+            return Ok(());
+        };
+        if let Some(parent) = storage_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let contents: String = self
+            .paths
+            .iter()
+            .filter_map(|path| path.to_str())
+            .map(|path| format!("{path}\n"))
+            .collect();
+        fs::write(storage_path, contents)
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Adds `path` if it isn't already a favorite, returning whether it was added
+    pub fn add(&mut self, path: &Path) -> bool {
+        if self.paths.iter().any(|favorite| favorite == path) {
+            false
+        } else {
+            self.paths.push(path.to_path_buf());
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn adding_a_favorite_twice_only_stores_it_once() {
+        let mut favorites = Favorites::default();
+
+        assert!(favorites.add(Path::new("/some/dir")));
+        assert!(!favorites.add(Path::new("/some/dir")));
+        assert_eq!(favorites.paths(), [PathBuf::from("/some/dir")]);
+    }
+
+    #[test]
+    fn favorites_persist_between_loads() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let storage_path = tmp_dir.path().join("nested").join("favorites");
+
+        let mut favorites = Favorites {
+            paths: vec![],
+            storage_path: Some(storage_path.clone()),
+        };
+        favorites.add(Path::new("/some/dir"));
+        favorites.add(Path::new("/another/dir"));
+        favorites.save().unwrap();
+
+        let loaded_favorites = Favorites::load(storage_path);
+        assert_eq!(loaded_favorites, favorites);
+    }
+}
@@ -0,0 +1,692 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::human_size::{SizeUnits, human_size};
+
+/// The total size of `paths`, including everything inside directories
+pub fn total_size(paths: &[PathBuf]) -> u64 {
+    paths.iter().map(|path| size_of(path)).sum()
+}
+
+fn size_of(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+
+    if metadata.is_dir() {
+        fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| Some(size_of(&entry.ok()?.path())))
+                    .sum()
+            })
+            .unwrap_or(0)
+    } else {
+        metadata.len()
+    }
+}
+
+/// The number of bytes available to the current user on the filesystem holding `path`
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> io::Result<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `c_path` is a valid nul terminated string and `stats` is a valid statvfs to write into
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+pub fn available_space(_path: &Path) -> io::Result<u64> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+/// A warning to show instead of copying, if `needed` bytes won't fit in `available`
+pub fn insufficient_space_warning(needed: u64, available: u64, units: SizeUnits) -> Option<String> {
+    (needed > available).then(|| {
+        format!(
+            "Not enough space: need {}, have {}",
+            human_size(needed, units),
+            human_size(available, units)
+        )
+    })
+}
+
+/// Copies a file, or a directory and everything in it, to `destination`, which can't be inside
+/// the directory as the copy would never stop growing. Symlinks are copied as links, pointing
+/// wherever the originals do
+pub fn copy_recursively(source: &Path, destination: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    if metadata.is_dir() {
+        if destination.starts_with(source) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "can't copy a directory inside itself",
+            ));
+        }
+        fs::create_dir(destination)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else if destination.symlink_metadata().is_ok() {
+        Err(io::Error::from(io::ErrorKind::AlreadyExists))
+    } else if metadata.is_symlink() {
+        copy_symlink(source, destination)
+    } else {
+        fs::copy(source, destination).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, destination: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, destination)
+}
+
+#[cfg(windows)]
+fn copy_symlink(source: &Path, destination: &Path) -> io::Result<()> {
+    use std::os::windows::fs::{symlink_dir, symlink_file};
+
+    let target = fs::read_link(source)?;
+    // Windows links are made for a file or a directory, so follow the original to see which
+    if source.is_dir() {
+        symlink_dir(target, destination)
+    } else {
+        symlink_file(target, destination)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn copy_symlink(_source: &Path, _destination: &Path) -> io::Result<()> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+/// Where a copy of `source` goes in `dir`: under its own name if that is free, or else with
+/// ` (copy)` (then ` (copy 2)`, and so on) added before its extension
+pub fn copy_destination(source: &Path, dir: &Path) -> Option<PathBuf> {
+    let file_name = source.file_name()?;
+    let destination = dir.join(file_name);
+    if destination.symlink_metadata().is_err() {
+        return Some(destination);
+    }
+
+    let name = Path::new(file_name);
+    let stem = name.file_stem()?.to_string_lossy();
+    let extension = match name.extension() {
+        Some(extension) if !source.is_dir() => format!(".{}", extension.to_string_lossy()),
+        _ => String::new(),
+    };
+    let stem = if extension.is_empty() {
+        file_name.to_string_lossy()
+    } else {
+        stem
+    };
+    (1..)
+        .map(|number| {
+            let suffix = if number == 1 {
+                " (copy)".to_string()
+            } else {
+                format!(" (copy {number})")
+            };
+            dir.join(format!("{stem}{suffix}{extension}"))
+        })
+        .find(|destination| destination.symlink_metadata().is_err())
+}
+
+/// The id of the device holding `path`, which differs on either side of a mount point
+pub fn device_id(path: &Path) -> io::Result<u64> {
+    device_of(&fs::symlink_metadata(path)?)
+}
+
+#[cfg(unix)]
+fn device_of(metadata: &fs::Metadata) -> io::Result<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Ok(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device_of(_metadata: &fs::Metadata) -> io::Result<u64> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+/// How many hard links the entry with `metadata` has, when it is a file with more than one
+#[cfg(unix)]
+pub fn hard_link_count(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.nlink()).filter(|&links| metadata.is_file() && links > 1)
+}
+
+#[cfg(not(unix))]
+pub fn hard_link_count(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Whether another filesystem is mounted at the directory with `metadata`, which then has a
+/// different device to `parent_device`
+pub fn is_mount_point(metadata: &fs::Metadata, parent_device: u64) -> bool {
+    metadata.is_dir() && device_of(metadata).is_ok_and(|device| device != parent_device)
+}
+
+/// How an entry is moved, as renaming only works within one filesystem
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveStrategy {
+    Rename,
+    CopyThenDelete,
+}
+
+impl MoveStrategy {
+    /// Compares the devices `device_id` gives for each side, renaming when either is unknown
+    pub fn for_move(
+        source: &Path,
+        destination: &Path,
+        device_id: impl Fn(&Path) -> io::Result<u64>,
+    ) -> Self {
+        let destination_dir = destination.parent().unwrap_or(destination);
+        match (device_id(source), device_id(destination_dir)) {
+            (Ok(source_device), Ok(destination_device)) if source_device != destination_device => {
+                MoveStrategy::CopyThenDelete
+            }
+            _ => MoveStrategy::Rename,
+        }
+    }
+}
+
+/// Moves a file, or a directory and everything in it, to `destination`, copying then deleting it
+/// when `destination` is on another device
+pub fn move_path(source: &Path, destination: &Path) -> io::Result<()> {
+    // A dangling symlink is still in the way, though `exists` follows it to nothing
+    if destination.symlink_metadata().is_ok() {
+        return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+    }
+
+    move_with(
+        source,
+        destination,
+        MoveStrategy::for_move(source, destination, device_id),
+    )
+}
+
+/// Moves `source` the way `strategy` says, falling back to copying then deleting when a rename
+/// turns out to cross devices after all. A copy that fails part way is removed again
+fn move_with(source: &Path, destination: &Path, strategy: MoveStrategy) -> io::Result<()> {
+    match strategy {
+        MoveStrategy::Rename => match fs::rename(source, destination) {
+            Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+                move_with(source, destination, MoveStrategy::CopyThenDelete)
+            }
+            result => result,
+        },
+        MoveStrategy::CopyThenDelete => {
+            if destination.symlink_metadata().is_ok() {
+                return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+            }
+            if let Err(err) = copy_recursively(source, destination) {
+                if destination.symlink_metadata().is_ok() {
+                    let _ = remove_recursively(destination);
+                }
+                return Err(err);
+            }
+            remove_recursively(source)
+        }
+    }
+}
+
+/// Exchanges the places of two entries, moving `first` aside under a temporary name while
+/// `second` takes its place. Either move may cross devices, as with `move_path`, and whatever
+/// went wrong part way through is undone before the error is returned
+pub fn swap_paths(first: &Path, second: &Path) -> io::Result<()> {
+    let (resolved_first, resolved_second) = (resolve_parents(first), resolve_parents(second));
+    if resolved_first.starts_with(&resolved_second) || resolved_second.starts_with(&resolved_first)
+    {
+        return Err(io::Error::from(io::ErrorKind::InvalidInput));
+    }
+    let file_name = first
+        .file_name()
+        .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+    let parent = first.parent().unwrap_or(Path::new(""));
+    let temporary = (0..)
+        .map(|number| parent.join(format!(".{}.swap{number}", file_name.to_string_lossy())))
+        .find(|path| path.symlink_metadata().is_err())
+        .ok_or_else(|| io::Error::from(io::ErrorKind::AlreadyExists))?;
+
+    fs::rename(first, &temporary)?;
+    if let Err(err) = move_path(second, first) {
+        return Err(match fs::rename(&temporary, first) {
+            Ok(()) => err,
+            Err(undo_err) => swap_undo_error(err, undo_err, first, &temporary),
+        });
+    }
+    if let Err(err) = move_path(&temporary, second) {
+        let undone = move_path(first, second).and_then(|()| fs::rename(&temporary, first));
+        return Err(match undone {
+            Ok(()) => err,
+            Err(undo_err) => swap_undo_error(err, undo_err, first, &temporary),
+        });
+    }
+    Ok(())
+}
+
+/// The error from a swap that couldn't be undone either, saying where `first` was left
+fn swap_undo_error(
+    err: io::Error,
+    undo_err: io::Error,
+    first: &Path,
+    temporary: &Path,
+) -> io::Error {
+    io::Error::new(
+        err.kind(),
+        format!(
+            "{err}, and undoing the swap failed ({undo_err}), leaving {} at {}",
+            first.display(),
+            temporary.display()
+        ),
+    )
+}
+
+/// `path` with the directories leading to it resolved, so two ways of reaching an entry compare
+/// equal. The entry itself is kept, as a symlink is moved rather than what it points to
+pub fn resolve_parents(path: &Path) -> PathBuf {
+    let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return path.to_path_buf();
+    };
+    let parent = if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        parent
+    };
+    fs::canonicalize(parent).map_or_else(|_| path.to_path_buf(), |parent| parent.join(file_name))
+}
+
+/// Deletes a file, or a directory and everything in it
+pub fn remove_recursively(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// The start of a text file, and whether the rest was left out for being over the cap
+#[derive(Debug, PartialEq)]
+pub struct CappedText {
+    pub text: String,
+    pub truncated: bool,
+}
+
+/// Reads a whole text file, up to `max_bytes`, refusing anything that looks binary
+pub fn read_text_capped(path: &Path, max_bytes: u64) -> io::Result<CappedText> {
+    let mut bytes = Vec::new();
+    fs::File::open(path)?
+        .take(max_bytes + 1)
+        .read_to_end(&mut bytes)?;
+
+    let truncated = bytes.len() as u64 > max_bytes;
+    bytes.truncate(max_bytes as usize);
+
+    let not_text = || io::Error::new(io::ErrorKind::InvalidData, "not a text file");
+    if bytes.contains(&0) {
+        return Err(not_text());
+    }
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        // The cap can fall part way through a character
+        Err(err) if truncated && err.utf8_error().error_len().is_none() => {
+            let valid_up_to = err.utf8_error().valid_up_to();
+            let mut bytes = err.into_bytes();
+            bytes.truncate(valid_up_to);
+            String::from_utf8(bytes).map_err(|_| not_text())?
+        }
+        Err(_) => return Err(not_text()),
+    };
+
+    Ok(CappedText { text, truncated })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{File, create_dir};
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn copies_are_named_after_the_original_when_it_is_taken() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let notes_path = tmp_dir.path().join("notes.txt");
+        let docs_path = tmp_dir.path().join("docs.d");
+        File::create(&notes_path).unwrap();
+        create_dir(&docs_path).unwrap();
+        let other_dir = TempDir::new("other_dir").unwrap();
+
+        assert_eq!(
+            copy_destination(&notes_path, other_dir.path()),
+            Some(other_dir.path().join("notes.txt"))
+        );
+        assert_eq!(
+            copy_destination(&notes_path, tmp_dir.path()),
+            Some(tmp_dir.path().join("notes (copy).txt"))
+        );
+        File::create(tmp_dir.path().join("notes (copy).txt")).unwrap();
+        assert_eq!(
+            copy_destination(&notes_path, tmp_dir.path()),
+            Some(tmp_dir.path().join("notes (copy 2).txt"))
+        );
+        // A directory's name is kept whole, whatever dots it has
+        assert_eq!(
+            copy_destination(&docs_path, tmp_dir.path()),
+            Some(tmp_dir.path().join("docs.d (copy)"))
+        );
+    }
+
+    #[test]
+    fn total_size_includes_directory_contents() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        fs::write(&file_path, "12345").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        create_dir(&nested_dir_path).unwrap();
+        fs::write(nested_dir_path.join("nested.txt"), "123").unwrap();
+
+        assert_eq!(total_size(&[file_path, nested_dir_path]), 8);
+    }
+
+    #[test]
+    fn warning_only_when_needed_exceeds_available() {
+        assert_eq!(
+            insufficient_space_warning(1024, 1024, SizeUnits::Binary),
+            None
+        );
+        assert_eq!(
+            insufficient_space_warning(4_509_715_660, 1_181_116_006, SizeUnits::Binary),
+            Some("Not enough space: need 4.2G, have 1.1G".to_string())
+        );
+    }
+
+    #[test]
+    fn directories_are_copied_recursively() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source_path = tmp_dir.path().join("source");
+        create_dir(&source_path).unwrap();
+        create_dir(source_path.join("nested_dir")).unwrap();
+        fs::write(source_path.join("nested_dir").join("file.txt"), "hello").unwrap();
+
+        let destination_path = tmp_dir.path().join("destination");
+        copy_recursively(&source_path, &destination_path).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(destination_path.join("nested_dir").join("file.txt")).unwrap(),
+            "hello"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn moves_across_devices_copy_then_delete() {
+        let devices = |path: &Path| match path.to_str() {
+            Some("/home/user/file.txt" | "/home/user/other") => Ok(1),
+            Some("/mnt/usb") => Ok(2),
+            _ => Err(io::Error::from(io::ErrorKind::NotFound)),
+        };
+        let source = Path::new("/home/user/file.txt");
+
+        assert_eq!(
+            MoveStrategy::for_move(source, Path::new("/home/user/other/file.txt"), devices),
+            MoveStrategy::Rename
+        );
+        assert_eq!(
+            MoveStrategy::for_move(source, Path::new("/mnt/usb/file.txt"), devices),
+            MoveStrategy::CopyThenDelete
+        );
+        // Falls back to renaming, which fails cleanly if it does cross devices
+        assert_eq!(
+            MoveStrategy::for_move(source, Path::new("/unknown/file.txt"), devices),
+            MoveStrategy::Rename
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn mount_points_have_a_different_device_to_their_parent() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let nested_dir_path = tmp_dir.path().join("nested_dir");
+        create_dir(&nested_dir_path).unwrap();
+
+        assert_eq!(
+            device_id(&nested_dir_path).unwrap(),
+            device_id(tmp_dir.path()).unwrap()
+        );
+        let parent_device = device_id(tmp_dir.path()).unwrap();
+        assert!(!is_mount_point(
+            &fs::symlink_metadata(&nested_dir_path).unwrap(),
+            parent_device
+        ));
+        assert!(is_mount_point(
+            &fs::symlink_metadata(&nested_dir_path).unwrap(),
+            parent_device + 1
+        ));
+    }
+
+    #[test]
+    fn moved_entries_leave_their_old_place() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source_path = tmp_dir.path().join("file.txt");
+        fs::write(&source_path, "hello").unwrap();
+        let destination_dir_path = tmp_dir.path().join("destination");
+        create_dir(&destination_dir_path).unwrap();
+
+        let destination_path = destination_dir_path.join("file.txt");
+        move_path(&source_path, &destination_path).unwrap();
+
+        assert!(!source_path.exists());
+        assert_eq!(fs::read_to_string(&destination_path).unwrap(), "hello");
+
+        File::create(&source_path).unwrap();
+        assert_eq!(
+            move_path(&source_path, &destination_path)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::AlreadyExists
+        );
+    }
+
+    #[test]
+    fn text_is_read_in_full_up_to_the_cap() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        fs::write(&file_path, "line 1\nline 2\n").unwrap();
+
+        assert_eq!(
+            read_text_capped(&file_path, 1024).unwrap(),
+            CappedText {
+                text: "line 1\nline 2\n".to_string(),
+                truncated: false
+            }
+        );
+        assert_eq!(
+            read_text_capped(&file_path, 6).unwrap(),
+            CappedText {
+                text: "line 1".to_string(),
+                truncated: true
+            }
+        );
+    }
+
+    #[test]
+    fn cap_does_not_split_a_character() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.txt");
+        fs::write(&file_path, "a写真").unwrap();
+
+        assert_eq!(
+            read_text_capped(&file_path, 5).unwrap(),
+            CappedText {
+                text: "a写".to_string(),
+                truncated: true
+            }
+        );
+    }
+
+    #[test]
+    fn binary_files_are_refused() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let file_path = tmp_dir.path().join("file.bin");
+        fs::write(&file_path, [0x7f, b'E', b'L', b'F', 0, 1]).unwrap();
+
+        assert_eq!(
+            read_text_capped(&file_path, 1024).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn copy_then_delete_moves_everything_and_leaves_nothing_behind() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source = tmp_dir.path().join("photos");
+        create_dir(&source).unwrap();
+        create_dir(source.join("2024")).unwrap();
+        fs::write(source.join("2024").join("beach.jpg"), "beach").unwrap();
+        let destination = tmp_dir.path().join("archive");
+
+        move_with(&source, &destination, MoveStrategy::CopyThenDelete).unwrap();
+
+        assert!(!source.exists());
+        assert_eq!(
+            fs::read_to_string(destination.join("2024").join("beach.jpg")).unwrap(),
+            "beach"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_moved_as_links() {
+        use std::os::unix::fs::symlink;
+
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source = tmp_dir.path().join("links");
+        create_dir(&source).unwrap();
+        create_dir(tmp_dir.path().join("target_dir")).unwrap();
+        symlink(tmp_dir.path().join("target_dir"), source.join("to_dir")).unwrap();
+        symlink("missing.txt", source.join("dangling")).unwrap();
+        let destination = tmp_dir.path().join("moved");
+
+        move_with(&source, &destination, MoveStrategy::CopyThenDelete).unwrap();
+
+        assert!(!source.exists());
+        assert_eq!(
+            fs::read_link(destination.join("to_dir")).unwrap(),
+            tmp_dir.path().join("target_dir")
+        );
+        assert_eq!(
+            fs::read_link(destination.join("dangling")).unwrap(),
+            Path::new("missing.txt")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dangling_symlink_at_the_destination_is_not_overwritten() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source = tmp_dir.path().join("file.txt");
+        fs::write(&source, "hello").unwrap();
+        let destination = tmp_dir.path().join("link");
+        std::os::unix::fs::symlink("missing.txt", &destination).unwrap();
+
+        assert_eq!(
+            move_path(&source, &destination).unwrap_err().kind(),
+            io::ErrorKind::AlreadyExists
+        );
+        assert!(source.exists());
+        assert!(destination.symlink_metadata().unwrap().is_symlink());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_copy_then_delete_leaves_no_partial_copy() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source = tmp_dir.path().join("source");
+        create_dir(&source).unwrap();
+        fs::write(source.join("file.txt"), "hello").unwrap();
+        // A socket can't be opened to copy its contents
+        let _listener = std::os::unix::net::UnixListener::bind(source.join("socket")).unwrap();
+        let destination = tmp_dir.path().join("destination");
+
+        assert!(move_with(&source, &destination, MoveStrategy::CopyThenDelete).is_err());
+        assert!(destination.symlink_metadata().is_err());
+        assert_eq!(
+            fs::read_to_string(source.join("file.txt")).unwrap(),
+            "hello"
+        );
+    }
+
+    #[test]
+    fn swapped_entries_take_each_others_places() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let first = tmp_dir.path().join("first.txt");
+        let second_dir = tmp_dir.path().join("second");
+        create_dir(&second_dir).unwrap();
+        let second = second_dir.join("second.txt");
+        fs::write(&first, "first").unwrap();
+        fs::write(&second, "second").unwrap();
+
+        swap_paths(&first, &second).unwrap();
+
+        assert_eq!(fs::read_to_string(&first).unwrap(), "second");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "first");
+        assert_eq!(fs::read_dir(tmp_dir.path()).unwrap().count(), 2);
+
+        // An entry can't trade places with itself or with something inside it
+        assert!(swap_paths(&first, &first).is_err());
+        assert!(swap_paths(&second_dir, &second).is_err());
+        assert!(swap_paths(&second_dir.join("..").join("first.txt"), &first).is_err());
+        assert_eq!(fs::read_to_string(&first).unwrap(), "second");
+    }
+
+    #[test]
+    fn copying_a_file_does_not_overwrite() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source_path = tmp_dir.path().join("source.txt");
+        let _source_file = File::create(&source_path).unwrap();
+        let destination_path = tmp_dir.path().join("destination.txt");
+        fs::write(&destination_path, "keep me").unwrap();
+
+        assert_eq!(
+            copy_recursively(&source_path, &destination_path)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::AlreadyExists
+        );
+        assert_eq!(fs::read_to_string(&destination_path).unwrap(), "keep me");
+    }
+
+    #[test]
+    fn directory_is_not_copied_inside_itself() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let source_path = tmp_dir.path().join("photos");
+        create_dir(&source_path).unwrap();
+        create_dir(source_path.join("2024")).unwrap();
+
+        for destination_path in [
+            source_path.join("photos"),
+            source_path.join("2024").join("photos"),
+        ] {
+            assert_eq!(
+                copy_recursively(&source_path, &destination_path)
+                    .unwrap_err()
+                    .kind(),
+                io::ErrorKind::InvalidInput
+            );
+            assert!(!destination_path.exists());
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use ignore::gitignore::Gitignore;
+
+/// Decides which entries a repository's `.gitignore` files (including nested ones) ignore
+#[derive(Debug)]
+pub struct GitignoreFilter {
+    /// The deepest directory's rules come first, as they take precedence
+    gitignores: Vec<Gitignore>,
+}
+
+impl GitignoreFilter {
+    /// Reads every `.gitignore` from `dir_path` up to the root of its repository, or returns `None`
+    /// when it isn't inside one
+    pub fn for_dir(dir_path: &Path) -> Option<Self> {
+        let repo_root = dir_path
+            .ancestors()
+            .find(|ancestor| ancestor.join(".git").exists())?;
+
+        let gitignores = dir_path
+            .ancestors()
+            .take_while(|ancestor| ancestor.starts_with(repo_root))
+            .map(|ancestor| ancestor.join(".gitignore"))
+            .filter(|gitignore_path| gitignore_path.is_file())
+            .map(|gitignore_path| Gitignore::new(gitignore_path).0)
+            .collect();
+
+        Some(GitignoreFilter { gitignores })
+    }
+
+    /// Whether `path`, or a directory it's inside, is ignored
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        for gitignore in &self.gitignores {
+            let matched = gitignore.matched_path_or_any_parents(path, is_dir);
+            if matched.is_ignore() {
+                return true;
+            }
+            // A `!pattern` re-includes the path, overriding any shallower rules
+            if matched.is_whitelist() {
+                return false;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File, create_dir};
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn nested_gitignores_are_combined() {
+        let tmp_dir = TempDir::new("repo").unwrap();
+        create_dir(tmp_dir.path().join(".git")).unwrap();
+        fs::write(tmp_dir.path().join(".gitignore"), "*.log\ntarget/\n").unwrap();
+        let src_path = tmp_dir.path().join("src");
+        create_dir(&src_path).unwrap();
+        fs::write(src_path.join(".gitignore"), "generated.rs\n!keep.log\n").unwrap();
+        create_dir(src_path.join("target")).unwrap();
+        for file_name in ["main.rs", "generated.rs", "debug.log", "keep.log"] {
+            File::create(src_path.join(file_name)).unwrap();
+        }
+
+        let gitignore_filter = GitignoreFilter::for_dir(&src_path).unwrap();
+
+        assert!(!gitignore_filter.is_ignored(&src_path.join("main.rs")));
+        assert!(gitignore_filter.is_ignored(&src_path.join("generated.rs")));
+        assert!(gitignore_filter.is_ignored(&src_path.join("debug.log")));
+        assert!(!gitignore_filter.is_ignored(&src_path.join("keep.log")));
+        assert!(gitignore_filter.is_ignored(&src_path.join("target")));
+    }
+
+    #[test]
+    fn entries_inside_an_ignored_dir_are_ignored() {
+        let tmp_dir = TempDir::new("repo").unwrap();
+        create_dir(tmp_dir.path().join(".git")).unwrap();
+        fs::write(tmp_dir.path().join(".gitignore"), "target/\n").unwrap();
+        let target_path = tmp_dir.path().join("target");
+        create_dir(&target_path).unwrap();
+        File::create(target_path.join("build.log")).unwrap();
+
+        let gitignore_filter = GitignoreFilter::for_dir(&target_path).unwrap();
+
+        assert!(gitignore_filter.is_ignored(&target_path.join("build.log")));
+    }
+
+    #[test]
+    fn no_filter_outside_a_repo() {
+        let tmp_dir = TempDir::new("not_a_repo").unwrap();
+
+        assert!(GitignoreFilter::for_dir(tmp_dir.path()).is_none());
+    }
+}
@@ -0,0 +1,273 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::key_handlers::KeyHandlers;
+
+/// Listed for a registered handler that wasn't given a description
+pub const CUSTOM_ACTION_DESCRIPTION: &str = "Custom action";
+
+const NONE: KeyModifiers = KeyModifiers::NONE;
+const CONTROL: KeyModifiers = KeyModifiers::CONTROL;
+const ALT: KeyModifiers = KeyModifiers::ALT;
+const SHIFT: KeyModifiers = KeyModifiers::SHIFT;
+
+/// The keys handled in the listing, in the order the help overlay lists them
+const BUILT_IN_BINDINGS: &[(KeyCode, KeyModifiers, &str)] = &[
+    (KeyCode::Up, NONE, "Move up"),
+    (KeyCode::Down, NONE, "Move down"),
+    (KeyCode::Left, NONE, "Move to the previous column"),
+    (KeyCode::Right, NONE, "Move to the next column"),
+    (
+        KeyCode::Up,
+        SHIFT,
+        "Mark the range up from where shift was first held",
+    ),
+    (
+        KeyCode::Down,
+        SHIFT,
+        "Mark the range down from where shift was first held",
+    ),
+    (KeyCode::Enter, NONE, "Open the entry under the cursor"),
+    (KeyCode::Backspace, NONE, "Go to the parent directory"),
+    (KeyCode::Char('['), NONE, "Go back"),
+    (KeyCode::Left, ALT, "Go back"),
+    (KeyCode::Char(']'), NONE, "Go forward"),
+    (KeyCode::Right, ALT, "Go forward"),
+    (
+        KeyCode::Char('S'),
+        NONE,
+        "Save the directories visited as a cd script",
+    ),
+    (
+        KeyCode::Char(':'),
+        NONE,
+        "Descend into a subdirectory by name",
+    ),
+    (
+        KeyCode::Char('H'),
+        NONE,
+        "List recently visited directories",
+    ),
+    (KeyCode::Char('c'), NONE, "Preview the file"),
+    (KeyCode::Char('Y'), NONE, "Copy the visible preview"),
+    (KeyCode::Char('a'), NONE, "Toggle auto-preview"),
+    (KeyCode::Char('i'), NONE, "Show details in the right pane"),
+    (
+        KeyCode::Char('G'),
+        NONE,
+        "Show the file's unstaged git changes",
+    ),
+    (KeyCode::Char('j'), NONE, "Scroll the preview down"),
+    (KeyCode::Char('k'), NONE, "Scroll the preview up"),
+    (KeyCode::Char('m'), NONE, "Read more of a long preview"),
+    (KeyCode::Right, SHIFT, "Scroll the preview right"),
+    (KeyCode::Left, SHIFT, "Scroll the preview left"),
+    (KeyCode::Tab, NONE, "Cycle what the right pane shows"),
+    (
+        KeyCode::Char(' '),
+        NONE,
+        "Quick Look, or mark while multi-selecting",
+    ),
+    (KeyCode::Char('e'), NONE, "Open the file in your editor"),
+    (KeyCode::Char('v'), NONE, "Open the file in your pager"),
+    (KeyCode::Char('s'), NONE, "Sort by name, size or modified"),
+    (
+        KeyCode::Char('g'),
+        NONE,
+        "Toggle listing directories before files",
+    ),
+    (
+        KeyCode::Char('o'),
+        NONE,
+        "Expand or collapse the directory in place",
+    ),
+    (
+        KeyCode::Char('O'),
+        NONE,
+        "Show the entry in the file manager",
+    ),
+    (KeyCode::Char('w'), NONE, "Toggle manual column widths"),
+    (KeyCode::Char('+'), NONE, "Widen the column"),
+    (KeyCode::Char('-'), NONE, "Narrow the column"),
+    (KeyCode::Char('z'), NONE, "Toggle the summarized view"),
+    (KeyCode::Char('#'), NONE, "Toggle directory item counts"),
+    (
+        KeyCode::Char('u'),
+        NONE,
+        "Count everything inside the directory",
+    ),
+    (KeyCode::Char('.'), NONE, "Toggle hiding dotfiles"),
+    (KeyCode::Char('I'), NONE, "Toggle hiding gitignored entries"),
+    (KeyCode::Char('F'), NONE, "Add the directory to favorites"),
+    (KeyCode::Char('b'), NONE, "Toggle the favorites sidebar"),
+    (KeyCode::Char('*'), NONE, "Star the file"),
+    (
+        KeyCode::Char('f'),
+        NONE,
+        "Toggle listing only starred files",
+    ),
+    (
+        KeyCode::Char('/'),
+        NONE,
+        "Fuzzy search and narrow the listing",
+    ),
+    (
+        KeyCode::Char('n'),
+        NONE,
+        "Next match while searching, otherwise create a new file",
+    ),
+    (KeyCode::Char('R'), NONE, "Rename the entry"),
+    (
+        KeyCode::Char('X'),
+        NONE,
+        "Swap the yanked entry with the selected one",
+    ),
+    (KeyCode::Char('x'), NONE, "Cut the entry, for p to move it"),
+    (KeyCode::Char('N'), NONE, "Previous match while searching"),
+    (KeyCode::Char('V'), NONE, "Toggle multi-selecting"),
+    (KeyCode::Char('y'), NONE, "Yank the entry"),
+    (
+        KeyCode::Char('p'),
+        NONE,
+        "Paste a copy of the yanked entry here",
+    ),
+    (KeyCode::Char('P'), NONE, "Copy the marked entries here"),
+    (KeyCode::Char('M'), NONE, "Move the marked entries here"),
+    (KeyCode::Char('B'), NONE, "Rename the marked entries"),
+    (KeyCode::Char('C'), NONE, "Toggle collecting"),
+    (KeyCode::Char('E'), NONE, "Copy the collected files here"),
+    (KeyCode::Char('d'), NONE, "Delete the entry"),
+    (KeyCode::Char('y'), CONTROL, "Copy the file's contents"),
+    (KeyCode::Char('t'), CONTROL, "Copy the directory as a tree"),
+    (KeyCode::Char('r'), CONTROL, "Copy the relative path"),
+    (
+        KeyCode::Char('d'),
+        CONTROL,
+        "Copy the current directory's path",
+    ),
+    (KeyCode::Char('l'), CONTROL, "Copy the listing as shown"),
+    (KeyCode::Char('D'), NONE, "Show the layout of the listing"),
+    (KeyCode::Char('?'), NONE, "Show this help"),
+    (KeyCode::Char('q'), NONE, "Quit"),
+    (KeyCode::Char('c'), CONTROL, "Quit without asking"),
+];
+
+/// One `key  description` line per binding, with registered handlers in place of the built-in
+/// actions they replace and any other handlers after them
+pub fn help_lines(key_handlers: &KeyHandlers) -> Vec<String> {
+    let mut bindings: Vec<(String, &str)> = BUILT_IN_BINDINGS
+        .iter()
+        .map(|&(code, modifiers, description)| {
+            let key_event = KeyEvent::new(code, modifiers);
+            // `ctrl+c` quits before any handler is looked up
+            let description = key_handlers
+                .description(&key_event)
+                .filter(|_| key_event != KeyEvent::new(KeyCode::Char('c'), CONTROL))
+                .unwrap_or(description);
+            (key_label(&key_event), description)
+        })
+        .collect();
+
+    let mut added_bindings: Vec<(String, &str)> = key_handlers
+        .descriptions()
+        .filter(|(key_event, _)| {
+            !BUILT_IN_BINDINGS.iter().any(|&(code, modifiers, _)| {
+                key_event.code == code && key_event.modifiers == modifiers
+            })
+        })
+        .map(|(key_event, description)| (key_label(key_event), description))
+        .collect();
+    added_bindings.sort();
+    bindings.extend(added_bindings);
+
+    let key_width = bindings
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or_default();
+    bindings
+        .into_iter()
+        .map(|(key, description)| format!("{key:<key_width$}  {description}"))
+        .collect()
+}
+
+/// Names a key the way the README does, such as `ctrl+r` or `alt+left`
+pub fn key_label(key_event: &KeyEvent) -> String {
+    let mut label = String::new();
+    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+        label.push_str("ctrl+");
+    }
+    if key_event.modifiers.contains(KeyModifiers::ALT) {
+        label.push_str("alt+");
+    }
+    // Shifted characters arrive already in upper case
+    if key_event.modifiers.contains(KeyModifiers::SHIFT)
+        && !matches!(key_event.code, KeyCode::Char(_) | KeyCode::BackTab)
+    {
+        label.push_str("shift+");
+    }
+
+    match key_event.code {
+        KeyCode::Char(' ') => label.push_str("space"),
+        KeyCode::Char(char) => label.push(char),
+        KeyCode::F(number) => label.push_str(&format!("f{number}")),
+        KeyCode::BackTab => label.push_str("shift+tab"),
+        code => label.push_str(&format!("{code:?}").to_lowercase()),
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_labelled_with_their_modifiers() {
+        assert_eq!(key_label(&KeyCode::Char('q').into()), "q");
+        assert_eq!(
+            key_label(&KeyEvent::new(KeyCode::Char('r'), CONTROL)),
+            "ctrl+r"
+        );
+        assert_eq!(key_label(&KeyEvent::new(KeyCode::Left, ALT)), "alt+left");
+        assert_eq!(key_label(&KeyCode::Char(' ').into()), "space");
+        assert_eq!(key_label(&KeyCode::PageDown.into()), "pagedown");
+    }
+
+    #[test]
+    fn registered_handlers_replace_or_follow_the_built_in_bindings() {
+        let mut key_handlers = KeyHandlers::default();
+        key_handlers.register(KeyCode::Char('d').into(), "Duplicate", |_| {});
+        key_handlers.register(KeyCode::F(5).into(), "Refresh", |_| {});
+
+        let help_lines = help_lines(&key_handlers);
+
+        let line_for = |key: &str| {
+            help_lines
+                .iter()
+                .find(|line| line.split_whitespace().next() == Some(key))
+                .map(|line| {
+                    line.split_whitespace()
+                        .skip(1)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+        };
+        assert_eq!(line_for("d").as_deref(), Some("Duplicate"));
+        assert_eq!(line_for("f5").as_deref(), Some("Refresh"));
+        assert!(help_lines.last().unwrap().starts_with("f5"));
+        assert_eq!(help_lines.len(), BUILT_IN_BINDINGS.len() + 1);
+    }
+
+    #[test]
+    fn range_marking_is_listed() {
+        let help_lines = help_lines(&KeyHandlers::default());
+
+        for key in ["shift+up", "shift+down"] {
+            assert!(
+                help_lines
+                    .iter()
+                    .any(|line| line.split_whitespace().next() == Some(key)),
+                "{key} isn't listed"
+            );
+        }
+    }
+}
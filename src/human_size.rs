@@ -0,0 +1,111 @@
+const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+
+/// How sizes are written
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SizeUnits {
+    /// Powers of 1024 with single letter units, e.g. `4.2G`
+    #[default]
+    Binary,
+    /// Powers of 1024 with IEC units, e.g. `4.2 GiB`
+    Iec,
+    /// Powers of 1000 with SI units, e.g. `4.5 GB`
+    Si,
+}
+
+impl SizeUnits {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "binary" => Some(SizeUnits::Binary),
+            "iec" => Some(SizeUnits::Iec),
+            "si" => Some(SizeUnits::Si),
+            _ => None,
+        }
+    }
+
+    fn base(self) -> f64 {
+        match self {
+            SizeUnits::Binary | SizeUnits::Iec => 1024.0,
+            SizeUnits::Si => 1000.0,
+        }
+    }
+
+    fn suffix(self, unit: &str) -> String {
+        match self {
+            SizeUnits::Binary => unit.to_string(),
+            SizeUnits::Iec => format!(" {unit}iB"),
+            SizeUnits::Si => format!(" {unit}B"),
+        }
+    }
+}
+
+/// Formats a byte count in the largest unit it makes at least one of, with a single decimal place
+pub fn human_size(bytes: u64, units: SizeUnits) -> String {
+    let base = units.base();
+    if (bytes as f64) < base {
+        return match units {
+            SizeUnits::Binary => format!("{bytes}B"),
+            SizeUnits::Iec | SizeUnits::Si => format!("{bytes} B"),
+        };
+    }
+
+    let mut size = bytes as f64 / base;
+    let mut unit_index = 0;
+    // Compared once rounded, so a size just under the next unit isn't shown as e.g. `1024.0K`
+    while (size * 10.0).round() / 10.0 >= base && unit_index < UNITS.len() - 1 {
+        size /= base;
+        unit_index += 1;
+    }
+    format!("{size:.1}{}", units.suffix(UNITS[unit_index]))
+}
+
+/// Formats a count with commas between groups of three digits, e.g. `1,234,567`
+pub fn format_with_thousands_separator(number: usize) -> String {
+    let digits = number.to_string();
+    let mut formatted = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_are_formatted_with_largest_fitting_unit() {
+        assert_eq!(human_size(0, SizeUnits::Binary), "0B");
+        assert_eq!(human_size(1023, SizeUnits::Binary), "1023B");
+        assert_eq!(human_size(1024, SizeUnits::Binary), "1.0K");
+        assert_eq!(human_size(1536, SizeUnits::Binary), "1.5K");
+        assert_eq!(human_size(4_509_715_660, SizeUnits::Binary), "4.2G");
+    }
+
+    #[test]
+    fn si_and_iec_units_have_their_own_base_and_suffix() {
+        assert_eq!(human_size(999, SizeUnits::Si), "999 B");
+        assert_eq!(human_size(1000, SizeUnits::Si), "1.0 KB");
+        assert_eq!(human_size(1000, SizeUnits::Iec), "1000 B");
+        assert_eq!(human_size(1024, SizeUnits::Iec), "1.0 KiB");
+        assert_eq!(human_size(1024, SizeUnits::Si), "1.0 KB");
+        assert_eq!(human_size(4_509_715_660, SizeUnits::Si), "4.5 GB");
+        assert_eq!(human_size(4_509_715_660, SizeUnits::Iec), "4.2 GiB");
+    }
+
+    #[test]
+    fn sizes_rounding_up_to_the_next_unit_move_into_it() {
+        assert_eq!(human_size(1024 * 1024 - 1, SizeUnits::Binary), "1.0M");
+        assert_eq!(human_size(999_949, SizeUnits::Si), "999.9 KB");
+        assert_eq!(human_size(999_950, SizeUnits::Si), "1.0 MB");
+    }
+
+    #[test]
+    fn thousands_are_separated() {
+        assert_eq!(format_with_thousands_separator(7), "7");
+        assert_eq!(format_with_thousands_separator(1024), "1,024");
+        assert_eq!(format_with_thousands_separator(1234567), "1,234,567");
+    }
+}
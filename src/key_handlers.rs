@@ -0,0 +1,61 @@
+use std::{collections::HashMap, fmt, rc::Rc};
+
+use crossterm::event::KeyEvent;
+
+use crate::components::App;
+
+type KeyHandlerFn = dyn Fn(&mut App);
+
+struct KeyHandler {
+    /// Listed against the key in the help overlay
+    description: String,
+    handler: Rc<KeyHandlerFn>,
+}
+
+/// Actions added by whoever embeds the app, run in place of the built-in handling of their key
+#[derive(Default)]
+pub struct KeyHandlers(HashMap<KeyEvent, KeyHandler>);
+
+impl KeyHandlers {
+    /// Runs `handler` whenever `key_event` is pressed, replacing any handler already registered
+    pub fn register(
+        &mut self,
+        key_event: KeyEvent,
+        description: impl Into<String>,
+        handler: impl Fn(&mut App) + 'static,
+    ) {
+        self.0.insert(
+            key_event,
+            KeyHandler {
+                description: description.into(),
+                handler: Rc::new(handler),
+            },
+        );
+    }
+
+    /// The handler for `key_event`, shared so it can be called while the app is borrowed mutably
+    pub fn get(&self, key_event: &KeyEvent) -> Option<Rc<KeyHandlerFn>> {
+        self.0
+            .get(key_event)
+            .map(|key_handler| Rc::clone(&key_handler.handler))
+    }
+
+    pub fn description(&self, key_event: &KeyEvent) -> Option<&str> {
+        self.0
+            .get(key_event)
+            .map(|key_handler| key_handler.description.as_str())
+    }
+
+    /// Every registered key with its description, in no particular order
+    pub fn descriptions(&self) -> impl Iterator<Item = (&KeyEvent, &str)> {
+        self.0
+            .iter()
+            .map(|(key_event, key_handler)| (key_event, key_handler.description.as_str()))
+    }
+}
+
+impl fmt::Debug for KeyHandlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
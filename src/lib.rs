@@ -0,0 +1,27 @@
+mod batch_rename;
+mod clipboard;
+mod components;
+mod config;
+mod display_width;
+mod external_program;
+mod favorites;
+mod file_ops;
+mod gitignore_filter;
+mod help;
+mod human_size;
+mod key_handlers;
+mod navigation_history;
+mod paths;
+mod pinned;
+mod recent_dirs;
+mod scan;
+mod selection_hook;
+mod selection_list;
+mod selection_listeners;
+mod sorting;
+mod starred;
+mod tree_text;
+mod view_settings;
+
+pub use components::{App, DirectoryView, PreviewContent, Theme, preview_lines};
+pub use selection_listeners::SelectionChange;
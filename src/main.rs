@@ -1,11 +1,36 @@
-use std::{env, io};
+use std::{env, io, path};
 
-mod components;
-use components::App;
+use tui_file_explorer::App;
 
 fn main() -> io::Result<()> {
+    let (flags, paths): (Vec<_>, Vec<_>) = env::args_os()
+        .skip(1)
+        .partition(|arg| arg.to_str().is_some_and(|arg| arg.starts_with("--")));
+    let no_color = flags.iter().any(|flag| flag == "--no-color");
+    let read_only = flags.iter().any(|flag| flag == "--read-only");
+
+    // An optional path argument either opens that directory, or opens a file's parent with the file selected
+    let start_path = match paths.into_iter().next() {
+        Some(path) => path::absolute(path)?,
+        None => env::current_dir()?,
+    };
+
+    let mut app = if start_path.is_file() {
+        let mut app = App::new(start_path.parent().unwrap().to_path_buf());
+        app.select_entry_by_name(start_path.file_name().unwrap());
+        app
+    } else {
+        App::new(start_path)
+    };
+    if no_color {
+        app = app.without_colors();
+    }
+    if read_only {
+        app = app.read_only();
+    }
+
     let mut terminal = ratatui::init();
-    let app_result = App::new(env::current_dir().unwrap()).run(&mut terminal);
+    let app_result = app.run(&mut terminal);
     ratatui::restore();
     app_result
 }
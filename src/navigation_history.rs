@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+
+/// The directories navigated through, with a position that moves back and forward like a browser's
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NavigationHistory {
+    paths: Vec<PathBuf>,
+    position: usize,
+    /// Every directory arrived in this session in order, kept whatever is dropped from `paths`
+    visited: Vec<PathBuf>,
+}
+
+impl NavigationHistory {
+    /// Records navigating to `path`, dropping anything that could have been gone forward to
+    pub fn push(&mut self, path: &Path) {
+        self.arrived(path);
+        if self.current() == Some(path) {
+            return;
+        }
+        if !self.paths.is_empty() {
+            self.paths.truncate(self.position + 1);
+        }
+        self.paths.push(path.to_path_buf());
+        self.position = self.paths.len() - 1;
+    }
+
+    pub fn current(&self) -> Option<&Path> {
+        self.paths.get(self.position).map(PathBuf::as_path)
+    }
+
+    /// Steps back, returning the directory to go to
+    pub fn back(&mut self) -> Option<&Path> {
+        self.position = self.position.checked_sub(1)?;
+        self.current()
+    }
+
+    /// Records arriving in `path` for the session's script, which going back and forward does
+    /// without changing the history
+    pub fn arrived(&mut self, path: &Path) {
+        if self.visited.last().map(PathBuf::as_path) != Some(path) {
+            self.visited.push(path.to_path_buf());
+        }
+    }
+
+    /// A shell script with a `cd` to each directory arrived in this session, in order
+    pub fn cd_script(&self) -> String {
+        let mut script = "#!/bin/sh\n".to_string();
+        for path in &self.visited {
+            script.push_str(&format!("cd {}\n", shell_quote(&path.to_string_lossy())));
+        }
+        script
+    }
+
+    /// How many directory changes `cd_script` replays
+    pub fn visit_count(&self) -> usize {
+        self.visited.len()
+    }
+
+    /// Steps forward again after going back, returning the directory to go to
+    pub fn forward(&mut self) -> Option<&Path> {
+        if self.position + 1 >= self.paths.len() {
+            return None;
+        }
+        self.position += 1;
+        self.current()
+    }
+}
+
+/// Single quotes `text` for a POSIX shell, ending and restarting the quotes around any it holds
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn back_and_forward_step_through_the_history() {
+        let mut history = NavigationHistory::default();
+        history.push(Path::new("/a"));
+        history.push(Path::new("/b"));
+        history.push(Path::new("/c"));
+
+        assert_eq!(history.back(), Some(Path::new("/b")));
+        assert_eq!(history.back(), Some(Path::new("/a")));
+        assert_eq!(history.back(), None);
+        assert_eq!(history.forward(), Some(Path::new("/b")));
+        assert_eq!(history.forward(), Some(Path::new("/c")));
+        assert_eq!(history.forward(), None);
+    }
+
+    #[test]
+    fn new_navigation_drops_the_forward_history() {
+        let mut history = NavigationHistory::default();
+        history.push(Path::new("/a"));
+        history.push(Path::new("/b"));
+        history.back();
+        history.push(Path::new("/c"));
+
+        assert_eq!(history.forward(), None);
+        assert_eq!(history.back(), Some(Path::new("/a")));
+    }
+
+    #[test]
+    fn cd_script_keeps_every_arrival_in_order() {
+        let mut history = NavigationHistory::default();
+        history.push(Path::new("/a"));
+        history.push(Path::new("/a"));
+        history.push(Path::new("/a/it's here"));
+        history.back();
+        history.arrived(Path::new("/a"));
+        history.push(Path::new("/c"));
+
+        assert_eq!(
+            history.cd_script(),
+            "#!/bin/sh\ncd '/a'\ncd '/a/it'\\''s here'\ncd '/a'\ncd '/c'\n"
+        );
+        assert_eq!(history.visit_count(), 4);
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+
+/// `path` relative to `base`, or `path` unchanged if it isn't under `base`
+pub fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    match path.strip_prefix(base) {
+        Ok(relative_path) if relative_path.as_os_str().is_empty() => PathBuf::from("."),
+        Ok(relative_path) => relative_path.to_path_buf(),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// `path` without any trailing separator (apart from the root's own), so its last component and
+/// its parent come out the same however it was typed
+pub fn normalize_dir_path(path: PathBuf) -> PathBuf {
+    path.components().collect()
+}
+
+/// The absolute path of a directory as it's copied, without a trailing separator
+pub fn dir_path_text(path: &Path) -> String {
+    normalize_dir_path(path.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Whether `path` is a dotfile, which are hidden by convention
+pub fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.as_encoded_bytes().starts_with(b"."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_under_base_is_made_relative() {
+        assert_eq!(
+            relative_to(Path::new("/project/src/main.rs"), Path::new("/project")),
+            PathBuf::from("src/main.rs")
+        );
+        assert_eq!(
+            relative_to(Path::new("/project"), Path::new("/project")),
+            PathBuf::from(".")
+        );
+    }
+
+    #[test]
+    fn trailing_separators_are_stripped_except_from_root() {
+        // `PathBuf` equality ignores trailing separators, so compare the raw strings
+        let normalized = |path: &str| normalize_dir_path(PathBuf::from(path)).into_os_string();
+
+        assert_eq!(normalized("/home/user/"), "/home/user");
+        assert_eq!(normalized("/home//user//"), "/home/user");
+        assert_eq!(normalized("/"), "/");
+    }
+
+    #[test]
+    fn dir_path_text_drops_the_trailing_separator() {
+        assert_eq!(dir_path_text(Path::new("/home/user/")), "/home/user");
+        assert_eq!(dir_path_text(Path::new("/home/user")), "/home/user");
+        assert_eq!(dir_path_text(Path::new("/")), "/");
+    }
+
+    #[test]
+    fn path_outside_base_stays_absolute() {
+        assert_eq!(
+            relative_to(Path::new("/other/file.txt"), Path::new("/project")),
+            PathBuf::from("/other/file.txt")
+        );
+        // Only whole components count as being under the base
+        assert_eq!(
+            relative_to(Path::new("/project-old/file.txt"), Path::new("/project")),
+            PathBuf::from("/project-old/file.txt")
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+/// Whether any of `patterns` pins `path` to the top of its directory
+///
+/// An absolute pattern pins that exact path. Any other pattern is matched against the entry's
+/// name, where `*` stands for any run of characters and `?` for any one, and a trailing `/`
+/// only matches directories.
+pub fn is_pinned(path: &Path, patterns: &[String]) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+
+    patterns.iter().any(|pattern| {
+        if Path::new(pattern).is_absolute() {
+            return Path::new(pattern) == path;
+        }
+        match pattern.strip_suffix('/') {
+            Some(dir_pattern) => path.is_dir() && glob_matches(dir_pattern, name),
+            None => glob_matches(pattern, name),
+        }
+    })
+}
+
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Where the last `*` was, and how much of the name it has swallowed so far, to backtrack to
+    let mut star: Option<(usize, usize)> = None;
+    let (mut pattern_index, mut name_index) = (0, 0);
+    while name_index < name.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                star = Some((pattern_index, name_index));
+                pattern_index += 1;
+            }
+            Some(&char) if char == '?' || char == name[name_index] => {
+                pattern_index += 1;
+                name_index += 1;
+            }
+            _ => match star {
+                Some((star_index, swallowed_to)) => {
+                    star = Some((star_index, swallowed_to + 1));
+                    pattern_index = star_index + 1;
+                    name_index = swallowed_to + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[pattern_index..].iter().all(|&char| char == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn globs_match_whole_names() {
+        assert!(glob_matches("README*", "README.md"));
+        assert!(glob_matches("README*", "README"));
+        assert!(glob_matches("*.rs", "main.rs"));
+        assert!(glob_matches("a?c", "abc"));
+        assert!(glob_matches("*a*b", "xaybzb"));
+        assert!(!glob_matches("README*", "OLD_README"));
+        assert!(!glob_matches("*.rs", "main.rs.bak"));
+    }
+
+    #[test]
+    fn trailing_slash_only_pins_directories() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        std::fs::create_dir(tmp_dir.path().join(".git")).unwrap();
+        std::fs::write(tmp_dir.path().join("git"), "").unwrap();
+        let patterns = [".git/".to_string(), "git/".to_string()];
+
+        assert!(is_pinned(&tmp_dir.path().join(".git"), &patterns));
+        assert!(!is_pinned(&tmp_dir.path().join("git"), &patterns));
+    }
+
+    #[test]
+    fn absolute_patterns_pin_that_path_only() {
+        let patterns = ["/project/notes.txt".to_string()];
+
+        assert!(is_pinned(Path::new("/project/notes.txt"), &patterns));
+        assert!(!is_pinned(Path::new("/other/notes.txt"), &patterns));
+    }
+}
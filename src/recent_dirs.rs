@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+
+/// How many directories are remembered before the oldest are forgotten
+const MAX_RECENT_DIRS: usize = 30;
+
+/// Directories visited this session, most recent first and each listed only once
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RecentDirs {
+    paths: Vec<PathBuf>,
+}
+
+impl RecentDirs {
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Moves `path` to the front, dropping the oldest directory if there are too many
+    pub fn visit(&mut self, path: &Path) {
+        self.paths.retain(|recent| recent != path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(MAX_RECENT_DIRS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revisiting_moves_a_directory_to_the_front() {
+        let mut recent_dirs = RecentDirs::default();
+        recent_dirs.visit(Path::new("/a"));
+        recent_dirs.visit(Path::new("/b"));
+        recent_dirs.visit(Path::new("/a"));
+
+        assert_eq!(
+            recent_dirs.paths(),
+            [PathBuf::from("/a"), PathBuf::from("/b")]
+        );
+    }
+
+    #[test]
+    fn oldest_directories_are_forgotten() {
+        let mut recent_dirs = RecentDirs::default();
+        for index in 0..MAX_RECENT_DIRS + 5 {
+            recent_dirs.visit(&PathBuf::from(format!("/{index}")));
+        }
+
+        assert_eq!(recent_dirs.paths().len(), MAX_RECENT_DIRS);
+        assert_eq!(
+            recent_dirs.paths()[0],
+            PathBuf::from(format!("/{}", MAX_RECENT_DIRS + 4))
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+use crate::human_size::{SizeUnits, format_with_thousands_separator, human_size};
+
+/// Everything found below a scanned directory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScanTotals {
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+/// A recursive walk of a directory on a background thread, counting entries as it finds them.
+/// The walk stops early once the scan is dropped
+#[derive(Debug)]
+pub struct Scan {
+    path: PathBuf,
+    discovered: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+    receiver: Receiver<ScanTotals>,
+    totals: Option<ScanTotals>,
+}
+
+impl Scan {
+    pub fn start(path: PathBuf) -> Self {
+        let discovered = Arc::new(AtomicUsize::new(0));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+
+        let walk_path = path.clone();
+        let walk_discovered = Arc::clone(&discovered);
+        let walk_cancelled = Arc::clone(&cancelled);
+        thread::spawn(move || {
+            let bytes = walk(&walk_path, &walk_discovered, &walk_cancelled);
+            let entries = walk_discovered.load(Ordering::Relaxed);
+            // The receiver only goes away when the scan is no longer wanted
+            let _ = sender.send(ScanTotals { entries, bytes });
+        });
+
+        Scan {
+            path,
+            discovered,
+            cancelled,
+            receiver,
+            totals: None,
+        }
+    }
+
+    /// A scan of `path` that is never walked, with the count and totals given through what is
+    /// returned alongside it instead
+    #[cfg(test)]
+    pub(crate) fn fed_by_hand(path: PathBuf) -> (Self, Arc<AtomicUsize>, mpsc::Sender<ScanTotals>) {
+        let discovered = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::channel();
+        let scan = Scan {
+            path,
+            discovered: Arc::clone(&discovered),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            receiver,
+            totals: None,
+        };
+        (scan, discovered, sender)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Collects the totals if the walk has finished
+    pub fn poll(&mut self) {
+        if let Ok(totals) = self.receiver.try_recv() {
+            self.totals = Some(totals);
+        }
+    }
+
+    /// The live count while scanning, then the final totals
+    pub fn status(&self, units: SizeUnits) -> String {
+        match self.totals {
+            Some(ScanTotals { entries, bytes }) => format!(
+                "{} items, {}",
+                format_with_thousands_separator(entries),
+                human_size(bytes, units)
+            ),
+            None => format!(
+                "scanning… {}",
+                format_with_thousands_separator(self.discovered.load(Ordering::Relaxed))
+            ),
+        }
+    }
+}
+
+impl Drop for Scan {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Counts every entry below `dir_path` into `discovered`, returning the total size of the files.
+/// Gives up, with what it has so far, as soon as `cancelled` is set
+fn walk(dir_path: &Path, discovered: &AtomicUsize, cancelled: &AtomicBool) -> u64 {
+    let Ok(entries) = fs::read_dir(dir_path) else {
+        return 0;
+    };
+
+    entries
+        .take_while(|_| !cancelled.load(Ordering::Relaxed))
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            discovered.fetch_add(1, Ordering::Relaxed);
+            match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => walk(&entry.path(), discovered, cancelled),
+                Ok(metadata) => metadata.len(),
+                Err(_) => 0,
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::create_dir;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn status_shows_partial_count_then_totals() {
+        let (sender, receiver) = mpsc::channel();
+        let mut scan = Scan {
+            path: PathBuf::from("/does/not/exist"),
+            discovered: Arc::new(AtomicUsize::new(3412)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            receiver,
+            totals: None,
+        };
+
+        scan.poll();
+        assert_eq!(scan.status(SizeUnits::Binary), "scanning… 3,412");
+
+        sender
+            .send(ScanTotals {
+                entries: 5000,
+                bytes: 2048,
+            })
+            .unwrap();
+        scan.poll();
+        assert_eq!(scan.status(SizeUnits::Binary), "5,000 items, 2.0K");
+    }
+
+    #[test]
+    fn walk_counts_nested_entries_and_sizes() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        fs::write(tmp_dir.path().join("a.txt"), "12345").unwrap();
+        create_dir(tmp_dir.path().join("nested_dir")).unwrap();
+        fs::write(tmp_dir.path().join("nested_dir").join("b.txt"), "123").unwrap();
+
+        let discovered = AtomicUsize::new(0);
+        assert_eq!(
+            walk(tmp_dir.path(), &discovered, &AtomicBool::new(false)),
+            8
+        );
+        assert_eq!(discovered.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn dropped_scan_stops_walking() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        fs::write(tmp_dir.path().join("a.txt"), "12345").unwrap();
+
+        let scan = Scan::start(tmp_dir.path().to_path_buf());
+        let cancelled = Arc::clone(&scan.cancelled);
+        drop(scan);
+        assert!(cancelled.load(Ordering::Relaxed));
+
+        let discovered = AtomicUsize::new(0);
+        assert_eq!(walk(tmp_dir.path(), &discovered, &cancelled), 0);
+        assert_eq!(discovered.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Builds the hook command, with the selected path appended as its final argument
+pub fn hook_command(hook: &str, selected_path: &Path) -> Option<Command> {
+    let mut hook_parts = hook.split_whitespace();
+    let mut command = Command::new(hook_parts.next()?);
+    command.args(hook_parts).arg(selected_path);
+    Some(command)
+}
+
+/// Waits for the selection to settle for `delay` before reporting it, so that holding an arrow
+/// key doesn't run the hook for every entry passed over
+#[derive(Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    pending: Option<(PathBuf, Instant)>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Self {
+        Debouncer {
+            delay,
+            pending: None,
+        }
+    }
+
+    pub fn selection_changed(&mut self, selected_path: PathBuf, now: Instant) {
+        self.pending = Some((selected_path, now));
+    }
+
+    /// The settled selection, if there is one that hasn't already been returned
+    pub fn ready(&mut self, now: Instant) -> Option<PathBuf> {
+        match &self.pending {
+            Some((_, changed_at)) if now.duration_since(*changed_at) >= self.delay => {
+                self.pending.take().map(|(selected_path, _)| selected_path)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Runs an external command whenever the selection changes, collecting its output for the preview
+#[derive(Debug)]
+pub struct SelectionHook {
+    hook: String,
+    debouncer: Debouncer,
+    last_selected_path: Option<PathBuf>,
+    output_sender: Sender<(PathBuf, Vec<String>)>,
+    output_receiver: Receiver<(PathBuf, Vec<String>)>,
+    output: Option<(PathBuf, Vec<String>)>,
+}
+
+impl SelectionHook {
+    pub fn new(hook: String, debounce_delay: Duration) -> Self {
+        let (output_sender, output_receiver) = mpsc::channel();
+        SelectionHook {
+            hook,
+            debouncer: Debouncer::new(debounce_delay),
+            last_selected_path: None,
+            output_sender,
+            output_receiver,
+            output: None,
+        }
+    }
+
+    /// Called on every tick, starting the hook in the background once the selection has settled
+    pub fn update(&mut self, selected_path: Option<&Path>, now: Instant) {
+        if let Some(selected_path) = selected_path
+            && self.last_selected_path.as_deref() != Some(selected_path)
+        {
+            self.last_selected_path = Some(selected_path.to_path_buf());
+            self.debouncer
+                .selection_changed(selected_path.to_path_buf(), now);
+        }
+
+        if let Some(selected_path) = self.debouncer.ready(now)
+            && let Some(mut command) = hook_command(&self.hook, &selected_path)
+        {
+            let output_sender = self.output_sender.clone();
+            thread::spawn(move || {
+                let lines = match command.output() {
+                    Ok(output) => String::from_utf8_lossy(&output.stdout)
+                        .lines()
+                        .map(str::to_string)
+                        .collect(),
+                    Err(err) => vec![format!("Unable to run hook: {err}")],
+                };
+                // The receiver only goes away when the app is closing
+                let _ = output_sender.send((selected_path, lines));
+            });
+        }
+
+        while let Ok(output) = self.output_receiver.try_recv() {
+            self.output = Some(output);
+        }
+    }
+
+    /// The hook's latest output, if it was produced for `selected_path`
+    pub fn output_for(&self, selected_path: &Path) -> Option<&[String]> {
+        match &self.output {
+            Some((output_path, lines)) if output_path == selected_path => Some(lines),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    #[test]
+    fn hook_command_appends_selected_path() {
+        let command = hook_command("preview.sh --color", Path::new("/tmp/file.txt")).unwrap();
+
+        assert_eq!(command.get_program(), OsStr::new("preview.sh"));
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            vec![OsStr::new("--color"), OsStr::new("/tmp/file.txt")]
+        );
+        assert!(hook_command("  ", Path::new("/tmp/file.txt")).is_none());
+    }
+
+    #[test]
+    fn debouncer_only_reports_settled_selection() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::from_millis(200));
+
+        debouncer.selection_changed(PathBuf::from("a"), start);
+        debouncer.selection_changed(PathBuf::from("b"), start + Duration::from_millis(100));
+        assert_eq!(debouncer.ready(start + Duration::from_millis(250)), None);
+
+        assert_eq!(
+            debouncer.ready(start + Duration::from_millis(300)),
+            Some(PathBuf::from("b"))
+        );
+        // Only reported once
+        assert_eq!(debouncer.ready(start + Duration::from_millis(400)), None);
+    }
+}
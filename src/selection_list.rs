@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+/// Files collected for a batch action, kept in the order they were added and spanning directories
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SelectionList {
+    paths: Vec<PathBuf>,
+}
+
+impl SelectionList {
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.iter().any(|selected| selected == path)
+    }
+
+    /// Appends `path` unless it is already in the list, returning whether it was added
+    pub fn add(&mut self, path: &Path) -> bool {
+        if self.contains(path) {
+            false
+        } else {
+            self.paths.push(path.to_path_buf());
+            true
+        }
+    }
+
+    /// Removes `path`, keeping the order of everything else, returning whether it was present
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let length_before = self.paths.len();
+        self.paths.retain(|selected| selected != path);
+        self.paths.len() != length_before
+    }
+
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_from_different_directories_keep_insertion_order() {
+        let mut selection_list = SelectionList::default();
+
+        assert!(selection_list.add(Path::new("/b/second.txt")));
+        assert!(selection_list.add(Path::new("/a/first.txt")));
+        assert!(selection_list.add(Path::new("/c/nested/third.txt")));
+        // Adding again doesn't move or duplicate it
+        assert!(!selection_list.add(Path::new("/b/second.txt")));
+
+        assert_eq!(
+            selection_list.paths(),
+            [
+                PathBuf::from("/b/second.txt"),
+                PathBuf::from("/a/first.txt"),
+                PathBuf::from("/c/nested/third.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn removing_keeps_the_order_of_the_rest() {
+        let mut selection_list = SelectionList::default();
+        for path in ["/b/second.txt", "/a/first.txt", "/c/third.txt"] {
+            selection_list.add(Path::new(path));
+        }
+
+        assert!(selection_list.remove(Path::new("/a/first.txt")));
+        assert!(!selection_list.remove(Path::new("/a/first.txt")));
+
+        assert_eq!(
+            selection_list.paths(),
+            [
+                PathBuf::from("/b/second.txt"),
+                PathBuf::from("/c/third.txt")
+            ]
+        );
+    }
+}
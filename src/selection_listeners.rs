@@ -0,0 +1,35 @@
+use std::{fmt, path::PathBuf, rc::Rc};
+
+/// The entry under the cursor changing, from a cursor move, a change of directory or a refresh
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionChange {
+    /// `None` when the listing was empty
+    pub old_path: Option<PathBuf>,
+    /// `None` when the listing is now empty
+    pub new_path: Option<PathBuf>,
+}
+
+type SelectionListener = dyn Fn(&SelectionChange);
+
+/// Callbacks added by whoever embeds the app, run whenever the selection changes
+#[derive(Default)]
+pub struct SelectionListeners(Vec<Rc<SelectionListener>>);
+
+impl SelectionListeners {
+    pub fn register(&mut self, listener: impl Fn(&SelectionChange) + 'static) {
+        self.0.push(Rc::new(listener));
+    }
+
+    /// Runs every listener, in the order they were registered
+    pub fn notify(&self, selection_change: &SelectionChange) {
+        for listener in &self.0 {
+            listener(selection_change);
+        }
+    }
+}
+
+impl fmt::Debug for SelectionListeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SelectionListeners({})", self.0.len())
+    }
+}
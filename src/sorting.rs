@@ -0,0 +1,505 @@
+use std::{
+    borrow::Cow,
+    cell::OnceCell,
+    cmp::{Ordering, Reverse},
+    fmt, fs,
+    iter::Peekable,
+    path::{Path, PathBuf},
+    str::Chars,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::components::{MediaKind, media_kind};
+
+type CompareFn = dyn Fn(&SortEntry, &SortEntry) -> Ordering;
+
+/// Decides the order entries are listed in
+pub struct Comparator(Box<CompareFn>);
+
+impl Comparator {
+    pub fn new(compare: impl Fn(&Path, &Path) -> Ordering + 'static) -> Self {
+        Comparator(Box::new(move |a, b| compare(&a.path, &b.path)))
+    }
+
+    fn by_entry(compare: impl Fn(&SortEntry, &SortEntry) -> Ordering + 'static) -> Self {
+        Comparator(Box::new(compare))
+    }
+
+    pub fn compare(&self, a: &SortEntry, b: &SortEntry) -> Ordering {
+        (self.0)(a, b)
+    }
+}
+
+/// An entry being sorted, whose metadata is read the first time it's needed and then kept for
+/// the rest of the sort, rather than read again on every comparison
+#[derive(Debug)]
+pub struct SortEntry {
+    pub path: PathBuf,
+    symlink_metadata: OnceCell<Option<fs::Metadata>>,
+    /// What a symlink points to, only read for symlinks as anything else is its own target
+    target_metadata: OnceCell<Option<fs::Metadata>>,
+}
+
+impl SortEntry {
+    pub fn new(path: PathBuf) -> Self {
+        SortEntry {
+            path,
+            symlink_metadata: OnceCell::new(),
+            target_metadata: OnceCell::new(),
+        }
+    }
+
+    /// The entry's own metadata, without following it when it is a symlink
+    pub fn symlink_metadata(&self) -> Option<&fs::Metadata> {
+        self.symlink_metadata
+            .get_or_init(|| self.path.symlink_metadata().ok())
+            .as_ref()
+    }
+
+    fn metadata(&self) -> Option<&fs::Metadata> {
+        let symlink_metadata = self.symlink_metadata()?;
+        if !symlink_metadata.is_symlink() {
+            return Some(symlink_metadata);
+        }
+        self.target_metadata
+            .get_or_init(|| self.path.metadata().ok())
+            .as_ref()
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.metadata().is_some_and(fs::Metadata::is_dir)
+    }
+
+    /// How many bytes the entry takes up, or `None` when that can't be read
+    fn size(&self) -> Option<u64> {
+        Some(self.metadata()?.len())
+    }
+
+    /// When the entry was last modified, or `None` when that can't be read
+    fn modified(&self) -> Option<SystemTime> {
+        self.metadata()?.modified().ok()
+    }
+}
+
+impl AsRef<Path> for SortEntry {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Default for Comparator {
+    /// Orders entries by their path
+    fn default() -> Self {
+        SortMode::default().comparator(SortTiebreaker::default())
+    }
+}
+
+impl fmt::Debug for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Comparator")
+    }
+}
+
+/// The built in orders entries can be listed in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortMode {
+    /// Byte by byte on the path, so uppercase names come before lowercase ones
+    #[default]
+    Path,
+    /// Case-insensitive, with runs of digits compared by value, and directories mixed in with files
+    AlphaNaturalMixed,
+    /// Directories, then images, audio, video and other files, each ordered by extension
+    Type,
+    /// Most recently modified first
+    Modified,
+    /// Largest first, directories by the size of their own entry rather than their contents
+    Size,
+}
+
+impl SortMode {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "path" => Some(SortMode::Path),
+            "alpha_natural_mixed" => Some(SortMode::AlphaNaturalMixed),
+            "type" => Some(SortMode::Type),
+            "modified" => Some(SortMode::Modified),
+            "size" => Some(SortMode::Size),
+            _ => None,
+        }
+    }
+
+    /// What `parse` reads this order from
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            SortMode::Path => "path",
+            SortMode::AlphaNaturalMixed => "alpha_natural_mixed",
+            SortMode::Type => "type",
+            SortMode::Modified => "modified",
+            SortMode::Size => "size",
+        }
+    }
+
+    /// Orders entries this way, settling entries it finds equal with `tiebreaker`
+    pub fn comparator(self, tiebreaker: SortTiebreaker) -> Comparator {
+        match self {
+            SortMode::Path => Comparator::new(|a, b| a.cmp(b)),
+            SortMode::AlphaNaturalMixed => Comparator::by_entry(move |a, b| {
+                natural_cmp(&file_name(&a.path), &file_name(&b.path))
+                    .then_with(|| tiebreaker.compare(a, b))
+            }),
+            SortMode::Type => Comparator::by_entry(move |a, b| {
+                TypeGroup::of(a)
+                    .cmp(&TypeGroup::of(b))
+                    .then_with(|| a.path.extension().cmp(&b.path.extension()))
+                    .then_with(|| tiebreaker.compare(a, b))
+            }),
+            SortMode::Modified => Comparator::by_entry(move |a, b| {
+                Reverse(a.modified())
+                    .cmp(&Reverse(b.modified()))
+                    .then_with(|| tiebreaker.compare(a, b))
+            }),
+            SortMode::Size => Comparator::by_entry(move |a, b| {
+                Reverse(a.size())
+                    .cmp(&Reverse(b.size()))
+                    .then_with(|| tiebreaker.compare(a, b))
+            }),
+        }
+    }
+
+    /// The order `s` switches to, going round name, size and modification time. The configured
+    /// order takes the place of the name order when it is neither of the others, so that it can be
+    /// come back to
+    pub(crate) fn next(self, configured: SortMode) -> Self {
+        let first = match configured {
+            SortMode::Size | SortMode::Modified => SortMode::Path,
+            configured => configured,
+        };
+        match self {
+            SortMode::Size => SortMode::Modified,
+            SortMode::Modified => first,
+            SortMode::Path | SortMode::AlphaNaturalMixed | SortMode::Type => SortMode::Size,
+        }
+    }
+
+    /// Shown in the title bar, after "sorted by"
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            SortMode::Path => "name",
+            SortMode::AlphaNaturalMixed => "natural name",
+            SortMode::Type => "type",
+            SortMode::Modified => "modified",
+            SortMode::Size => "size",
+        }
+    }
+
+    /// Which way the order runs, `↓` for the largest or newest first
+    pub(crate) fn arrow(self) -> char {
+        match self {
+            SortMode::Path | SortMode::AlphaNaturalMixed | SortMode::Type => '↑',
+            SortMode::Modified | SortMode::Size => '↓',
+        }
+    }
+
+    /// The group `path` falls in when listed in this order, for the orders that group entries
+    pub(crate) fn group_label(self, path: &Path, now: SystemTime) -> Option<&'static str> {
+        let entry = SortEntry::new(path.to_path_buf());
+        match self {
+            SortMode::Path | SortMode::AlphaNaturalMixed | SortMode::Size => None,
+            SortMode::Type => Some(TypeGroup::of(&entry).label()),
+            SortMode::Modified => Some(age_label(entry.modified(), now)),
+        }
+    }
+
+    /// Where the group `entry` falls in comes in the listing, for the orders that group entries
+    pub(crate) fn group_rank(self, entry: &SortEntry, now: SystemTime) -> Option<u8> {
+        match self {
+            SortMode::Path | SortMode::AlphaNaturalMixed | SortMode::Size => None,
+            SortMode::Type => Some(TypeGroup::of(entry) as u8),
+            SortMode::Modified => Some(AgeGroup::of(entry.modified(), now) as u8),
+        }
+    }
+}
+
+/// How entries the sort order finds equal, such as files of the same size, are ordered
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SortTiebreaker {
+    /// Case-insensitive on the name
+    #[default]
+    Name,
+    /// Most recently modified first
+    Modified,
+}
+
+impl SortTiebreaker {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "name" => Some(SortTiebreaker::Name),
+            "modified" => Some(SortTiebreaker::Modified),
+            _ => None,
+        }
+    }
+
+    /// Falls back on the path, so that no two entries are ever left equal
+    pub fn compare(self, a: &SortEntry, b: &SortEntry) -> Ordering {
+        match self {
+            SortTiebreaker::Name => file_name(&a.path)
+                .to_lowercase()
+                .cmp(&file_name(&b.path).to_lowercase()),
+            SortTiebreaker::Modified => Reverse(a.modified()).cmp(&Reverse(b.modified())),
+        }
+        .then_with(|| a.path.cmp(&b.path))
+    }
+}
+
+/// The kinds of entry the type order lists together, in the order they are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum TypeGroup {
+    Directories,
+    Images,
+    Audio,
+    Video,
+    OtherFiles,
+}
+
+impl TypeGroup {
+    fn of(entry: &SortEntry) -> Self {
+        if entry.is_dir() {
+            return TypeGroup::Directories;
+        }
+        match media_kind(&entry.path) {
+            Some(MediaKind::Image) => TypeGroup::Images,
+            Some(MediaKind::Audio) => TypeGroup::Audio,
+            Some(MediaKind::Video) => TypeGroup::Video,
+            None => TypeGroup::OtherFiles,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TypeGroup::Directories => "Directories",
+            TypeGroup::Images => "Images",
+            TypeGroup::Audio => "Audio",
+            TypeGroup::Video => "Video",
+            TypeGroup::OtherFiles => "Other files",
+        }
+    }
+}
+
+/// How long before `now` a modification was, counted in whole days in UTC
+fn age_label(modified: Option<SystemTime>, now: SystemTime) -> &'static str {
+    AgeGroup::of(modified, now).label()
+}
+
+/// How long before now an entry was modified, in the order the modified order lists them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum AgeGroup {
+    Today,
+    Yesterday,
+    LastWeek,
+    LastMonth,
+    Older,
+    Unknown,
+}
+
+impl AgeGroup {
+    fn of(modified: Option<SystemTime>, now: SystemTime) -> Self {
+        let Some(modified) = modified else {
+            return AgeGroup::Unknown;
+        };
+        let day_of = |time: SystemTime| {
+            time.duration_since(UNIX_EPOCH)
+                .map_or(0, |since_epoch| since_epoch.as_secs() / 86_400)
+        };
+
+        match day_of(now).saturating_sub(day_of(modified)) {
+            0 => AgeGroup::Today,
+            1 => AgeGroup::Yesterday,
+            2..7 => AgeGroup::LastWeek,
+            7..30 => AgeGroup::LastMonth,
+            _ => AgeGroup::Older,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            AgeGroup::Today => "Today",
+            AgeGroup::Yesterday => "Yesterday",
+            AgeGroup::LastWeek => "Last week",
+            AgeGroup::LastMonth => "Last month",
+            AgeGroup::Older => "Older",
+            AgeGroup::Unknown => "Unknown",
+        }
+    }
+}
+
+fn file_name(path: &Path) -> Cow<'_, str> {
+    path.file_name().unwrap_or_default().to_string_lossy()
+}
+
+/// Compares case-insensitively, treating each run of digits as a single number so `2` sorts before `10`
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char)) if a_char.is_ascii_digit() && b_char.is_ascii_digit() => {
+                let a_number = take_digits(&mut a);
+                let b_number = take_digits(&mut b);
+                let a_value = a_number.trim_start_matches('0');
+                let b_value = b_number.trim_start_matches('0');
+                let ordering = a_value
+                    .len()
+                    .cmp(&b_value.len())
+                    .then_with(|| a_value.cmp(b_value));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(a_char), Some(b_char)) => {
+                let ordering = a_char.to_lowercase().cmp(b_char.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Write, path::PathBuf, time::Duration};
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn sorted(paths: Vec<PathBuf>, comparator: &Comparator) -> Vec<PathBuf> {
+        let mut entries: Vec<_> = paths.into_iter().map(SortEntry::new).collect();
+        entries.sort_by(|a, b| comparator.compare(a, b));
+        entries.into_iter().map(|entry| entry.path).collect()
+    }
+
+    #[test]
+    fn alpha_natural_mixed_interleaves_dirs_and_orders_numbers_by_value() {
+        let paths = vec![
+            PathBuf::from("/tmp/img10.txt"),
+            PathBuf::from("/tmp/Folder2/"),
+            PathBuf::from("/tmp/img2.txt"),
+        ];
+
+        let comparator = SortMode::AlphaNaturalMixed.comparator(SortTiebreaker::Name);
+        assert_eq!(
+            sorted(paths, &comparator),
+            vec![
+                PathBuf::from("/tmp/Folder2/"),
+                PathBuf::from("/tmp/img2.txt"),
+                PathBuf::from("/tmp/img10.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn same_size_files_are_ordered_by_the_tiebreaker() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let write = |name: &str, contents: &str, modified: SystemTime| {
+            let path = tmp_dir.path().join(name);
+            File::create(&path)
+                .and_then(|mut file| {
+                    file.write_all(contents.as_bytes())?;
+                    file.set_modified(modified)
+                })
+                .unwrap();
+            path
+        };
+        let day = Duration::from_secs(86_400);
+        let big = write("big.txt", "0123456789", UNIX_EPOCH + day);
+        let charlie = write("charlie.txt", "12345", UNIX_EPOCH + day * 3);
+        let alpha = write("Alpha.txt", "12345", UNIX_EPOCH + day);
+        let bravo = write("bravo.txt", "12345", UNIX_EPOCH + day * 2);
+        let paths = vec![charlie.clone(), bravo.clone(), big.clone(), alpha.clone()];
+
+        let by_name = SortMode::Size.comparator(SortTiebreaker::Name);
+        assert_eq!(
+            sorted(paths.clone(), &by_name),
+            vec![big.clone(), alpha.clone(), bravo.clone(), charlie.clone()]
+        );
+
+        let by_modified = SortMode::Size.comparator(SortTiebreaker::Modified);
+        assert_eq!(
+            sorted(paths, &by_modified),
+            vec![big, charlie, bravo, alpha]
+        );
+    }
+
+    #[test]
+    fn cycle_comes_back_to_the_configured_order() {
+        let cycle = |configured: SortMode| {
+            let mut sort_mode = configured;
+            (0..3)
+                .map(|_| {
+                    sort_mode = sort_mode.next(configured);
+                    sort_mode
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            cycle(SortMode::Path),
+            [SortMode::Size, SortMode::Modified, SortMode::Path]
+        );
+        assert_eq!(
+            cycle(SortMode::AlphaNaturalMixed),
+            [
+                SortMode::Size,
+                SortMode::Modified,
+                SortMode::AlphaNaturalMixed
+            ]
+        );
+        assert_eq!(
+            cycle(SortMode::Type),
+            [SortMode::Size, SortMode::Modified, SortMode::Type]
+        );
+        assert_eq!(
+            cycle(SortMode::Modified),
+            [SortMode::Path, SortMode::Size, SortMode::Modified]
+        );
+    }
+
+    #[test]
+    fn ages_are_counted_in_whole_days() {
+        let day = Duration::from_secs(86_400);
+        let now = UNIX_EPOCH + day * 100 + Duration::from_secs(60);
+
+        assert_eq!(age_label(Some(UNIX_EPOCH + day * 100), now), "Today");
+        assert_eq!(
+            age_label(Some(now - Duration::from_secs(120)), now),
+            "Yesterday"
+        );
+        assert_eq!(age_label(Some(now - day * 6), now), "Last week");
+        assert_eq!(age_label(Some(now - day * 29), now), "Last month");
+        assert_eq!(age_label(Some(now - day * 30), now), "Older");
+        assert_eq!(age_label(None, now), "Unknown");
+    }
+
+    #[test]
+    fn natural_cmp_ignores_case_and_leading_zeros() {
+        assert_eq!(natural_cmp("b", "A"), Ordering::Greater);
+        assert_eq!(natural_cmp("file007", "file7"), Ordering::Equal);
+        assert_eq!(natural_cmp("file9", "file10"), Ordering::Less);
+        assert_eq!(natural_cmp("file", "file1"), Ordering::Less);
+    }
+}
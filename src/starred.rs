@@ -0,0 +1,110 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::config::config_dir;
+
+/// Starred files, persisted one absolute path per line
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Starred {
+    paths: HashSet<PathBuf>,
+    storage_path: Option<PathBuf>,
+}
+
+impl Starred {
+    pub fn load_default() -> Self {
+        match config_dir() {
+            Some(config_dir) => Self::load(config_dir.join("starred")),
+            None => Self::default(),
+        }
+    }
+
+    /// Loads the stars stored at `storage_path`, starting with none if nothing has been stored yet
+    pub fn load(storage_path: PathBuf) -> Self {
+        let paths = fs::read_to_string(&storage_path)
+            .map(|contents| {
+                contents
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(PathBuf::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Starred {
+            paths,
+            storage_path: Some(storage_path),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(storage_path) = &self.storage_path else {
+            return Ok(());
+        };
+        if let Some(parent) = storage_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut paths: Vec<&str> = self.paths.iter().filter_map(|path| path.to_str()).collect();
+        paths.sort();
+        let contents: String = paths.iter().map(|path| format!("{path}\n")).collect();
+        fs::write(storage_path, contents)
+    }
+
+    pub fn paths(&self) -> &HashSet<PathBuf> {
+        &self.paths
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.contains(path)
+    }
+
+    /// Stars `path`, or unstars it if it was already starred, returning whether it is now starred
+    pub fn toggle(&mut self, path: &Path) -> bool {
+        if self.paths.remove(path) {
+            false
+        } else {
+            self.paths.insert(path.to_path_buf());
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn toggling_twice_unstars() {
+        let mut starred = Starred::default();
+
+        assert!(starred.toggle(Path::new("/some/file.txt")));
+        assert!(starred.contains(Path::new("/some/file.txt")));
+        assert!(!starred.toggle(Path::new("/some/file.txt")));
+        assert!(starred.paths().is_empty());
+    }
+
+    #[test]
+    fn stars_persist_between_loads() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let storage_path = tmp_dir.path().join("nested").join("starred");
+
+        let mut starred = Starred {
+            paths: HashSet::new(),
+            storage_path: Some(storage_path.clone()),
+        };
+        starred.toggle(Path::new("/some/file.txt"));
+        starred.toggle(Path::new("/another/file.md"));
+        starred.save().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&storage_path).unwrap(),
+            "/another/file.md\n/some/file.txt\n"
+        );
+        assert_eq!(Starred::load(storage_path), starred);
+    }
+}
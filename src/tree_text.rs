@@ -0,0 +1,109 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use itertools::sorted;
+
+use crate::paths::is_hidden;
+
+/// A `tree` style drawing of `root` and everything up to `max_depth` levels below it
+pub fn tree_text(root: &Path, max_depth: usize, show_hidden: bool) -> String {
+    let root_name = root
+        .file_name()
+        .unwrap_or(root.as_os_str())
+        .to_string_lossy();
+    let mut lines = vec![root_name.to_string()];
+    push_children(&mut lines, root, "", max_depth, show_hidden);
+    lines.join("\n")
+}
+
+fn push_children(
+    lines: &mut Vec<String>,
+    dir_path: &Path,
+    indent: &str,
+    remaining_depth: usize,
+    show_hidden: bool,
+) {
+    if remaining_depth == 0 {
+        return;
+    }
+    // Unreadable directories are drawn without any children
+    let Ok(entries) = fs::read_dir(dir_path) else {
+        return;
+    };
+
+    let children: Vec<PathBuf> = sorted(
+        entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| show_hidden || !is_hidden(path)),
+    )
+    .collect();
+
+    for (index, child) in children.iter().enumerate() {
+        let is_last = index == children.len() - 1;
+        let (branch, child_indent) = if is_last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        let name = child.file_name().unwrap_or_default().to_string_lossy();
+        lines.push(format!("{indent}{branch}{name}"));
+
+        if child.is_dir() {
+            push_children(
+                lines,
+                child,
+                &format!("{indent}{child_indent}"),
+                remaining_depth - 1,
+                show_hidden,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{File, create_dir};
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn small_tree() -> TempDir {
+        let tmp_dir = TempDir::new("project").unwrap();
+        create_dir(tmp_dir.path().join("src")).unwrap();
+        File::create(tmp_dir.path().join("src").join("main.rs")).unwrap();
+        create_dir(tmp_dir.path().join("src").join("components")).unwrap();
+        // Deeper than the requested depth, so not drawn
+        File::create(tmp_dir.path().join("src").join("components").join("app.rs")).unwrap();
+        File::create(tmp_dir.path().join("Cargo.toml")).unwrap();
+        File::create(tmp_dir.path().join(".gitignore")).unwrap();
+        tmp_dir
+    }
+
+    #[test]
+    fn tree_is_drawn_to_the_given_depth() {
+        let tmp_dir = small_tree();
+        let root_name = tmp_dir.path().file_name().unwrap().to_str().unwrap();
+
+        assert_eq!(
+            tree_text(tmp_dir.path(), 2, true),
+            format!(
+                "{root_name}\n\
+                 ├── .gitignore\n\
+                 ├── Cargo.toml\n\
+                 └── src\n    \
+                     ├── components\n    \
+                     └── main.rs"
+            )
+        );
+    }
+
+    #[test]
+    fn hidden_entries_can_be_left_out() {
+        let tmp_dir = small_tree();
+
+        assert!(!tree_text(tmp_dir.path(), 1, false).contains(".gitignore"));
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{config::config_dir, sorting::SortMode};
+
+/// The view toggles that can be chosen separately for each directory
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ViewSettings {
+    pub summarize_extensions: bool,
+    pub hide_gitignored: bool,
+    pub sort_mode: SortMode,
+    pub dirs_first: bool,
+    pub hide_dotfiles: bool,
+}
+
+impl ViewSettings {
+    /// Parses space separated `key=value` pairs, skipping anything unrecognised
+    fn parse(line: &str) -> Self {
+        let mut settings = ViewSettings::default();
+        for (key, value) in line.split(' ').filter_map(|pair| pair.split_once('=')) {
+            if key == "sort_mode" {
+                if let Some(sort_mode) = SortMode::parse(value) {
+                    settings.sort_mode = sort_mode;
+                }
+                continue;
+            }
+            let Ok(value) = value.parse() else {
+                continue;
+            };
+            match key {
+                "summarize_extensions" => settings.summarize_extensions = value,
+                "hide_gitignored" => settings.hide_gitignored = value,
+                "dirs_first" => settings.dirs_first = value,
+                "hide_dotfiles" => settings.hide_dotfiles = value,
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    fn format(&self) -> String {
+        format!(
+            "summarize_extensions={} hide_gitignored={} sort_mode={} dirs_first={} hide_dotfiles={}",
+            self.summarize_extensions,
+            self.hide_gitignored,
+            self.sort_mode.name(),
+            self.dirs_first,
+            self.hide_dotfiles
+        )
+    }
+}
+
+/// The view settings last used in each directory, persisted one directory per line as the
+/// settings, a tab, then the directory's absolute path
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DirViewSettings {
+    settings: HashMap<PathBuf, ViewSettings>,
+    storage_path: Option<PathBuf>,
+}
+
+impl DirViewSettings {
+    pub fn load_default() -> Self {
+        match config_dir() {
+            Some(config_dir) => Self::load(config_dir.join("view_settings")),
+            None => Self::default(),
+        }
+    }
+
+    /// Loads the settings stored at `storage_path`, starting empty if nothing has been stored yet
+    pub fn load(storage_path: PathBuf) -> Self {
+        let settings = fs::read_to_string(&storage_path)
+            .map(|contents| {
+                contents
+                    .lines()
+                    .filter_map(|line| line.split_once('\t'))
+                    .map(|(settings, path)| (PathBuf::from(path), ViewSettings::parse(settings)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        DirViewSettings {
+            settings,
+            storage_path: Some(storage_path),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(storage_path) = &self.storage_path else {
+            return Ok(());
+        };
+        if let Some(parent) = storage_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut lines: Vec<String> = self
+            .settings
+            .iter()
+            .filter_map(|(path, settings)| {
+                Some(format!("{}\t{}\n", settings.format(), path.to_str()?))
+            })
+            .collect();
+        lines.sort();
+        fs::write(storage_path, lines.concat())
+    }
+
+    /// The settings last used in `dir_path`, if they were ever changed there
+    pub fn get(&self, dir_path: &Path) -> Option<ViewSettings> {
+        self.settings.get(dir_path).copied()
+    }
+
+    pub fn set(&mut self, dir_path: &Path, settings: ViewSettings) {
+        self.settings.insert(dir_path.to_path_buf(), settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn settings_persist_between_loads() {
+        let tmp_dir = TempDir::new("tmp_dir").unwrap();
+        let storage_path = tmp_dir.path().join("nested").join("view_settings");
+
+        let mut dir_view_settings = DirViewSettings {
+            settings: HashMap::new(),
+            storage_path: Some(storage_path.clone()),
+        };
+        dir_view_settings.set(
+            Path::new("/home/me/Downloads"),
+            ViewSettings {
+                summarize_extensions: true,
+                hide_gitignored: false,
+                sort_mode: SortMode::Size,
+                dirs_first: true,
+                hide_dotfiles: false,
+            },
+        );
+        dir_view_settings.set(
+            Path::new("/home/me/a dir with spaces"),
+            ViewSettings {
+                summarize_extensions: false,
+                hide_gitignored: true,
+                sort_mode: SortMode::AlphaNaturalMixed,
+                dirs_first: false,
+                hide_dotfiles: true,
+            },
+        );
+        dir_view_settings.save().unwrap();
+
+        assert_eq!(DirViewSettings::load(storage_path), dir_view_settings);
+    }
+
+    #[test]
+    fn unrecognised_settings_are_skipped() {
+        assert_eq!(
+            ViewSettings::parse(
+                "hide_gitignored=true colour=blue summarize_extensions=maybe sort_mode=random"
+            ),
+            ViewSettings {
+                summarize_extensions: false,
+                hide_gitignored: true,
+                ..ViewSettings::default()
+            }
+        );
+    }
+}